@@ -1,6 +1,6 @@
 use crate::{
     core::handle::Handle, logging, 
-    module::auto_launch,
+    module::{auto_launch, hotkey::HotkeyCommand, ics_feed::DEFAULT_ICS_FEED_PORT, rest_api::DEFAULT_API_PORT},
     utils::{
         dirs,
         help::{read_yaml, save_yaml},
@@ -8,6 +8,8 @@ use crate::{
     }
 };
 
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -17,6 +19,17 @@ pub struct Config {
     pub enable_auto_launch: Option<bool>,
     pub silent_launch: Option<bool>,
     pub language: Option<String>,
+    /// 是否启用本地 .ics 日历订阅服务
+    pub ics_feed_enabled: Option<bool>,
+    pub ics_feed_port: Option<u16>,
+    /// 是否启用本地 HTTP API，需携带 `Authorization: Bearer <api_token>` 访问
+    pub api_enabled: Option<bool>,
+    pub api_port: Option<u16>,
+    pub api_token: Option<String>,
+    /// 命令动作允许执行的程序，可填写完整路径或文件名；为空时不限制
+    pub command_allowlist: Option<Vec<String>>,
+    /// 全局快捷键，键为快捷键（如 `CmdOrCtrl+Shift+D`），值为触发的操作
+    pub hotkeys: Option<BTreeMap<String, HotkeyCommand>>,
 }
 
 impl Config {
@@ -56,6 +69,13 @@ impl Config {
             enable_auto_launch: Some(false),
             silent_launch: Some(false),
             language: Some("zh".to_string()),
+            ics_feed_enabled: Some(false),
+            ics_feed_port: Some(DEFAULT_ICS_FEED_PORT),
+            api_enabled: Some(false),
+            api_port: Some(DEFAULT_API_PORT),
+            api_token: None,
+            command_allowlist: None,
+            hotkeys: None,
         }
     }
 
@@ -79,6 +99,13 @@ impl Config {
         patch!(enable_auto_launch);
         patch!(silent_launch);
         patch!(language);
+        patch!(ics_feed_enabled);
+        patch!(ics_feed_port);
+        patch!(api_enabled);
+        patch!(api_port);
+        patch!(api_token);
+        patch!(command_allowlist);
+        patch!(hotkeys);
     }
 }
 
@@ -1,29 +1,55 @@
-use super::cmd;
-use tauri::{AppHandle, RunEvent};
+use super::{
+    cli::{self, CliCommand},
+    cmd,
+};
+use tauri::{async_runtime, AppHandle, RunEvent};
 #[cfg(desktop)]
 use tauri_plugin_notification::NotificationExt;
 use crate::core::handle::Handle;
 
 /// Setup plugins for the Tauri builder
 pub fn setup_plugins(builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<tauri::Wry> {
-    #[allow(unused_mut)]
     let mut builder = builder
         .plugin(tauri_plugin_window_state::Builder::new().build())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_single_instance::init(|app, _, _| {
-            app.notification()
-                .builder()
-                .title("The program is already running. Please do not start it again!")
-                .icon("ducker")
-                .show()
-                .unwrap();
-        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_autostart::Builder::new().app_name("ducker").build());
-    
+    // 命令行指令在调用方进程中执行，不能被单实例插件拦截；只有刷新通知需要转发给正在运行的实例
+    let single_instance = match cli::command_from_env() {
+        Ok(Some(command)) => matches!(command, CliCommand::Refresh { .. }),
+        _ => true,
+    };
+    if single_instance {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            match CliCommand::parse(argv.get(1..).unwrap_or_default()) {
+                Ok(Some(command @ CliCommand::Refresh { .. })) => {
+                    let app = app.clone();
+                    async_runtime::spawn(async move {
+                        if let Err(e) = cli::execute(&command, &app).await {
+                            logging!(error, Type::Cmd, true, "刷新命令行修改的数据失败: {:#}", e);
+                        }
+                    });
+                }
+                Ok(Some(command)) => {
+                    logging!(warn, Type::Cmd, true, "忽略转发的命令行指令: {:?}", command);
+                }
+                Ok(None) => {
+                    app.notification()
+                        .builder()
+                        .title("The program is already running. Please do not start it again!")
+                        .icon("ducker")
+                        .show()
+                        .unwrap();
+                }
+                Err(e) => {
+                    logging!(warn, Type::Cmd, true, "命令行参数无效: {:#}", e);
+                }
+            }
+        }));
+    }
 
     builder
 }
@@ -53,27 +79,39 @@ pub fn generate_handlers() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool +
         cmd::window::close_window,
         cmd::window::destroy_window,
         // Actions
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::execute_actions,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::execute_single_action,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::create_action,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::get_action,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::delete_action,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::update_action,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::get_all_actions,
-        #[cfg(target_os = "windows")]
+        #[cfg(desktop)]
         cmd::action::select_file,
+        #[cfg(desktop)]
+        cmd::action::get_action_runs,
+        #[cfg(desktop)]
+        cmd::action::get_action_run,
+        #[cfg(desktop)]
+        cmd::action::clear_action_runs,
+        #[cfg(desktop)]
+        cmd::action::get_running_actions,
+        #[cfg(desktop)]
+        cmd::action::cancel_action_run,
         // Tasks
         cmd::task::create_task,
         cmd::task::gen_random_task_id,
         cmd::task::update_task,
         cmd::task::update_task_status,
+        cmd::task::set_task_blockers,
+        cmd::task::snooze_task_reminder,
         cmd::task::delete_task,
         cmd::task::get_task,
         cmd::task::get_all_tasks,
@@ -90,6 +128,20 @@ pub fn generate_handlers() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool +
         cmd::task::create_periodic_task,
         cmd::task::update_periodic_task,
         cmd::task::delete_periodic_task,
+        // Tags
+        cmd::tag::get_all_tags,
+        cmd::tag::create_tag,
+        cmd::tag::update_tag,
+        cmd::tag::delete_tag,
+        cmd::tag::set_task_tags,
+        // Search
+        cmd::search::search,
+        // Bundle
+        cmd::bundle::export_bundle,
+        cmd::bundle::import_bundle,
+        // Calendar
+        cmd::calendar::export_ics,
+        cmd::calendar::import_ics,
         // Config
         cmd::config::save_config,
         cmd::config::get_config,
@@ -103,8 +155,9 @@ pub async fn check_periodic_task() {
     use crate::{
         get_app_handle,logging,
         schema::action::Action,
-        schema::AppState,
+        schema::{AppState, RunTrigger},
         service::execute::execute_plural_actions,
+        feat::action::ActionVars,
         store::module::{ActionManager, PeriodicTaskManager, TaskManager},
         utils::date::is_today,utils::logging::Type,
     };
@@ -144,7 +197,7 @@ pub async fn check_periodic_task() {
                             .update_periodic_tasks_last_run(&prepared_tasks_ids)    
                             .unwrap();
                         drop(db_guard);
-                        let r = execute_plural_actions(collected_actions).await;
+                        let r = execute_plural_actions(collected_actions, RunTrigger::StartupPeriodic, &ActionVars::default()).await;
                         if let Err(e) = r {
                             logging!(error, Type::Database,true, "执行周期性任务的所有动作失败{:?}",e);
                         } else {
@@ -168,7 +221,9 @@ pub async fn check_periodic_task() {
 
 use crate::{utils::logging::Type, logging};
 #[cfg(target_os = "macos")]
-use crate::log_err;
+use crate::AppHandleManager;
+#[cfg(target_os = "macos")]
+use tauri::Manager;
 
 #[allow(unused_variables)]
 pub fn app_event_handle(app_handle: &AppHandle, event: RunEvent){
@@ -197,6 +252,10 @@ match event {
         tauri::RunEvent::ExitRequested { api, code, .. } => {
             if code.is_none() {
                 api.prevent_exit();
+            } else {
+                crate::module::ics_feed::IcsFeed::global().stop();
+                crate::module::rest_api::RestApi::global().stop();
+                cli::clear_instance_mark();
             }
         }
         tauri::RunEvent::WindowEvent { label, event, .. } => {
@@ -219,32 +278,6 @@ match event {
                         .unwrap();
                     let _ = window.hide();
                 }
-                tauri::WindowEvent::Focused(true) => {
-                    #[cfg(target_os = "macos")]
-                    {
-                        log_err!(hotkey::Hotkey::global().register("CMD+Q", "quit"));
-                        log_err!(hotkey::Hotkey::global().register("CMD+W", "hide"));
-                    }
-
-                    #[cfg(not(target_os = "macos"))]
-                    {
-                        // log_err!(hotkey::Hotkey::global().register("Control+Q", "quit"));
-                    };
-                }
-                tauri::WindowEvent::Focused(false) => {
-                    #[cfg(target_os = "macos")]
-                    {
-                        log_err!(hotkey::Hotkey::global().unregister("CMD+Q"));
-                        log_err!(hotkey::Hotkey::global().unregister("CMD+W"));
-                    }
-                }
-                tauri::WindowEvent::Destroyed => {
-                    #[cfg(target_os = "macos")]
-                    {
-                        log_err!(hotkey::Hotkey::global().unregister("CMD+Q"));
-                        log_err!(hotkey::Hotkey::global().unregister("CMD+W"));
-                    }
-                }
                 _ => {}
             }
             // }
@@ -0,0 +1,449 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{
+    core::timer::Timer,
+    feat::action::execute_action_by_id,
+    logging,
+    schema::{AppState, ExportOptions, RunTrigger, TaskData},
+    service::{bundle::BundleFormat, dependency, hub::Hub, ical},
+    store::{
+        db::Database,
+        module::{ActionManager, BundleManager, PeriodicTaskManager, TaskManager},
+    },
+    utils::{date::to_datetime_str, dirs::APP_ID, logging::Type},
+};
+
+pub const USAGE: &str = "用法:
+  ducker task add <名称> [--due <时间>] [--reminder <时间>] [--parent <任务id>]
+  ducker task list [--all]
+  ducker task done <任务id>
+  ducker action run <动作名称或id>
+  ducker export <文件路径>    (.json/.yaml 导出数据，.ics 导出日历)
+
+时间格式: 2025-06-01 18:00、2025-06-01 18:00:00 或 RFC 3339";
+
+/// 命令行子命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    Help,
+    TaskAdd {
+        name: String,
+        due: Option<String>,
+        reminder: Option<String>,
+        parent: Option<String>,
+    },
+    TaskList {
+        all: bool,
+    },
+    TaskDone {
+        id: String,
+    },
+    ActionRun {
+        target: String,
+    },
+    Export {
+        path: PathBuf,
+    },
+    /// 内部使用：命令行修改数据后经单实例插件转发给正在运行的实例，使其刷新调度与界面
+    Refresh {
+        done: Option<String>,
+    },
+}
+
+impl CliCommand {
+    /// 解析不含程序名的参数，第一个参数不是子命令时返回 None，按正常方式启动界面
+    pub fn parse(args: &[String]) -> Result<Option<Self>> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let command = match args.as_slice() {
+            ["help" | "--help" | "-h", ..] => CliCommand::Help,
+            ["task", "add", rest @ ..] => {
+                let mut name = None;
+                let mut due = None;
+                let mut reminder = None;
+                let mut parent = None;
+                let mut rest = rest.iter();
+                while let Some(arg) = rest.next() {
+                    let mut value = |flag: &str| {
+                        rest.next()
+                            .map(|value| value.to_string())
+                            .ok_or_else(|| anyhow!("{} 缺少参数值", flag))
+                    };
+                    match *arg {
+                        "--due" => due = Some(parse_time(&value("--due")?)?),
+                        "--reminder" => reminder = Some(parse_time(&value("--reminder")?)?),
+                        "--parent" => parent = Some(value("--parent")?),
+                        flag if flag.starts_with("--") => bail!("未知参数: {}", flag),
+                        _ if name.is_some() => bail!("任务名称只能有一个，含空格时请加引号"),
+                        _ => name = Some(arg.to_string()),
+                    }
+                }
+                CliCommand::TaskAdd {
+                    name: name.ok_or_else(|| anyhow!("缺少任务名称"))?,
+                    due,
+                    reminder,
+                    parent,
+                }
+            }
+            ["task", "list"] => CliCommand::TaskList { all: false },
+            ["task", "list", "--all"] => CliCommand::TaskList { all: true },
+            ["task", "done", id] => CliCommand::TaskDone { id: id.to_string() },
+            ["action", "run", target] => CliCommand::ActionRun {
+                target: target.to_string(),
+            },
+            ["export", path] => CliCommand::Export { path: PathBuf::from(path) },
+            ["refresh"] => CliCommand::Refresh { done: None },
+            ["refresh", "--done", id] => CliCommand::Refresh {
+                done: Some(id.to_string()),
+            },
+            ["task" | "action" | "export", ..] => bail!("参数无效\n\n{}", USAGE),
+            _ => return Ok(None),
+        };
+        Ok(Some(command))
+    }
+
+    /// 执行动作需要应用上下文（通知、确认对话框等），刷新需要转发给正在运行的实例，其他命令可以直接操作数据库
+    pub fn needs_app(&self) -> bool {
+        matches!(self, CliCommand::ActionRun { .. } | CliCommand::Refresh { .. })
+    }
+
+    /// 执行成功后通知正在运行的实例刷新时使用的参数，不修改数据的命令返回 None
+    fn refresh_args(&self) -> Option<Vec<String>> {
+        match self {
+            CliCommand::TaskAdd { .. } | CliCommand::ActionRun { .. } => Some(vec!["refresh".to_string()]),
+            CliCommand::TaskDone { id } => Some(vec!["refresh".to_string(), "--done".to_string(), id.clone()]),
+            _ => None,
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(to_datetime_str(datetime.timestamp()));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            let datetime = Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .ok_or_else(|| anyhow!("本地时间不存在: {}", value))?;
+            return Ok(to_datetime_str(datetime.timestamp()));
+        }
+    }
+    bail!("时间格式无效: {}", value)
+}
+
+/// 从进程参数中解析子命令
+pub fn command_from_env() -> Result<Option<CliCommand>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    CliCommand::parse(&args)
+}
+
+/// 在数据库上执行命令，返回要输出的文本
+fn execute_on_database(command: &CliCommand, db: &Database) -> Result<String> {
+    match command {
+        CliCommand::Help => Ok(USAGE.to_string()),
+        CliCommand::TaskAdd {
+            name,
+            due,
+            reminder,
+            parent,
+        } => {
+            if let Some(parent) = parent {
+                db.get_task(parent)
+                    .with_context(|| format!("父任务不存在: {}", parent))?;
+            }
+            let record = db.create_task(&TaskData {
+                id: None,
+                name: name.clone(),
+                value: None,
+                completed: false,
+                auto: false,
+                parent_id: parent.clone(),
+                periodic: None,
+                actions: Vec::new(),
+                created_at: None,
+                due_to: due.clone(),
+                reminder: reminder.clone(),
+                tags: None,
+                blocked_by: None,
+            })?;
+            Ok(record.id)
+        }
+        CliCommand::TaskList { all } => {
+            let mut tasks = if *all {
+                db.get_all_tasks()?
+            } else {
+                db.get_tasks_by_status(false)?
+            };
+            tasks.sort_by_key(|task| task.due_to);
+            Ok(tasks
+                .iter()
+                .map(|task| {
+                    format!(
+                        "{}\t{}\t{}\t{}",
+                        task.id,
+                        if task.completed { "x" } else { " " },
+                        to_datetime_str(task.due_to),
+                        task.name
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+        CliCommand::TaskDone { id } => {
+            db.get_task(id).with_context(|| format!("任务不存在: {}", id))?;
+            db.update_task_status(id, true)?;
+            Ok(format!("任务 {} 已完成", id))
+        }
+        CliCommand::Export { path } => {
+            let is_calendar = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"));
+            let content = if is_calendar {
+                ical::render_calendar(
+                    &db.get_all_tasks()?,
+                    &db.get_enabled_periodic_tasks()?,
+                    Local::now().timestamp(),
+                )
+            } else {
+                let bundle = db.export_bundle(&ExportOptions::default())?;
+                BundleFormat::from_path(path).serialize(&bundle)?
+            };
+            std::fs::write(path, content)
+                .with_context(|| format!("写入导出文件失败: {}", path.display()))?;
+            Ok(format!("已导出到 {}", path.display()))
+        }
+        CliCommand::ActionRun { .. } | CliCommand::Refresh { .. } => bail!("该命令需要应用上下文"),
+    }
+}
+
+fn find_action(db: &Database, target: &str) -> Result<String> {
+    if let Ok(action) = db.get_action(target) {
+        return Ok(action.id);
+    }
+    let matches: Vec<_> = db
+        .get_all_actions()?
+        .into_iter()
+        .filter(|action| action.name == target)
+        .collect();
+    match matches.as_slice() {
+        [action] => Ok(action.id.clone()),
+        [] => bail!("动作不存在: {}", target),
+        _ => bail!("存在多个名为 {} 的动作，请改用 id", target),
+    }
+}
+
+fn data_dir() -> Result<PathBuf> {
+    Ok(::dirs::data_dir()
+        .ok_or_else(|| anyhow!("无法获取数据目录"))?
+        .join(APP_ID))
+}
+
+/// 正在运行的实例在数据目录中记录自己的进程 id，命令行据此决定是否转发给它
+const INSTANCE_FILE: &str = "ducker.pid";
+
+pub fn mark_instance_running(data_dir: &Path) {
+    if let Err(e) = std::fs::write(data_dir.join(INSTANCE_FILE), std::process::id().to_string()) {
+        logging!(warn, Type::Cmd, "写入实例标记失败: {}", e);
+    }
+}
+
+/// 只清除自己写入的标记，命令行启动的无界面实例退出时不能清除正在运行的实例的标记
+pub fn clear_instance_mark() {
+    let Ok(data_dir) = data_dir() else {
+        return;
+    };
+    let file = data_dir.join(INSTANCE_FILE);
+    let marked = std::fs::read_to_string(&file)
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok());
+    if marked == Some(std::process::id()) {
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+/// 是否有正在运行的实例；判断有误时仍会经过单实例插件，只是多启动一次应用上下文
+pub fn instance_running() -> bool {
+    data_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(INSTANCE_FILE)).ok())
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(process_alive)
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "windows")]
+fn process_alive(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .creation_flags(0x08000000)
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+}
+
+/// 发布版在 Windows 上没有控制台，输出前附加到启动程序的终端，否则输出会被丢弃
+#[cfg(target_os = "windows")]
+pub fn attach_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    // 已有控制台或不是从终端启动时会失败，忽略即可
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn attach_console() {}
+
+/// 不启动界面，直接在数据库上执行命令；执行动作需要应用上下文，不能在这里执行
+pub fn run_direct(command: &CliCommand) -> Result<String> {
+    if *command == CliCommand::Help {
+        return Ok(USAGE.to_string());
+    }
+    let data_dir = data_dir()?;
+    if !data_dir.join("ducker.db").exists() {
+        bail!("数据库不存在，请先启动一次 Ducker: {}", data_dir.display());
+    }
+    let db = Database::new(data_dir)?;
+    execute_on_database(command, &db)
+}
+
+/// 在应用上下文中执行命令：执行动作在命令行启动的无界面实例中进行，刷新在正在运行的实例中进行
+pub async fn execute(command: &CliCommand, app_handle: &AppHandle) -> Result<String> {
+    logging!(info, Type::Cmd, "执行命令行指令: {:?}", command);
+    let state = app_handle.state::<AppState>();
+    let output = match command {
+        CliCommand::ActionRun { target } => {
+            let id = find_action(&state.db.lock(), target)?;
+            execute_action_by_id(app_handle, &id, RunTrigger::Cli)
+                .await
+                .map_err(|e| anyhow!(e))?
+        }
+        CliCommand::Refresh { done } => {
+            refresh(done.as_deref(), app_handle).await?;
+            String::new()
+        }
+        _ => execute_on_database(command, &state.db.lock())?,
+    };
+    Ok(output)
+}
+
+/// 其他进程修改数据后刷新调度与界面，完成任务时同时执行因此解除阻塞的任务
+async fn refresh(done: Option<&str>, app_handle: &AppHandle) -> Result<()> {
+    if let Some(id) = done {
+        dependency::on_tasks_status_changed(vec![id.to_string()], true).await?;
+    } else {
+        Hub::global().refresh().await;
+        Timer::global().refresh()?;
+    }
+    app_handle.emit("task-changed", None::<()>)?;
+    Ok(())
+}
+
+/// 命令执行成功后，若有正在运行的实例，启动一个刷新命令交给单实例插件转发给它；
+/// 数据已经写入，通知失败只输出警告
+pub fn notify_running_instance(command: &CliCommand) {
+    let Some(args) = command.refresh_args() else {
+        return;
+    };
+    if !instance_running() {
+        return;
+    }
+    let status = std::env::current_exe()
+        .context("无法获取程序路径")
+        .and_then(|exe| {
+            std::process::Command::new(exe)
+                .args(&args)
+                .status()
+                .context("启动刷新命令失败")
+        });
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("通知正在运行的 Ducker 刷新失败: {}", status),
+        Err(e) => eprintln!("通知正在运行的 Ducker 刷新失败: {:#}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<CliCommand>> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        CliCommand::parse(&args)
+    }
+
+    #[test]
+    fn subcommands_are_parsed() {
+        assert_eq!(parse(&[]).unwrap(), None);
+        assert_eq!(parse(&["--minimized"]).unwrap(), None);
+        assert_eq!(
+            parse(&["task", "add", "写周报", "--parent", "task1"]).unwrap(),
+            Some(CliCommand::TaskAdd {
+                name: "写周报".to_string(),
+                due: None,
+                reminder: None,
+                parent: Some("task1".to_string()),
+            })
+        );
+        assert_eq!(
+            parse(&["task", "list", "--all"]).unwrap(),
+            Some(CliCommand::TaskList { all: true })
+        );
+        assert_eq!(
+            parse(&["action", "run", "备份"]).unwrap(),
+            Some(CliCommand::ActionRun { target: "备份".to_string() })
+        );
+
+        assert!(parse(&["action", "run", "备份"]).unwrap().unwrap().needs_app());
+        let done = parse(&["task", "done", "task1"]).unwrap().unwrap();
+        assert!(!done.needs_app());
+        assert_eq!(
+            done.refresh_args(),
+            Some(vec!["refresh".to_string(), "--done".to_string(), "task1".to_string()])
+        );
+        assert_eq!(
+            parse(&["refresh", "--done", "task1"]).unwrap(),
+            Some(CliCommand::Refresh { done: Some("task1".to_string()) })
+        );
+        assert!(parse(&["refresh"]).unwrap().unwrap().refresh_args().is_none());
+
+        let export = parse(&["export", "tasks.yaml"]).unwrap().unwrap();
+        assert!(!export.needs_app() && export.refresh_args().is_none());
+        assert_eq!(export, CliCommand::Export { path: PathBuf::from("tasks.yaml") });
+    }
+
+    #[test]
+    fn task_add_parses_times_and_rejects_bad_input() {
+        let Some(CliCommand::TaskAdd { due, .. }) =
+            parse(&["task", "add", "会议", "--due", "2025-06-01 18:00"]).unwrap()
+        else {
+            panic!("应解析为 task add");
+        };
+        let expected = Local.with_ymd_and_hms(2025, 6, 1, 18, 0, 0).unwrap();
+        assert_eq!(due, Some(to_datetime_str(expected.timestamp())));
+
+        assert!(parse(&["task", "add"]).is_err());
+        assert!(parse(&["task", "add", "会议", "--due", "明天"]).is_err());
+        assert!(parse(&["task", "add", "会议", "--due"]).is_err());
+        assert!(parse(&["task", "remove", "x"]).is_err());
+    }
+}
@@ -1,13 +1,11 @@
 use crate::{
-    feat::action::execute_action, store::module::ActionManager,
+    feat::{action::execute_action, run}, store::module::{ActionManager, ActionRunManager},
     logging,
-    schema::{Action, AppState},
+    schema::{Action, ActionRunFilter, ActionRunRecord, AppState},
     utils::logging::Type,
 };
 
 use anyhow::Result;
-use tokio::time::timeout;
-use std::time::Duration;
 use tauri::{async_runtime, AppHandle, State};
 
 #[tauri::command]
@@ -26,67 +24,15 @@ pub async fn execute_actions(state: State<'_, AppState>,actions: Vec<Action>) ->
         }
         // 根据等待时间决定执行模式
         if action.wait > 0 {
-            // 同步执行 - 等待任务完成后再执行下一个
-            let max_retries = action.retry.unwrap_or(0);
-            let mut retry_count = 0;
-            let mut last_error = String::new();
-            let timeout_duration = Duration::from_secs(action.timeout.unwrap_or(30));
-
-            while retry_count <= max_retries {
-                match timeout(timeout_duration, execute_single_action(action.clone())).await {
-                    Ok(result) => {
-                        match result {
-                            Ok(_) => {
-                                logging!(info, Type::Service, true, "任务 {} 执行成功", action.name);
-                                break;
-                            }
-                            Err(e) => {
-                                logging!(error, Type::Service, true, "任务 {} 执行失败:{}", action.name, e);
-                                last_error = e.to_string();
-
-                                if max_retries <= 0 {
-                                    return Err(format!("任务 {} 执行失败:{}", action.name, &last_error));
-                                }
-                                retry_count += 1;
-                                if retry_count <= max_retries {
-                                    logging!(
-                                        info,
-                                        Type::Service,
-                                        true,
-                                        "任务执行失败，正在重试 ({}/{}): {}",
-                                        retry_count,
-                                        max_retries,
-                                        &last_error
-                                    );
-                                    tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-                                }
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        last_error = format!("任务执行超时（{}秒）", timeout_duration.as_secs());
-                        if max_retries <= 0 {
-                            return Err(format!("任务执行失败:{}", &last_error));
-                        }
-                        retry_count += 1;
-                        if retry_count <= max_retries {
-                            logging!(
-                                info,
-                                Type::Service,
-                                true,
-                                "任务执行超时，正在重试 ({}/{}): {}",
-                                retry_count,
-                                max_retries,
-                                &last_error
-                            );
-                            tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-                        }
-                    }
+            // 同步执行 - 等待任务完成后再执行下一个，重试与超时由动作自身的设置决定
+            match execute_single_action(action.clone()).await {
+                Ok(_) => {
+                    logging!(info, Type::Service, true, "任务 {} 执行成功", action.name);
+                }
+                Err(e) => {
+                    logging!(error, Type::Service, true, "任务 {} 执行失败:{}", action.name, e);
+                    return Err(format!("任务 {} 执行失败:{}", action.name, e));
                 }
-            }
-
-            if retry_count > max_retries {
-                return Err(format!("任务执行失败，已达到最大重试次数: {}", &last_error));
             }
 
             // 等待指定时间后再执行下一个任务
@@ -220,3 +166,37 @@ pub async fn get_all_actions(state: State<'_, AppState>) -> Result<Vec<Action>,
         Err(e) => Err(e.to_string()),
     }
 }
+
+#[tauri::command]
+pub async fn get_action_runs(
+    state: State<'_, AppState>,
+    filter: Option<ActionRunFilter>,
+) -> Result<Vec<ActionRunRecord>, String> {
+    let db = state.db.lock();
+    db.get_action_runs(&filter.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_action_run(state: State<'_, AppState>, id: &str) -> Result<ActionRunRecord, String> {
+    let db = state.db.lock();
+    db.get_action_run(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_action_runs(state: State<'_, AppState>, before: i64) -> Result<usize, String> {
+    let db = state.db.lock();
+    db.delete_action_runs_before(before).map_err(|e| e.to_string())
+}
+
+/// 正在执行的命令，包括独立运行的命令
+#[tauri::command]
+pub async fn get_running_actions() -> Result<Vec<run::RunInfo>, String> {
+    Ok(run::list())
+}
+
+/// 终止正在执行的命令及其子进程，`run_id` 来自 `action-output` 事件或 [`get_running_actions`]
+#[tauri::command]
+pub async fn cancel_action_run(run_id: &str) -> Result<(), String> {
+    run::cancel(run_id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+
+use crate::{
+    logging,
+    schema::{ExportOptions, ImportOptions, ImportReport},
+    service::bundle,
+    utils::logging::Type,
+};
+
+#[tauri::command]
+pub async fn export_bundle(path: String, options: Option<ExportOptions>) -> Result<(), String> {
+    bundle::export_to_file(&PathBuf::from(path), &options.unwrap_or_default())
+        .map(|_| ())
+        .map_err(|e| {
+            logging!(error, Type::Service, true, "导出数据失败: {:#}", e);
+            format!("{:#}", e)
+        })
+}
+
+#[tauri::command]
+pub async fn import_bundle(
+    path: String,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport, String> {
+    bundle::import_from_file(&PathBuf::from(path), &options.unwrap_or_default())
+        .await
+        .map_err(|e| {
+            logging!(error, Type::Service, true, "导入数据失败: {:#}", e);
+            format!("{:#}", e)
+        })
+}
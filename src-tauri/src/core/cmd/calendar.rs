@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use crate::{
+    logging,
+    schema::{ImportOptions, ImportReport},
+    service::ical,
+    utils::logging::Type,
+};
+
+#[tauri::command]
+pub async fn export_ics(path: String) -> Result<(), String> {
+    ical::export_to_file(&PathBuf::from(path)).map_err(|e| {
+        logging!(error, Type::Service, true, "导出日历失败: {:#}", e);
+        format!("{:#}", e)
+    })
+}
+
+#[tauri::command]
+pub async fn import_ics(
+    path: String,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport, String> {
+    ical::import_from_file(&PathBuf::from(path), &options.unwrap_or_default())
+        .await
+        .map_err(|e| {
+            logging!(error, Type::Service, true, "导入日历失败: {:#}", e);
+            format!("{:#}", e)
+        })
+}
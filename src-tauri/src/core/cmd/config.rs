@@ -1,4 +1,8 @@
-use crate::config::Config;
+use crate::{
+    config::Config, core::handle::Handle, logging, logging_error,
+    module::{hotkey::{self, Hotkey}, ics_feed::IcsFeed, rest_api::RestApi},
+    utils::logging::Type,
+};
 
 #[tauri::command]
 pub async fn save_config() {
@@ -12,11 +16,24 @@ pub async fn get_config() -> Config {
     c
 }
 
+/// 快捷键无效或互相冲突时不保存配置
 #[tauri::command]
-pub async fn update_config(config: Config) -> Config {
+pub async fn update_config(config: Config) -> Result<Config, String> {
+    if let Some(hotkeys) = &config.hotkeys {
+        hotkey::validate_hotkeys(hotkeys).map_err(|e| e.to_string())?;
+    }
     let original = Config::global();
     let mut config_guard = original.lock();
     config_guard.patch_config(config.clone());
     config_guard.save().ok();
-    config
+    drop(config_guard);
+    logging_error!(Type::Service, IcsFeed::global().apply_config());
+    logging_error!(Type::Service, RestApi::global().apply_config());
+    if config.hotkeys.is_some() {
+        if let Err(e) = Hotkey::global().apply_config() {
+            logging!(error, Type::Hotkey, true, "{}", e);
+            Handle::notice_message("Error", e.to_string());
+        }
+    }
+    Ok(config)
 }
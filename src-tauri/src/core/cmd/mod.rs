@@ -1,6 +1,11 @@
-#[cfg(target_os = "windows")]
+#[cfg(desktop)]
 pub mod action;
+pub mod bundle;
 pub mod config;
 pub mod task;
 pub mod window;
-pub mod sound;
\ No newline at end of file
+pub mod search;
+pub mod sound;
+pub mod tag;
+pub mod calendar;
+
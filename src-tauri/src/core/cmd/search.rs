@@ -0,0 +1,24 @@
+use tauri::State;
+
+use crate::{
+    logging,
+    schema::{AppState, SearchHit},
+    store::module::SearchManager,
+    utils::logging::Type,
+};
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+#[tauri::command]
+pub async fn search(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let db = state.db.lock();
+    db.search(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .map_err(|e| {
+            logging!(error, Type::Database, true, "搜索失败: {:?}", e);
+            e.to_string()
+        })
+}
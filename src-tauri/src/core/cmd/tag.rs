@@ -0,0 +1,62 @@
+use tauri::State;
+
+use crate::{
+    logging,
+    schema::{AppState, Tag, TagData},
+    store::module::TagManager,
+    utils::logging::Type,
+};
+
+#[tauri::command]
+pub async fn get_all_tags(state: State<'_, AppState>) -> Result<Vec<Tag>, String> {
+    let db = state.db.lock();
+    db.get_all_tags().map_err(|e| {
+        logging!(error, Type::Database, true, "获取标签失败: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub async fn create_tag(state: State<'_, AppState>, tag: TagData) -> Result<Tag, String> {
+    let db = state.db.lock();
+    db.create_tag(&tag).map_err(|e| {
+        logging!(error, Type::Database, true, "创建标签失败: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub async fn update_tag(
+    state: State<'_, AppState>,
+    id: &str,
+    tag: TagData,
+) -> Result<Tag, String> {
+    let db = state.db.lock();
+    db.update_tag(id, &tag).map_err(|e| {
+        logging!(error, Type::Database, true, "更新标签失败: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub async fn delete_tag(state: State<'_, AppState>, id: &str) -> Result<(), String> {
+    let db = state.db.lock();
+    db.delete_tag(id).map_err(|e| {
+        logging!(error, Type::Database, true, "删除标签失败: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub async fn set_task_tags(
+    state: State<'_, AppState>,
+    id: &str,
+    tag_ids: Vec<String>,
+) -> Result<(), String> {
+    let db = state.db.lock();
+    db.set_task_tags(id, &tag_ids).map_err(|e| {
+        logging!(error, Type::Database, true, "设置任务标签失败: {:?}", e);
+        e.to_string()
+    })
+}
+
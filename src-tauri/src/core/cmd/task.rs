@@ -1,20 +1,54 @@
 use crate::{
+    core::timer::Timer,
+    feat::action::ActionVars,
     logging, schema::{
         task::{TaskData, TaskRecord, TaskView},
-        AppState, PeriodicTask, PeriodicTaskData,
-    }, service::{execute::execute_plural_actions, periodic}, store::module::{
-        PeriodicTaskManager, TaskManager
-    }, utils::{
+        AppState, PeriodicTask, PeriodicTaskData, RunTrigger, TagFilter,
+    }, service::{dependency, execute::execute_plural_actions, hub::Hub, periodic, reminder}, store::{module::{
+        DependencyManager, PeriodicTaskManager, TagManager, TaskManager
+    }}, utils::{
+        date::to_datetime_str,
         help::random_string, 
         logging::Type
     }
 };
+use std::collections::{HashMap, HashSet};
 use tauri::State;
 
+/// 按标签筛选时返回匹配的任务 id 集合，未指定标签时返回 None 表示不筛选
+fn tagged_task_ids(
+    state: &AppState,
+    tags: Option<TagFilter>,
+) -> Result<Option<HashSet<String>>, String> {
+    let Some(filter) = tags.filter(|filter| !filter.is_empty()) else {
+        return Ok(None);
+    };
+    let db = state.db.lock();
+    match db.get_task_ids_by_tags(&filter) {
+        Ok(ids) => Ok(Some(ids)),
+        Err(e) => {
+            logging!(error, Type::Database, true, "按标签筛选任务失败: {:?}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+fn retain_tagged(
+    mut tasks: HashMap<i64, Vec<TaskView>>,
+    ids: Option<HashSet<String>>,
+) -> HashMap<i64, Vec<TaskView>> {
+    if let Some(ids) = ids {
+        for views in tasks.values_mut() {
+            views.retain(|view| ids.contains(&view.id));
+        }
+        tasks.retain(|_, views| !views.is_empty());
+    }
+    tasks
+}
+
 #[tauri::command]
 pub async fn create_task(state: State<'_, AppState>, task: TaskData) -> Result<String, String> {
-    let db = state.db.lock();
-    let res = db.create_task(&task);
+    let res = state.db.lock().create_task(&task);
     match res {
         Ok(data) => Ok(data.id),
         Err(e) => {
@@ -40,7 +74,9 @@ pub async fn update_task(
             }
         };
         
-        if let Some(periodic_id) = &record.periodic {
+        // 先更新任务，前置任务等校验失败时不会误删周期规则
+        let res = db.update_task(id, &task);
+        if let (Ok(_), Some(periodic_id)) = (&res, &record.periodic) {
             // 如果从周期任务转换为非周期任务，需要删除周期规则
             if task.periodic.is_none() {
                 if let Err(e) = db.delete_periodic_task(periodic_id) {
@@ -49,7 +85,7 @@ pub async fn update_task(
                 }
             }
         }
-        db.update_task(id, &task)
+        res
     };
     match res {
         Ok(data) => Ok(data),
@@ -66,10 +102,18 @@ pub async fn update_task_status(
     id: &str,
     completed: bool,
 ) -> Result<bool, String> {
-    let db = state.db.lock();
-    let res = db.update_task_status(id, completed);
+    let res = {
+        let db = state.db.lock();
+        db.update_task_status(id, completed)
+    };
     match res {
-        Ok(data) => Ok(data),
+        Ok(data) => {
+            // 通知前端并调度因此解除阻塞的任务
+            if let Err(e) = dependency::on_tasks_status_changed(vec![id.to_string()], completed).await {
+                logging!(error, Type::Service, true, "处理任务依赖失败: {:?}", e);
+            }
+            Ok(data)
+        }
         Err(e) => {
             println!("更新任务状态失败: {:?}", e);
             Err(e.to_string())
@@ -77,6 +121,46 @@ pub async fn update_task_status(
     }
 }
 
+/// 整体替换任务的前置任务，存在循环依赖时拒绝
+#[tauri::command]
+pub async fn set_task_blockers(
+    state: State<'_, AppState>,
+    id: &str,
+    blocker_ids: Vec<String>,
+) -> Result<(), String> {
+    let res = {
+        let db = state.db.lock();
+        db.set_task_blockers(id, &blocker_ids)
+    };
+    match res {
+        Ok(_) => {
+            // 阻塞状态变化会影响今天的自动任务调度
+            Hub::global().refresh().await;
+            if let Err(e) = Timer::global().refresh() {
+                logging!(error, Type::Timer, true, "刷新定时任务失败: {:?}", e);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            logging!(error, Type::Database, true, "设置任务依赖失败: {:?}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 稍后提醒：将任务的提醒时间推迟若干分钟，返回新的提醒时间
+#[tauri::command]
+pub async fn snooze_task_reminder(id: &str, minutes: Option<i64>) -> Result<String, String> {
+    let minutes = minutes.unwrap_or(reminder::DEFAULT_SNOOZE_MINUTES);
+    match reminder::snooze_task_reminder(id, minutes).await {
+        Ok(ts) => Ok(to_datetime_str(ts)),
+        Err(e) => {
+            logging!(error, Type::Database, true, "推迟任务提醒失败: {:?}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn delete_task(state: State<'_, AppState>, id: &str) -> Result<(), String> {
     
@@ -117,7 +201,8 @@ pub async fn execute_task(state: State<'_, AppState>, id: &str) -> Result<(), St
     match res {
         Ok(data) => {
             let task = TaskView::try_from((&data, state.inner())).unwrap();
-            if let Err(e) = execute_plural_actions(task.actions.unwrap_or_default()).await {
+            let vars = ActionVars::for_task(&task);
+            if let Err(e) = execute_plural_actions(task.actions.unwrap_or_default(), RunTrigger::Manual, &vars).await {
                 logging!(error, Type::Database, "执行任务失败: {:?}", e);
                 return Err(e.to_string());
             }
@@ -131,7 +216,11 @@ pub async fn execute_task(state: State<'_, AppState>, id: &str) -> Result<(), St
 }
 
 #[tauri::command]
-pub async fn get_all_tasks(state: State<'_, AppState>) -> Result<Vec<TaskView>, String> {
+pub async fn get_all_tasks(
+    state: State<'_, AppState>,
+    tags: Option<TagFilter>,
+) -> Result<Vec<TaskView>, String> {
+    let ids = tagged_task_ids(state.inner(), tags)?;
     // 快速获取数据并立即释放数据库锁
     let data = {
         let db = state.db.lock();   
@@ -141,6 +230,9 @@ pub async fn get_all_tasks(state: State<'_, AppState>) -> Result<Vec<TaskView>,
         Ok(task_records) => {
             let mut tasks = Vec::new();
             for task in task_records {
+                if ids.as_ref().is_some_and(|ids| !ids.contains(&task.id)) {
+                    continue;
+                }
                 tasks.push(TaskView::try_from((&task, state.inner())).unwrap());
             }
             Ok(tasks)
@@ -156,7 +248,9 @@ pub async fn get_tasks_by_date_range(
     state: State<'_, AppState>,
     start_date: i64,
     end_date: i64,
+    tags: Option<TagFilter>,
 ) -> Result<Vec<TaskView>, String> {
+    let ids = tagged_task_ids(state.inner(), tags)?;
     logging!(info, Type::Database, true, "获取任务范围: {:?}", (start_date, end_date));
     
     // 快速获取数据并立即释放数据库锁
@@ -170,6 +264,9 @@ pub async fn get_tasks_by_date_range(
             logging!(info, Type::Database, true, "获取任务范围成功: {:?}", task_records.len());
             let mut tasks = Vec::new();
             for task in task_records {
+                if ids.as_ref().is_some_and(|ids| !ids.contains(&task.id)) {
+                    continue;
+                }
                 tasks.push(TaskView::try_from((&task, state.inner())).unwrap());
             }
             Ok(tasks)
@@ -185,12 +282,19 @@ pub async fn get_tasks_by_date_range(
 pub async fn get_tasks_by_status(
     state: State<'_, AppState>,
     completed: bool,
+    tags: Option<TagFilter>,
 ) -> Result<Vec<TaskRecord>, String> {
+    let ids = tagged_task_ids(state.inner(), tags)?;
     let db = state.db.lock();
 
     let res = db.get_tasks_by_status(completed);
     match res {
-        Ok(data) => Ok(data),
+        Ok(mut data) => {
+            if let Some(ids) = ids {
+                data.retain(|task| ids.contains(&task.id));
+            }
+            Ok(data)
+        }
         Err(e) => {
             println!("获取任务失败: {:?}", e);
             Err(e.to_string())
@@ -385,13 +489,21 @@ pub async fn delete_periodic_task(state: State<'_, AppState>, id: String) -> Res
 // }
 
 #[tauri::command]
-pub async fn get_weekly_tasks() -> Result<std::collections::HashMap<i64, Vec<TaskView>>, String> {
-    Ok(periodic::get_weekly_tasks())
+pub async fn get_weekly_tasks(
+    state: State<'_, AppState>,
+    tags: Option<TagFilter>,
+) -> Result<HashMap<i64, Vec<TaskView>>, String> {
+    let ids = tagged_task_ids(state.inner(), tags)?;
+    Ok(retain_tagged(periodic::get_weekly_tasks(), ids))
 }
 
 #[tauri::command]
-pub async fn get_monthly_tasks() -> Result<std::collections::HashMap<i64, Vec<TaskView>>, String> {
-    Ok(periodic::get_monthly_tasks())
+pub async fn get_monthly_tasks(
+    state: State<'_, AppState>,
+    tags: Option<TagFilter>,
+) -> Result<HashMap<i64, Vec<TaskView>>, String> {
+    let ids = tagged_task_ids(state.inner(), tags)?;
+    Ok(retain_tagged(periodic::get_monthly_tasks(), ids))
 }
 
 
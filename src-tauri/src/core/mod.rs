@@ -5,4 +5,5 @@ pub mod timer;
 #[cfg(desktop)]
 pub mod tray;
 pub mod app_init;
+pub mod cli;
 pub mod window;
@@ -13,12 +13,14 @@ use std::{
 use crate::{
     core::handle::Handle,
     logging, logging_error,
-    service::{execute, hub::Hub},
+    service::{execute, hub::Hub, reminder},
     singleton,
     utils::logging::Type,
 };
 
 type TaskID = u64;
+/// 提醒类定时任务的 uid 前缀，用于与到期执行的任务区分
+const REMINDER_UID_PREFIX: &str = "reminder:";
 // const AUTO_REFRESH_ID: &str = "auto_refresh_task";
 
 #[derive(Debug, Clone)]
@@ -228,6 +230,28 @@ impl Timer {
                 }
             }
         }
+        if let Some(items) = Hub::global().latest_reminders() {
+            for (timestamp, tasks) in items.iter() {
+                let interval = timestamp - cur_time;
+                if interval <= 0 {
+                    continue;
+                }
+                for task in tasks.iter() {
+                    let uid = format!("{}{}", REMINDER_UID_PREFIX, task.id);
+                    logging!(
+                        debug,
+                        Type::Timer,
+                        "找到提醒配置: id={}, interval={}seconds",
+                        uid,
+                        interval
+                    );
+                    if new_map.get(&uid).is_some_and(|current| current < &interval) {
+                        continue;
+                    }
+                    new_map.insert(uid, interval);
+                }
+            }
+        }
         logging!(
             debug,
             Type::Timer,
@@ -355,30 +379,25 @@ impl Timer {
             id,
             timestamp
         );
-        match tokio::time::timeout(std::time::Duration::from_secs(40), async {
-            // feat::update_profile(uid.clone(), None, Some(is_current)).await
-            execute::execute_tasks(&id, timestamp).await
-        })
-        .await
-        {
-            Ok(result) => match result {
-                Ok(_) => {
-                    let duration = task_start.elapsed().as_millis();
-                    logging!(
-                        info,
-                        Type::Timer,
-                        "Timer task completed successfully for id: {} (took {}ms)",
-                        id,
-                        duration
-                    );
-                }
-                Err(e) => {
-                    logging_error!(Type::Timer, "Failed to update profile uid {}: {}", id, e);
-                    Handle::notice_message("Error", format!("定时任务执行失败:{}", e));
-                }
-            },
-            Err(_) => {
-                logging_error!(Type::Timer, "Timer task timed out for uid: {}", id);
+        // 不限制整体时长：动作按各自的超时时间执行，确认弹窗与重试等待也不应被打断
+        let result = match id.strip_prefix(REMINDER_UID_PREFIX) {
+            Some(task_id) => reminder::notify_task_reminders(task_id, timestamp).await,
+            None => execute::execute_tasks(&id, timestamp).await,
+        };
+        match result {
+            Ok(_) => {
+                let duration = task_start.elapsed().as_millis();
+                logging!(
+                    info,
+                    Type::Timer,
+                    "Timer task completed successfully for id: {} (took {}ms)",
+                    id,
+                    duration
+                );
+            }
+            Err(e) => {
+                logging_error!(Type::Timer, "Failed to update profile uid {}: {}", id, e);
+                Handle::notice_message("Error", format!("定时任务执行失败:{}", e));
             }
         }
     }
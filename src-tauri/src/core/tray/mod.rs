@@ -14,7 +14,7 @@ use crate::{
     core::handle, // utils::logging::Type
     feat,
     logging,get_app_handle,
-    schema::state::AppState,
+    schema::{state::AppState, RunTrigger},
     store::module::ActionManager,
     utils::{logging::Type, resolve, window_manager},
 };
@@ -245,7 +245,7 @@ fn on_menu_event(app_handle: &AppHandle, event: MenuEvent) {
         let app_handle_clone = app_handle.clone();
         logging!(info, Type::Tray, true, "执行托盘 Action: {}", action_id);
         tauri::async_runtime::spawn(async move {
-            if let Err(e) = feat::action::execute_action_by_id(&app_handle_clone, &action_id).await {
+            if let Err(e) = feat::action::execute_action_by_id(&app_handle_clone, &action_id, RunTrigger::Tray).await {
                 logging!(error, Type::Tray, true, "执行托盘 Action 失败: {}", e);
             }
         });
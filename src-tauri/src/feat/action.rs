@@ -1,27 +1,173 @@
-use crate::schema::{Action, ActionType, AppState};
-use crate::store::module::ActionManager;
-use crate::utils::logging::Type;
+use crate::feat::{group, http, policy, run, run::OutputStream, workflow};
+use crate::schema::{
+    truncate_output, Action, ActionRunRecord, ActionType, AppState, GroupOptions, HttpRequest, RetryPolicy,
+    RunTrigger, TaskView, Workflow,
+};
+use crate::store::module::{ActionManager, ActionRunManager};
+use crate::utils::{help::get_uid, logging::Type, template};
 use crate::{get_app_handle, logging};
-use std::process::Command;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
 use tauri_plugin_notification::NotificationExt;
 use tauri_plugin_opener::OpenerExt;
 use tauri::Manager;
+use tokio::io::AsyncWriteExt;
 use tokio::time::timeout;
 
-#[derive(Debug, Clone, Copy)]
+/// 组合动作与工作流最多嵌套的层数
+const MAX_NESTING_DEPTH: usize = 8;
+
+/// 动作 command、args 等字段中可用的模板变量，未提供的变量再按 [`template::builtin_variable`] 解析
+#[derive(Debug, Clone, Default)]
+pub struct ActionVars {
+    values: BTreeMap<String, String>,
+}
+
+impl ActionVars {
+    const EMPTY: Self = Self {
+        values: BTreeMap::new(),
+    };
+
+    /// 由任务触发时提供 `task.id`、`task.name`、`task.due_to`
+    pub fn for_task(task: &TaskView) -> Self {
+        let mut vars = Self::default();
+        vars.set("task.id", &task.id);
+        vars.set("task.name", &task.name);
+        vars.set("task.due_to", task.due_to.as_deref().unwrap_or_default());
+        vars
+    }
+
+    /// 由任务触发时所属的任务 ID
+    fn task_id(&self) -> Option<&str> {
+        self.values.get("task.id").map(String::as_str)
+    }
+
+    fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    /// 组合动作中上一个子动作的结果，成功时为 `prev.stdout`，失败时为 `prev.stderr`
+    fn with_prev(&self, prev: Option<&Result<String, String>>) -> Self {
+        let mut vars = self.clone();
+        if let Some(prev) = prev {
+            let (stdout, stderr) = match prev {
+                Ok(stdout) => (stdout.trim_end(), ""),
+                Err(stderr) => ("", stderr.trim_end()),
+            };
+            vars.set("prev.stdout", stdout);
+            vars.set("prev.stderr", stderr);
+        }
+        vars
+    }
+
+    fn render(&self, text: &str) -> String {
+        template::render(text, |name| {
+            self.values
+                .get(name)
+                .cloned()
+                .or_else(|| template::builtin_variable(name))
+        })
+    }
+
+    /// 工作流步骤的输出变量，如 `prev.stdout`、`steps.<id>.exit_code`
+    fn with_values(&self, values: BTreeMap<String, String>) -> Self {
+        let mut vars = self.clone();
+        vars.values.extend(values);
+        vars
+    }
+
+    /// 替换后交给 shell 解析的命令，每个变量的值都按当前平台的 shell 规则加引号
+    fn render_command(&self, text: &str) -> String {
+        template::render(text, |name| {
+            self.values
+                .get(name)
+                .cloned()
+                .or_else(|| template::builtin_variable(name))
+                .map(|value| quote_shell_arg(&value))
+        })
+    }
+
+    /// 替换动作中的变量，每个值只替换一次，替换进来的内容不会再被解析；
+    /// 请求、组合动作与工作流的 command 保存的是定义，不做替换
+    fn expand(&self, mut action: Action) -> Action {
+        match ActionType::try_from(action.typ.as_str()) {
+            Ok(ActionType::Http | ActionType::Group | ActionType::Workflow) => return action,
+            Ok(ActionType::Command) => action.command = self.render_command(&action.command),
+            _ => action.command = self.render(&action.command),
+        }
+        for arg in action.args.iter_mut().flatten() {
+            *arg = self.render(arg);
+        }
+        for value in action.env.iter_mut().flat_map(|env| env.values_mut()) {
+            *value = self.render(value);
+        }
+        for text in [&mut action.cwd, &mut action.stdin, &mut action.output_file]
+            .into_iter()
+            .flatten()
+        {
+            *text = self.render(text);
+        }
+        action
+    }
+}
+
+#[derive(Debug, Clone)]
 struct ExecutionContext {
     force_sync_command: bool,
+    /// 外层正在执行的组合动作、工作流，用于在执行时发现循环引用
+    ancestors: Vec<String>,
+    vars: ActionVars,
+    /// 本次执行的运行 ID，前台命令的输出事件与取消都以此为准
+    run_id: Option<String>,
 }
 
 impl ExecutionContext {
     const DEFAULT: Self = Self {
         force_sync_command: false,
+        ancestors: Vec::new(),
+        vars: ActionVars::EMPTY,
+        run_id: None,
     };
 
+    /// 组合动作内的执行上下文，实际执行时由 [`ExecutionContext::enter`] 生成
+    #[cfg(test)]
     const GROUP: Self = Self {
         force_sync_command: true,
+        ancestors: Vec::new(),
+        vars: ActionVars::EMPTY,
+        run_id: None,
     };
+
+    /// 进入组合动作或工作流，出现循环引用或嵌套过深时拒绝执行
+    fn enter(&self, action: &Action) -> Result<Self, ActionOutput> {
+        let id = action.id.clone().unwrap_or_else(|| action.name.clone());
+        if self.ancestors.contains(&id) {
+            let mut path = self.ancestors.clone();
+            path.push(id);
+            let error = format!("检测到循环引用，已停止执行: {}", path.join(" -> "));
+            logging!(error, Type::Cmd, true, "{}", error);
+            return Err(ActionOutput::error(error));
+        }
+        if self.ancestors.len() >= MAX_NESTING_DEPTH {
+            let error = format!("动作 {} 嵌套超过 {} 层，已停止执行", action.name, MAX_NESTING_DEPTH);
+            logging!(error, Type::Cmd, true, "{}", error);
+            return Err(ActionOutput::error(error));
+        }
+        let mut ancestors = self.ancestors.clone();
+        ancestors.push(id);
+        Ok(Self {
+            force_sync_command: true,
+            ancestors,
+            vars: self.vars.clone(),
+            run_id: self.run_id.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -42,9 +188,111 @@ fn resolve_command_execution_mode(action: &Action, context: ExecutionContext) ->
     }
 }
 
+/// 动作单次执行的输出，失败时同样保留 stderr 与退出码，便于写入执行记录
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+}
+
+impl ActionOutput {
+    pub fn message<S: Into<String>>(stdout: S) -> Self {
+        Self {
+            stdout: stdout.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn error<S: Into<String>>(stderr: S) -> Self {
+        Self {
+            stderr: stderr.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<String> for ActionOutput {
+    fn from(error: String) -> Self {
+        Self::error(error)
+    }
+}
+
+fn into_message(res: Result<ActionOutput, ActionOutput>) -> Result<String, String> {
+    res.map(|output| output.stdout).map_err(|output| output.stderr)
+}
+
+/// 记录一次动作执行的起止时间，结束时写入 action_runs
+struct RunRecorder {
+    id: String,
+    action_id: Option<String>,
+    action_name: String,
+    trigger: RunTrigger,
+    started_at: i64,
+    instant: Instant,
+}
+
+impl RunRecorder {
+    fn start(action: &Action, trigger: RunTrigger) -> Self {
+        Self {
+            id: get_uid("run"),
+            action_id: action.id.clone(),
+            action_name: action.name.clone(),
+            trigger,
+            started_at: chrono::Local::now().timestamp(),
+            instant: Instant::now(),
+        }
+    }
+
+    fn finish(self, attempts: usize, res: &Result<ActionOutput, ActionOutput>) {
+        // 单元测试等场景下没有 AppHandle，直接跳过记录
+        let Some(app_handle) = Handle::global().app_handle() else {
+            return;
+        };
+        let (success, output) = match res {
+            Ok(output) => (true, output),
+            Err(output) => (false, output),
+        };
+        let run = ActionRunRecord {
+            id: self.id,
+            action_id: self.action_id,
+            action_name: self.action_name,
+            trigger: self.trigger,
+            started_at: self.started_at,
+            ended_at: chrono::Local::now().timestamp(),
+            duration_ms: self.instant.elapsed().as_millis() as u64,
+            attempts,
+            success,
+            exit_code: output.exit_code,
+            stdout: truncate_output(&output.stdout),
+            stderr: truncate_output(&output.stderr),
+        };
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        if let Err(e) = db.create_action_run(&run) {
+            logging!(warn, Type::Cmd, true, "写入动作 {} 执行记录失败: {}", run.action_name, e);
+        }
+    }
+}
+
 pub async fn execute_action(action: Action) -> Result<String, String> {
+    execute_action_with_trigger(action, RunTrigger::Manual, ActionVars::default()).await
+}
+
+/// 执行动作并写入执行记录，`trigger` 标明本次执行的来源，`vars` 为可用的模板变量
+pub async fn execute_action_with_trigger(
+    action: Action,
+    trigger: RunTrigger,
+    vars: ActionVars,
+) -> Result<String, String> {
     let action_id = action.id.clone();
-    let res = execute_action_internal(action, ExecutionContext::DEFAULT).await;
+    let recorder = RunRecorder::start(&action, trigger);
+    let context = ExecutionContext {
+        vars,
+        ..ExecutionContext::DEFAULT
+    };
+    let time_limit = attempt_time_limit(&action);
+    let res = execute_with_retry(action, context, recorder, time_limit).await;
     if res.is_ok() {
         if let Some(id) = action_id {
             {
@@ -57,16 +305,20 @@ pub async fn execute_action(action: Action) -> Result<String, String> {
             let _ = crate::core::tray::Tray::global().update_menu();
         }
     }
-    res
+    into_message(res)
 }
 
-pub async fn execute_action_by_id(app_handle: &tauri::AppHandle, id: &str) -> Result<String, String> {
+pub async fn execute_action_by_id(
+    app_handle: &tauri::AppHandle,
+    id: &str,
+    trigger: RunTrigger,
+) -> Result<String, String> {
     let state = app_handle.state::<AppState>();
     let action = {
         let db = state.db.lock();
         db.get_action(id).map_err(|e| e.to_string())?
     };
-    execute_action(Action::from(action)).await
+    execute_action_with_trigger(Action::from(action), trigger, ActionVars::default()).await
 }
 use crate::core::handle::Handle;
 async fn open_path(path: String) -> Result<(), String> {
@@ -89,18 +341,44 @@ async fn open_url(url: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 按 CommandLineToArgvW 的规则为参数加引号，引号前的反斜杠需要加倍
 #[cfg(target_os = "windows")]
-fn quote_windows_cmd_arg(arg: &str) -> String {
-    if arg.is_empty() {
-        return "\"\"".to_string();
-    }
-
-    if !arg.chars().any(|c| c.is_whitespace() || c == '"' || c == '^' || c == '&' || c == '|' || c == '<' || c == '>') {
+fn quote_windows_argv_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || c == '"') {
         return arg.to_string();
     }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                continue;
+            }
+            '"' => quoted.push_str(&"\\".repeat(backslashes * 2 + 1)),
+            _ => quoted.push_str(&"\\".repeat(backslashes)),
+        }
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
 
-    let escaped = arg.replace('"', "\\\"");
-    format!("\"{}\"", escaped)
+/// cmd 不认 `\"` 转义，引号会切换它的解析状态；因此在按 argv 规则加引号后，
+/// 再为所有 cmd 特殊字符（包括引号本身）加 `^`，使参数中的内容不会串联命令或展开变量
+#[cfg(target_os = "windows")]
+fn quote_windows_cmd_arg(arg: &str) -> String {
+    let quoted = quote_windows_argv_arg(arg);
+    let mut escaped = String::with_capacity(quoted.len() * 2);
+    for c in quoted.chars() {
+        if matches!(c, '"' | '^' | '&' | '|' | '<' | '>' | '%' | '!' | '(' | ')') {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 #[cfg(target_os = "windows")]
@@ -112,39 +390,272 @@ fn build_windows_command_line(command: &str, args: Option<&Vec<String>>) -> Stri
     parts.join(" ")
 }
 
-#[cfg(target_os = "windows")]
-async fn execute_command(command: String, args: Option<Vec<String>>) -> Result<String, String> {
-    let full_command = build_windows_command_line(&command, args.as_ref());
-    let mut cmd = Command::new("cmd");
-    cmd.args(["/S", "/C", &full_command]);
-    let output = cmd.output().map_err(|e| e.to_string())?;
+/// 将前台进程的输出转为 ActionOutput，非零退出码视为失败
+fn command_output(output: std::process::Output) -> Result<ActionOutput, ActionOutput> {
+    let mut result = ActionOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code: output.status.code(),
+    };
     if !output.status.success() {
-        let error_message = String::from_utf8_lossy(&output.stderr);
-        return Err(error_message.to_string());
+        if result.stderr.trim().is_empty() {
+            result.stderr = format!("命令执行失败: {}", output.status);
+        }
+        return Err(result);
     }
-    let output_message = String::from_utf8_lossy(&output.stdout);
+    Ok(result)
+}
 
-    Ok(output_message.to_string())
+/// 设置了输出文件时，返回的输出只包含文件路径
+fn file_output(status: std::process::ExitStatus, path: &str) -> Result<ActionOutput, ActionOutput> {
+    let mut result = ActionOutput {
+        stdout: format!("输出已写入 {}", path),
+        stderr: String::new(),
+        exit_code: status.code(),
+    };
+    if !status.success() {
+        result.stderr = format!("命令执行失败: {}，输出见 {}", status, path);
+        return Err(result);
+    }
+    Ok(result)
+}
+
+/// 应用动作的工作目录与环境变量
+fn apply_command_options(cmd: &mut Command, action: &Action) -> Result<(), ActionOutput> {
+    if let Some(cwd) = action.cwd.as_deref().filter(|cwd| !cwd.trim().is_empty()) {
+        if !Path::new(cwd).is_dir() {
+            return Err(format!("工作目录不存在: {}", cwd).into());
+        }
+        cmd.current_dir(cwd);
+    }
+    if let Some(env) = &action.env {
+        cmd.envs(env);
+    }
+    Ok(())
+}
+
+/// 以追加方式打开输出文件，上级目录不存在时一并创建
+fn open_output_file(path: &str) -> Result<(File, File), ActionOutput> {
+    let open = || -> std::io::Result<(File, File)> {
+        if let Some(parent) = Path::new(path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let stdout = OpenOptions::new().create(true).append(true).open(path)?;
+        let stderr = stdout.try_clone()?;
+        Ok((stdout, stderr))
+    };
+    open().map_err(|e| format!("打开输出文件 {} 失败: {}", path, e).into())
+}
+
+fn output_file(action: &Action) -> Option<&str> {
+    action.output_file.as_deref().filter(|path| !path.trim().is_empty())
+}
+
+/// 子进程启动后写入 stdin，在单独的线程中进行以免与输出的读取互相等待
+fn write_stdin(child: &mut Child, action: &Action) {
+    if let (Some(input), Some(mut pipe)) = (action.stdin.clone(), child.stdin.take()) {
+        std::thread::spawn(move || {
+            let _ = pipe.write_all(input.as_bytes());
+        });
+    }
+}
+
+fn stdin_stdio(action: &Action) -> Stdio {
+    if action.stdin.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    }
+}
+
+/// 登记已启动的命令，取消或中断时据此终止进程
+fn register_run(action: &Action, run_id: &str, task_id: Option<&str>, pid: u32, detached: bool) -> run::RunGuard {
+    run::register(run::RunInfo {
+        run_id: run_id.to_string(),
+        pid,
+        action_id: action.id.clone(),
+        action_name: action.name.clone(),
+        task_id: task_id.map(str::to_string),
+        started_at: chrono::Local::now().timestamp(),
+        detached,
+    })
+}
+
+/// 前台执行命令并等待结束，输出按行发送 `action-output` 事件；设置了输出文件时输出直接写入文件
+async fn run_foreground(
+    mut cmd: tokio::process::Command,
+    action: &Action,
+    run_id: &str,
+    task_id: Option<&str>,
+) -> Result<ActionOutput, ActionOutput> {
+    apply_command_options(cmd.as_std_mut(), action)?;
+    cmd.stdin(stdin_stdio(action));
+    match output_file(action) {
+        Some(path) => {
+            let (stdout, stderr) = open_output_file(path)?;
+            cmd.stdout(stdout).stderr(stderr);
+        }
+        None => {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+    }
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    let guard = child
+        .id()
+        .map(|pid| register_run(action, run_id, task_id, pid, false));
+    if let (Some(input), Some(mut pipe)) = (action.stdin.clone(), child.stdin.take()) {
+        tokio::spawn(async move {
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let action_id = action.id.as_deref();
+    let read_stdout = async {
+        match child.stdout.take() {
+            Some(pipe) => run::stream_lines(pipe, run_id, action_id, OutputStream::Stdout).await,
+            None => Vec::new(),
+        }
+    };
+    let read_stderr = async {
+        match child.stderr.take() {
+            Some(pipe) => run::stream_lines(pipe, run_id, action_id, OutputStream::Stderr).await,
+            None => Vec::new(),
+        }
+    };
+    let (stdout, stderr) = tokio::join!(read_stdout, read_stderr);
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+
+    if guard.is_some_and(run::RunGuard::finish) {
+        return Err(ActionOutput {
+            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stderr: "命令已被取消".to_string(),
+            exit_code: status.code(),
+        });
+    }
+    match output_file(action) {
+        Some(path) => file_output(status, path),
+        None => command_output(std::process::Output { status, stdout, stderr }),
+    }
+}
+
+/// 启动命令后不等待结束，未设置输出文件时丢弃输出
+fn spawn_detached(
+    mut cmd: Command,
+    action: &Action,
+    run_id: &str,
+    task_id: Option<&str>,
+) -> Result<ActionOutput, ActionOutput> {
+    apply_command_options(&mut cmd, action)?;
+    cmd.stdin(stdin_stdio(action));
+    match output_file(action) {
+        Some(path) => {
+            let (stdout, stderr) = open_output_file(path)?;
+            cmd.stdout(stdout).stderr(stderr);
+        }
+        None => {
+            cmd.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+    }
+    match cmd.spawn() {
+        Ok(mut child) => {
+            write_stdin(&mut child, action);
+            let guard = register_run(action, run_id, task_id, child.id(), true);
+            // 不等待子进程完成，但需要在后台回收，避免留下僵尸进程
+            std::thread::spawn(move || {
+                let _ = child.wait();
+                guard.finish();
+            });
+            Ok(ActionOutput::message("命令已启动，独立运行中"))
+        }
+        Err(e) => Err(format!("启动命令失败: {}", e).into()),
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn execute_command(action: &Action, run_id: &str, task_id: Option<&str>) -> Result<ActionOutput, ActionOutput> {
+    let full_command = build_windows_command_line(&action.command, action.args.as_ref());
+    let mut cmd = tokio::process::Command::new("cmd");
+    // 命令行已按 cmd 的规则转义，原样传入，避免再按 argv 规则加一层引号
+    cmd.raw_arg(format!("/S /C \"{}\"", full_command));
+    run_foreground(cmd, action, run_id, task_id).await
 }
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 async fn execute_command_indepent(
-    command: String,
-    args: Option<Vec<String>>,
-) -> Result<String, String> {
-    let full_command = build_windows_command_line(&command, args.as_ref());
+    action: &Action,
+    run_id: &str,
+    task_id: Option<&str>,
+) -> Result<ActionOutput, ActionOutput> {
+    let full_command = build_windows_command_line(&action.command, action.args.as_ref());
     let mut cmd = Command::new("cmd");
-    cmd.args(["/S", "/C", &full_command]);
+    cmd.raw_arg(format!("/S /C \"{}\"", full_command));
     cmd.creation_flags(0x08000000);
-    match cmd.spawn() {
-        Ok(_child) => {
-            // 不等待子进程完成，直接返回成功
-            Ok("命令已启动，独立运行中".to_string())
-        }
-        Err(e) => Err(format!("启动命令失败: {}", e)),
+    spawn_detached(cmd, action, run_id, task_id)
+}
+
+/// 命令中替换进来的变量值与参数使用相同的转义规则
+#[cfg(unix)]
+fn quote_shell_arg(value: &str) -> String {
+    quote_posix_sh_arg(value)
+}
+
+#[cfg(target_os = "windows")]
+fn quote_shell_arg(value: &str) -> String {
+    quote_windows_cmd_arg(value)
+}
+
+/// 按 POSIX sh 的规则为参数加单引号，command 本身保持原样以便使用管道、重定向等 shell 语法
+#[cfg(unix)]
+fn quote_posix_sh_arg(arg: &str) -> String {
+    if arg.is_empty() {
+        return "''".to_string();
     }
+
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '=' | ':' | ',' | '+' | '@' | '%'))
+    {
+        return arg.to_string();
+    }
+
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(unix)]
+fn build_posix_command_line(command: &str, args: Option<&Vec<String>>) -> String {
+    let mut parts = vec![command.to_string()];
+    if let Some(args) = args {
+        parts.extend(args.iter().map(|arg| quote_posix_sh_arg(arg)));
+    }
+    parts.join(" ")
+}
+
+#[cfg(unix)]
+async fn execute_command(action: &Action, run_id: &str, task_id: Option<&str>) -> Result<ActionOutput, ActionOutput> {
+    let full_command = build_posix_command_line(&action.command, action.args.as_ref());
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.args(["-c", &full_command])
+        // 放入独立进程组，取消时可以一并终止命令启动的子进程
+        .process_group(0);
+    run_foreground(cmd, action, run_id, task_id).await
+}
+
+#[cfg(unix)]
+async fn execute_command_indepent(
+    action: &Action,
+    run_id: &str,
+    task_id: Option<&str>,
+) -> Result<ActionOutput, ActionOutput> {
+    use std::os::unix::process::CommandExt;
+
+    let full_command = build_posix_command_line(&action.command, action.args.as_ref());
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", &full_command])
+        // 放入独立进程组，避免随 ducker 收到的终端信号一起退出
+        .process_group(0);
+    spawn_detached(cmd, action, run_id, task_id)
 }
 
 /// 执行配置结构体
@@ -152,15 +663,15 @@ async fn execute_command_indepent(
 pub struct ExecutionConfig {
     pub max_retries: usize,
     pub timeout_seconds: u64,
-    pub retry_delay_ms: u64,
+    pub retry_policy: RetryPolicy,
 }
 
 impl Default for ExecutionConfig {
     fn default() -> Self {
         Self {
-            max_retries: 3,
+            max_retries: 0,
             timeout_seconds: 30,
-            retry_delay_ms: 1000,
+            retry_policy: RetryPolicy::default(),
         }
     }
 }
@@ -168,79 +679,154 @@ impl Default for ExecutionConfig {
 impl From<&Action> for ExecutionConfig {
     fn from(action: &Action) -> Self {
         Self {
-            max_retries: action.retry.unwrap_or(3),
-            timeout_seconds: action.timeout.unwrap_or(30),
-            retry_delay_ms: 1000,
+            max_retries: action.retry.unwrap_or(0),
+            timeout_seconds: action
+                .timeout
+                .filter(|seconds| *seconds > 0)
+                .unwrap_or(ExecutionConfig::default().timeout_seconds),
+            retry_policy: action.retry_policy.clone().unwrap_or_default(),
         }
     }
 }
 
 /// 带有retry和timeout机制的action执行函数，用于处理group Action
-pub async fn execute_action_with_retry(action: Action) -> Result<String, String> {
+async fn execute_action_with_retry(action: Action, context: ExecutionContext) -> Result<String, String> {
+    execute_member_action(action, context)
+        .await
+        .map(|output| output.stdout)
+        .map_err(|output| format!("动作执行失败: {}", output.stderr))
+}
+
+/// 单次尝试的时长限制，直接执行与作为子动作执行时一致，未设置超时时间时按 [`ExecutionConfig`] 的默认值；
+/// 组合动作与工作流的子动作各自受限，未设置超时时间时不限制整体时长
+fn attempt_time_limit(action: &Action) -> Option<Duration> {
+    let container = matches!(
+        ActionType::try_from(action.typ.as_str()),
+        Ok(ActionType::Group | ActionType::Workflow)
+    );
+    if container && action.timeout.unwrap_or(0) == 0 {
+        return None;
+    }
+    Some(Duration::from_secs(ExecutionConfig::from(action).timeout_seconds))
+}
+
+/// 执行组合动作或工作流中的子动作
+async fn execute_member_action(action: Action, context: ExecutionContext) -> Result<ActionOutput, ActionOutput> {
+    let recorder = RunRecorder::start(&action, RunTrigger::GroupParent);
+    let time_limit = attempt_time_limit(&action);
+    execute_with_retry(action, context, recorder, time_limit).await
+}
+
+/// 按动作的重试策略执行，`time_limit` 为单次尝试的时长限制；结束后写入执行记录，失败时返回最后一次尝试的输出
+async fn execute_with_retry(
+    action: Action,
+    context: ExecutionContext,
+    recorder: RunRecorder,
+    time_limit: Option<Duration>,
+) -> Result<ActionOutput, ActionOutput> {
+    let action = context.vars.expand(action);
+    // 只在执行前检查一次，拒绝执行不计入重试，确认弹窗也不受单次尝试的时长限制
+    if matches!(
+        ActionType::try_from(action.typ.as_str()),
+        Ok(ActionType::Command | ActionType::Http)
+    ) {
+        if let Err(error) = policy::authorize_action(&action).await {
+            let res = Err(error);
+            recorder.finish(0, &res);
+            return res;
+        }
+    }
     let config = ExecutionConfig::from(&action);
-    let mut last_error = String::new();
-    
-    for attempt in 0..=config.max_retries {
-        logging!(info, Type::Cmd, true, "执行动作 {} (尝试 {}/{})", action.name, attempt + 1, config.max_retries + 1);
-        
-        // 使用timeout包装执行
-        let execution_future = execute_action_internal(action.clone(), ExecutionContext::GROUP);
-        let timeout_duration = Duration::from_secs(config.timeout_seconds);
-        
-        match timeout(timeout_duration, execution_future).await {
-            Ok(Ok(result)) => {
-                if attempt > 0 {
-                    logging!(info, Type::Cmd, true, "动作 {} 在第 {} 次尝试后成功", action.name, attempt + 1);
+    let context = ExecutionContext {
+        run_id: Some(recorder.id.clone()),
+        ..context
+    };
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        logging!(info, Type::Cmd, true, "执行动作 {} (尝试 {}/{})", action.name, attempt, config.max_retries + 1);
+
+        let execution = execute_action_internal(action.clone(), context.clone());
+        let (res, timed_out) = match time_limit {
+            Some(limit) => match timeout(limit, execution).await {
+                Ok(res) => (res, false),
+                Err(_) => (Err(ActionOutput::error(format!("执行超时 ({}秒)", limit.as_secs()))), true),
+            },
+            None => (execution.await, false),
+        };
+        let error = match res {
+            Ok(output) => {
+                if attempt > 1 {
+                    logging!(info, Type::Cmd, true, "动作 {} 在第 {} 次尝试后成功", action.name, attempt);
                 }
-                return Ok(result);
-            }
-            Ok(Err(error)) => {
-                last_error = error;
-                logging!(warn, Type::Cmd, true, "动作 {} 第 {} 次尝试失败: {}", action.name, attempt + 1, last_error);
+                let res = Ok(output);
+                recorder.finish(attempt, &res);
+                return res;
             }
-            Err(_) => {
-                last_error = format!("执行超时 ({}秒)", config.timeout_seconds);
-                logging!(warn, Type::Cmd, true, "动作 {} 第 {} 次尝试超时", action.name, attempt + 1);
+            Err(error) => error,
+        };
+        logging!(warn, Type::Cmd, true, "动作 {} 第 {} 次尝试失败: {}", action.name, attempt, error.stderr);
+
+        if attempt > config.max_retries || !config.retry_policy.should_retry(timed_out, error.exit_code) {
+            if attempt > 1 {
+                logging!(error, Type::Cmd, true, "动作执行失败，已重试 {} 次。最后错误: {}", attempt - 1, error.stderr);
             }
+            let res = Err(error);
+            recorder.finish(attempt, &res);
+            return res;
         }
-        
-        // 如果不是最后一次尝试，等待一段时间再重试
-        if attempt < config.max_retries {
-            tokio::time::sleep(Duration::from_millis(config.retry_delay_ms)).await;
-        }
+        let delay = config.retry_policy.delay(attempt);
+        logging!(info, Type::Cmd, true, "动作 {} 将在 {} 毫秒后重试", action.name, delay.as_millis());
+        tokio::time::sleep(delay).await;
     }
-    
-    let final_error = format!("动作执行失败，已重试 {} 次。最后错误: {}", config.max_retries, last_error);
-    logging!(error, Type::Cmd, true, "{}", final_error);
-    Err(final_error)
 }
 
-/// 原始的action执行逻辑，重命名为内部函数
-async fn execute_action_internal(action: Action, context: ExecutionContext) -> Result<String, String> {
+type BoxedFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// 组合动作的子动作可能被放到单独的任务中执行，显式标注 Send 以打断递归的类型推导
+fn boxed_group_member(action: Action, context: ExecutionContext) -> BoxedFuture<Result<String, String>> {
+    Box::pin(async move {
+        logging!(info, Type::Cmd, true, "执行子动作时间: {:?}", chrono::Local::now().to_rfc3339());
+        execute_action_with_retry(action, context).await
+    })
+}
+
+fn boxed_workflow_step(action: Action, context: ExecutionContext) -> BoxedFuture<Result<ActionOutput, ActionOutput>> {
+    Box::pin(async move {
+        let wait = action.wait as u64;
+        let res = execute_member_action(action, context).await;
+        // 与组合动作一致，按子动作的等待时间间隔执行
+        tokio::time::sleep(Duration::from_millis(wait)).await;
+        res
+    })
+}
+
+/// 原始的action执行逻辑，重命名为内部函数；模板变量与执行策略已由 [`execute_with_retry`] 处理
+async fn execute_action_internal(action: Action, context: ExecutionContext) -> Result<ActionOutput, ActionOutput> {
     logging!(info, Type::Cmd, true, "内部执行动作: {:?}", action);
     if let Ok(t) = ActionType::try_from(action.typ.as_str()) {
         match t {
             ActionType::Directory => {
                 open_path(action.command).await?;
-                Ok(format!("open_dir: ok"))
+                Ok(ActionOutput::message("open_dir: ok"))
             }
             ActionType::File => {
                 open_path(action.command).await?;
-                Ok(format!("open_file: ok"))
+                Ok(ActionOutput::message("open_file: ok"))
             }
             ActionType::Url => {
                 open_url(action.command).await?;
-                Ok(format!("open_url: ok"))
+                Ok(ActionOutput::message("open_url: ok"))
             }
             ActionType::Command => {
+                let run_id = context.run_id.clone().unwrap_or_else(|| get_uid("run"));
+                let task_id = context.vars.task_id().map(str::to_string);
                 match resolve_command_execution_mode(&action, context) {
                     CommandExecutionMode::Foreground => {
-                        let output = execute_command(action.command, action.args).await?;
-                        Ok(output)
+                        execute_command(&action, &run_id, task_id.as_deref()).await
                     }
                     CommandExecutionMode::Detached => {
-                        let output = execute_command_indepent(action.command, action.args).await?;
-                        Ok(output)
+                        execute_command_indepent(&action, &run_id, task_id.as_deref()).await
                     }
                 }
             }
@@ -276,13 +862,19 @@ async fn execute_action_internal(action: Action, context: ExecutionContext) -> R
                     .body(body)
                     .show()
                     .unwrap();
-                Ok(format!("notice: sent notification with title '{}' and body '{}'", title, body))
+                Ok(ActionOutput::message(format!("notice: sent notification with title '{}' and body '{}'", title, body)))
+            }
+            ActionType::Http => {
+                let request = HttpRequest::parse(&action.command)
+                    .map_err(|e| ActionOutput::error(format!("请求定义无效: {:#}", e)))?
+                    .render(|text| context.vars.render(text));
+                let timeout = Duration::from_secs(ExecutionConfig::from(&action).timeout_seconds);
+                http::send(&request, timeout).await
             }
             ActionType::Group => {
-                // 解析 args 中的 action IDs（逗号分隔）
+                // args 中的每一项为一个 action ID
                 let action_ids = if let Some(args) = &action.args {
                     args.iter()
-                        .flat_map(|arg| arg.split(','))
                         .map(|id| id.trim().to_string())
                         .filter(|id| !id.is_empty())
                         .collect::<Vec<String>>()
@@ -291,7 +883,7 @@ async fn execute_action_internal(action: Action, context: ExecutionContext) -> R
                 };
 
                 if action_ids.is_empty() {
-                    return Ok("group: no actions to execute".to_string());
+                    return Ok(ActionOutput::message("group: no actions to execute"));
                 }
 
                 // 获取数据库实例并立即获取数据，然后释放锁
@@ -307,64 +899,60 @@ async fn execute_action_internal(action: Action, context: ExecutionContext) -> R
                                 .collect::<Vec<Action>>()
                         }
                         Err(e) => {
-                            return Err(format!("Failed to get actions: {}", e));
+                            return Err(format!("Failed to get actions: {}", e).into());
                         }
                     }
                 };
 
                 if actions_to_execute.is_empty() {
-                    return Ok("group: no valid actions found".to_string());
+                    return Ok(ActionOutput::message("group: no valid actions found"));
                 }
 
-                // 执行所有 actions，使用带retry的版本
-                let mut results = Vec::new();
-                let mut success_count = 0;
-                let mut error_count = 0;
-
-                for sub_action in actions_to_execute {
-                    let now = chrono::Local::now().to_rfc3339();
-                    logging!(info, Type::Cmd, true, "执行子动作时间: {:?}", now);
-
-                    let result = Box::pin(execute_action_with_retry(sub_action.clone())).await;
-                    match result {
-                        Ok(result) => {
-                            results.push(format!("✓ {}: {}", sub_action.name, result));
-                            success_count += 1;
-                        }
-                        Err(error) => {
-                            results.push(format!("✗ {}: {}", sub_action.name, error));
-                            error_count += 1;
-                        }
-                    }
-                    // 等待指定时间避免过快执行
-                    
-                    tokio::time::sleep(Duration::from_millis(sub_action.wait as u64)).await;
-                }
-
-                let summary = format!(
-                    "group: executed {} actions (success: {}, failed: {})",
-                    success_count + error_count,
-                    success_count,
-                    error_count
-                );
-
-                if error_count > 0 {
-                    // 如果有错误，返回详细信息
-                    Ok(format!("{}\nDetails:\n{}", summary, results.join("\n")))
-                } else {
-                    // 如果全部成功，返回简要信息
-                    Ok(summary)
-                }
+                let options = GroupOptions::parse(&action.command)
+                    .map_err(|e| ActionOutput::error(format!("组合动作的执行方式无效: {:#}", e)))?;
+                let context = context.enter(&action)?;
+                group::run_group(actions_to_execute, &options, |member, prev| {
+                    let context = ExecutionContext {
+                        vars: context.vars.with_prev(prev),
+                        ..context.clone()
+                    };
+                    boxed_group_member(member, context)
+                })
+                .await
+            }
+            ActionType::Workflow => {
+                let workflow = Workflow::parse(&action.command)
+                    .map_err(|e| ActionOutput::error(format!("工作流定义无效: {:#}", e)))?;
+                let actions = {
+                    let app_handle = get_app_handle!();
+                    let state = app_handle.state::<AppState>();
+                    let db = state.db.lock();
+                    db.get_actions(&workflow.action_ids())
+                        .map_err(|e| ActionOutput::error(format!("Failed to get actions: {}", e)))?
+                        .into_iter()
+                        .map(|record| (record.id.clone(), Action::from(record)))
+                        .collect::<HashMap<_, _>>()
+                };
+                let context = context.enter(&action)?;
+                Box::pin(workflow::run_workflow(&workflow, &actions, |step_action, step_vars| {
+                    let context = ExecutionContext {
+                        vars: context.vars.with_values(step_vars),
+                        ..context.clone()
+                    };
+                    boxed_workflow_step(step_action, context)
+                }))
+                .await
             }
         }
     } else {
-        return Err("未知操作类型".to_string());
+        return Err(ActionOutput::error("未知操作类型"));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::{RetryOn, TrustLevel};
 
     fn mock_command_action(
         command: &str,
@@ -383,7 +971,7 @@ mod tests {
             timeout,
             command: command.to_string(),
             args: args.map(|items| items.into_iter().map(|item| item.to_string()).collect()),
-            count: None,
+            ..Default::default()
         }
     }
 
@@ -396,6 +984,124 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn action_vars_expand_command_and_args() {
+        let task = TaskView {
+            id: "task1".to_string(),
+            name: "周报".to_string(),
+            value: 0.0,
+            completed: false,
+            auto: true,
+            periodic: None,
+            actions: None,
+            children: None,
+            created_at: String::new(),
+            due_to: Some("2024-05-20 18:00:00".to_string()),
+            reminder: None,
+            tags: Vec::new(),
+            blocked_by: Vec::new(),
+            blocked: false,
+        };
+        let vars = ActionVars::for_task(&task).with_prev(Some(&Ok("v1.2\n".to_string())));
+        let action = mock_command_action(
+            "echo {{task.name}}",
+            Some(vec!["{{task.id}}@{{task.due_to}}", "{{prev.stdout}}", "{{date:%Y}}", "{{unknown}}"]),
+            0,
+            None,
+            None,
+        );
+        let action = vars.expand(action);
+        assert_eq!(action.command, format!("echo {}", quote_shell_arg("周报")));
+        assert_eq!(
+            action.args.unwrap(),
+            vec![
+                "task1@2024-05-20 18:00:00".to_string(),
+                "v1.2".to_string(),
+                chrono::Local::now().format("%Y").to_string(),
+                "{{unknown}}".to_string(),
+            ]
+        );
+
+        let workflow = Action {
+            typ: "workflow".to_string(),
+            ..mock_command_action(r#"{"steps": [{"args": ["{{prev.stdout}}"]}]}"#, None, 0, None, None)
+        };
+        assert_eq!(vars.expand(workflow.clone()).command, workflow.command);
+
+        let notice = Action {
+            typ: "notice".to_string(),
+            ..mock_command_action("{{task.name}} 已到期", None, 0, None, None)
+        };
+        assert_eq!(vars.expand(notice).command, "周报 已到期");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_command_variables_are_quoted() {
+        let mut vars = ActionVars::default();
+        vars.set("task.name", "周报; echo injected $(id) '引号'");
+        let action = vars.expand(mock_command_action("printf '%s\\n' {{task.name}} {{date:%Y}}", None, 0, None, None));
+        let output = execute_command(&action, "run_test", None).await.unwrap();
+        assert_eq!(
+            output.stdout,
+            format!("周报; echo injected $(id) '引号'\n{}\n", chrono::Local::now().format("%Y"))
+        );
+    }
+
+    #[test]
+    fn action_vars_do_not_render_substituted_values_again() {
+        std::env::set_var("DUCKER_ACTION_SECRET", "secret");
+        let step_vars = BTreeMap::from([("prev.stdout".to_string(), "{{env.DUCKER_ACTION_SECRET}}".to_string())]);
+        let vars = ActionVars::default().with_values(step_vars);
+        let action = mock_command_action("echo {{prev.stdout}}", Some(vec!["{{prev.stdout}}", "{{env.DUCKER_ACTION_SECRET}}"]), 0, None, None);
+        let action = vars.expand(action);
+        assert_eq!(action.command, format!("echo {}", quote_shell_arg("{{env.DUCKER_ACTION_SECRET}}")));
+        assert_eq!(
+            action.args.unwrap(),
+            vec!["{{env.DUCKER_ACTION_SECRET}}".to_string(), "secret".to_string()]
+        );
+    }
+
+    #[test]
+    fn nested_groups_stop_on_cycles_and_depth_limit() {
+        let group = |id: &str| Action {
+            id: Some(id.to_string()),
+            typ: "group".to_string(),
+            ..mock_command_action("group", None, 0, None, None)
+        };
+        let context = ExecutionContext::DEFAULT.enter(&group("a")).unwrap();
+        assert!(context.force_sync_command);
+        let context = context.enter(&group("b")).unwrap();
+        // 不同分支中出现同一个动作不算循环，只有外层链路中已存在时才拒绝
+        assert!(context.enter(&group("c")).is_ok());
+        let err = context.enter(&group("a")).unwrap_err();
+        assert_eq!(err.stderr, "检测到循环引用，已停止执行: a -> b -> a");
+
+        let deep = (0..MAX_NESTING_DEPTH).fold(ExecutionContext::DEFAULT, |context, i| {
+            context.enter(&group(&format!("g{}", i))).unwrap()
+        });
+        assert!(deep.enter(&group("last")).is_err());
+    }
+
+    #[test]
+    fn attempt_time_limit_defaults_to_execution_config() {
+        let default = Some(Duration::from_secs(ExecutionConfig::default().timeout_seconds));
+        assert_eq!(attempt_time_limit(&mock_command_action("echo", None, 0, None, None)), default);
+        assert_eq!(attempt_time_limit(&mock_command_action("echo", None, 0, None, Some(0))), default);
+        assert_eq!(
+            attempt_time_limit(&mock_command_action("echo", None, 0, None, Some(90))),
+            Some(Duration::from_secs(90))
+        );
+
+        // 组合动作由子动作各自限制，只有显式设置时才限制整体
+        let group = |timeout| Action {
+            typ: "group".to_string(),
+            ..mock_command_action("group", None, 0, None, timeout)
+        };
+        assert_eq!(attempt_time_limit(&group(None)), None);
+        assert_eq!(attempt_time_limit(&group(Some(120))), Some(Duration::from_secs(120)));
+    }
+
     #[test]
     fn command_mode_forces_foreground_in_group_context() {
         let action = mock_command_action("echo", Some(vec!["hello"]), 0, None, None);
@@ -434,20 +1140,34 @@ mod tests {
                 "hello world".to_string(),
                 "plain".to_string(),
                 "a\"b".to_string(),
+                "C:\\dir\\".to_string(),
             ]),
         );
 
-        assert_eq!(cmdline, "echo \"hello world\" plain \"a\\\"b\"");
+        assert_eq!(cmdline, r#"echo ^"hello world^" plain ^"a\^"b^" C:\dir\"#);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[tokio::test]
+    async fn windows_args_cannot_break_out_of_quotes() {
+        let payload = r#"x"&echo injected&" %PATH% ^"#;
+        let action = mock_command_action("echo", Some(vec![payload]), 0, None, None);
+        let output = execute_command(&action, "run_test", None).await.unwrap();
+
+        // echo 原样输出 argv 形式的参数，没有执行第二条命令，也没有展开变量
+        assert_eq!(output.stdout.trim_end().lines().count(), 1, "{}", output.stdout);
+        assert!(output.stdout.contains(r#"&echo injected&"#), "{}", output.stdout);
+        assert!(output.stdout.contains("%PATH%"), "{}", output.stdout);
     }
 
     #[cfg(target_os = "windows")]
     #[tokio::test]
     async fn execute_command_returns_stdout_for_mock_echo() {
-        let output = execute_command("echo".to_string(), Some(vec!["hello action".to_string()]))
-            .await
-            .unwrap();
+        let action = mock_command_action("echo", Some(vec!["hello action"]), 0, None, None);
+        let output = execute_command(&action, "run_test", None).await.unwrap();
 
-        assert!(output.contains("hello action"));
+        assert!(output.stdout.contains("hello action"));
+        assert_eq!(output.exit_code, Some(0));
     }
 
     #[cfg(target_os = "windows")]
@@ -458,7 +1178,226 @@ mod tests {
             .await
             .unwrap();
 
-        assert!(output.contains("group foreground"));
+        assert!(output.stdout.contains("group foreground"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn posix_command_line_quotes_complex_args() {
+        let cmdline = build_posix_command_line(
+            "echo",
+            Some(&vec![
+                "hello world".to_string(),
+                "plain".to_string(),
+                "it's".to_string(),
+                "".to_string(),
+                "$HOME".to_string(),
+            ]),
+        );
+
+        assert_eq!(cmdline, "echo 'hello world' plain 'it'\\''s' '' '$HOME'");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_execute_command_keeps_args_verbatim() {
+        let action = mock_command_action("printf '%s|'", Some(vec!["a b", "it's", "$HOME"]), 0, None, None);
+        let output = execute_command(&action, "run_test", None).await.unwrap();
+
+        assert_eq!(output.stdout, "a b|it's|$HOME|");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_execute_command_reports_failure() {
+        let action = mock_command_action("echo oops >&2; exit 3", None, 0, None, None);
+        let err = execute_command(&action, "run_test", None).await.unwrap_err();
+
+        assert_eq!(err.exit_code, Some(3));
+        assert_eq!(err.stderr.trim(), "oops");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_execute_command_applies_cwd_env_stdin_and_output_file() {
+        let dir = std::env::temp_dir().join(format!("ducker-command-{}", get_uid("test")));
+        std::fs::create_dir_all(&dir).unwrap();
+        let action = Action {
+            cwd: Some(dir.to_string_lossy().to_string()),
+            env: Some([("DUCKER_GREETING".to_string(), "hi".to_string())].into()),
+            stdin: Some("line1\nline2\n".to_string()),
+            ..mock_command_action("pwd; echo $DUCKER_GREETING; wc -l", None, 0, None, None)
+        };
+        let output = execute_command(&action, "run_test", None).await.unwrap();
+        let lines: Vec<&str> = output.stdout.lines().map(str::trim).collect();
+        assert_eq!(lines, vec![dir.canonicalize().unwrap().to_str().unwrap(), "hi", "2"]);
+
+        // 输出追加写入文件，上级目录自动创建
+        let log = dir.join("logs").join("job.log");
+        let action = Action {
+            output_file: Some(log.to_string_lossy().to_string()),
+            ..mock_command_action("echo out; echo err >&2", None, 0, None, None)
+        };
+        execute_command(&action, "run_test", None).await.unwrap();
+        let err = execute_command(&Action {
+            command: "echo again; exit 4".to_string(),
+            ..action
+        }, "run_test", None)
+        .await
+        .unwrap_err();
+        assert_eq!(err.exit_code, Some(4));
+        assert!(err.stderr.contains("job.log"));
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "out\nerr\nagain\n");
+
+        let missing = Action {
+            cwd: Some(dir.join("missing").to_string_lossy().to_string()),
+            ..mock_command_action("pwd", None, 0, None, None)
+        };
+        assert!(execute_command(&missing, "run_test", None).await.unwrap_err().stderr.contains("工作目录不存在"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn posix_cancel_action_run_kills_process_tree() {
+        // 后台的 sleep 同样持有输出管道，只终止 sh 时读取会一直等待
+        let action = mock_command_action("sleep 30 & sleep 30; echo done", None, 0, None, None);
+        let started = Instant::now();
+        let task = tokio::spawn(async move { execute_command(&action, "run_cancel_test", None).await });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        run::cancel("run_cancel_test").unwrap();
+
+        let err = task.await.unwrap().unwrap_err();
+        assert_eq!(err.stderr, "命令已被取消");
+        assert!(err.stdout.is_empty());
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(run::cancel("run_cancel_test").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn posix_timeout_kills_running_command() {
+        let dir = std::env::temp_dir().join(get_uid("ducker-timeout"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let marker = dir.join("finished");
+        let command = format!("sleep 2 && touch {}", marker.display());
+        let action = mock_command_action(&command, None, 0, Some(0), Some(1));
+
+        let err = execute_member_action(action, ExecutionContext::GROUP).await.unwrap_err();
+        assert!(err.stderr.contains("执行超时"));
+        tokio::time::sleep(Duration::from_millis(2000)).await;
+        assert!(!marker.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_denied_commands_are_not_retried() {
+        let dir = std::env::temp_dir().join(get_uid("ducker-deny"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let attempts = dir.join("attempts");
+        let action = Action {
+            trust: Some(TrustLevel::Deny),
+            ..mock_command_action(&format!("echo x >> {}", attempts.display()), None, 0, Some(3), None)
+        };
+
+        let err = execute_member_action(action, ExecutionContext::GROUP).await.unwrap_err();
+        assert!(err.stderr.contains("禁止执行"), "{}", err.stderr);
+        assert!(!attempts.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_retries_follow_retry_policy_conditions() {
+        let dir = std::env::temp_dir().join(get_uid("ducker-retry"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let attempts = dir.join("attempts");
+        let command = format!("echo x >> {}; exit 3", attempts.display());
+        let with_policy = |retry_on: RetryOn| Action {
+            retry_policy: Some(RetryPolicy {
+                delay_ms: 10,
+                retry_on,
+                ..Default::default()
+            }),
+            ..mock_command_action(&command, None, 0, Some(2), None)
+        };
+        let count = || std::fs::read_to_string(&attempts).unwrap().lines().count();
+
+        let err = execute_member_action(with_policy(RetryOn::ExitCodes(vec![3])), ExecutionContext::GROUP)
+            .await
+            .unwrap_err();
+        assert_eq!(err.exit_code, Some(3));
+        assert_eq!(count(), 3);
+
+        std::fs::remove_file(&attempts).unwrap();
+        execute_member_action(with_policy(RetryOn::ExitCodes(vec![4])), ExecutionContext::GROUP)
+            .await
+            .unwrap_err();
+        assert_eq!(count(), 1);
+
+        std::fs::remove_file(&attempts).unwrap();
+        execute_member_action(with_policy(RetryOn::Timeout), ExecutionContext::GROUP)
+            .await
+            .unwrap_err();
+        assert_eq!(count(), 1);
+
+        // 未设置 retry 时不重试
+        std::fs::remove_file(&attempts).unwrap();
+        let action = Action {
+            retry: None,
+            ..with_policy(RetryOn::Failure)
+        };
+        execute_member_action(action, ExecutionContext::GROUP).await.unwrap_err();
+        assert_eq!(count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_detached_runs_are_listed_until_killed() {
+        let action = mock_command_action("sleep 30", None, 0, None, None);
+        execute_command_indepent(&action, "run_detached_test", Some("task_1")).await.unwrap();
+        let run = run::list()
+            .into_iter()
+            .find(|run| run.run_id == "run_detached_test")
+            .unwrap();
+        assert!(run.detached);
+        assert_eq!(run.task_id.as_deref(), Some("task_1"));
+        assert_eq!(run.action_name, "Mock Command");
+
+        run::cancel("run_detached_test").unwrap();
+        let mut listed = true;
+        for _ in 0..50 {
+            listed = run::list().iter().any(|run| run.run_id == "run_detached_test");
+            if !listed {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(!listed);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_execute_action_internal_uses_foreground_mode_in_group_context() {
+        let action = mock_command_action("echo", Some(vec!["group foreground"]), 0, None, None);
+        let output = execute_action_internal(action, ExecutionContext::GROUP)
+            .await
+            .unwrap();
+
+        assert!(output.stdout.contains("group foreground"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn posix_execute_action_internal_uses_detached_mode_by_default() {
+        let action = mock_command_action("echo", Some(vec!["detached"]), 0, None, None);
+        let output = execute_action_internal(action, ExecutionContext::DEFAULT)
+            .await
+            .unwrap();
+
+        assert_eq!(output.stdout, "命令已启动，独立运行中");
     }
 
     #[cfg(target_os = "windows")]
@@ -469,6 +1408,6 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(output, "命令已启动，独立运行中");
+        assert_eq!(output.stdout, "命令已启动，独立运行中");
     }
 }
@@ -0,0 +1,312 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    feat::action::ActionOutput,
+    logging,
+    schema::{Action, GroupMode, GroupOptions},
+    utils::logging::Type,
+};
+
+/// 子动作的执行结果，`result` 为 None 表示因已有子动作失败而未执行
+struct MemberResult {
+    name: String,
+    result: Option<Result<String, String>>,
+}
+
+/// 依次执行子动作，每个子动作结束后等待其 `wait` 毫秒，`run` 会收到上一个子动作的结果
+async fn run_sequential<F, Fut>(actions: Vec<Action>, stop_on_failure: bool, run: &F) -> Vec<MemberResult>
+where
+    F: Fn(Action, Option<&Result<String, String>>) -> Fut,
+    Fut: Future<Output = Result<String, String>> + Send + 'static,
+{
+    let mut results: Vec<MemberResult> = Vec::with_capacity(actions.len());
+    let mut stopped = false;
+    for action in actions {
+        let name = action.name.clone();
+        if stopped {
+            results.push(MemberResult { name, result: None });
+            continue;
+        }
+        let wait = action.wait as u64;
+        let prev = results.last().and_then(|member| member.result.as_ref());
+        let result = run(action, prev).await;
+        stopped = stop_on_failure && result.is_err();
+        results.push(MemberResult { name, result: Some(result) });
+        if !stopped {
+            // 等待指定时间避免过快执行
+            tokio::time::sleep(Duration::from_millis(wait)).await;
+        }
+    }
+    results
+}
+
+/// 同时执行子动作，`limit` 限制同时运行的数量，按组内顺序依次启动
+async fn run_parallel<F, Fut>(
+    actions: Vec<Action>,
+    limit: usize,
+    stop_on_failure: bool,
+    run: &F,
+) -> Vec<MemberResult>
+where
+    F: Fn(Action, Option<&Result<String, String>>) -> Fut,
+    Fut: Future<Output = Result<String, String>> + Send + 'static,
+{
+    // 前台命令会阻塞所在的任务，每个子动作需要放到单独的任务中才能真正并行；
+    // 组合动作被取消或超时时 JoinSet 随之销毁并中止所有子动作，子动作启动的进程也会被终止
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let mut tasks = JoinSet::new();
+    let mut indexes = HashMap::new();
+    let names: Vec<String> = actions.iter().map(|action| action.name.clone()).collect();
+    for (index, action) in actions.into_iter().enumerate() {
+        let future = run(action, None);
+        let semaphore = semaphore.clone();
+        let stopped = stopped.clone();
+        let handle = tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok()?;
+            if stopped.load(Ordering::SeqCst) {
+                return None;
+            }
+            let result = future.await;
+            if stop_on_failure && result.is_err() {
+                stopped.store(true, Ordering::SeqCst);
+            }
+            Some(result)
+        });
+        indexes.insert(handle.id(), index);
+    }
+
+    let mut member_results: Vec<Option<Result<String, String>>> = vec![None; names.len()];
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, result) = joined.unwrap_or_else(|e| {
+            logging!(error, Type::Cmd, true, "子动作 {} 异常退出: {}", names[indexes[&e.id()]], e);
+            (e.id(), Some(Err(format!("子动作异常退出: {}", e))))
+        });
+        member_results[indexes[&id]] = result;
+    }
+    names
+        .into_iter()
+        .zip(member_results)
+        .map(|(name, result)| MemberResult { name, result })
+        .collect()
+}
+
+/// 按组合动作的执行方式执行子动作，`run` 负责执行单个子动作，并行时不提供上一个子动作的结果
+pub async fn run_group<F, Fut>(
+    actions: Vec<Action>,
+    options: &GroupOptions,
+    run: F,
+) -> Result<ActionOutput, ActionOutput>
+where
+    F: Fn(Action, Option<&Result<String, String>>) -> Fut,
+    Fut: Future<Output = Result<String, String>> + Send + 'static,
+{
+    let results = match options.mode {
+        GroupMode::Sequential => run_sequential(actions, options.stop_on_failure, &run).await,
+        GroupMode::Parallel => {
+            let limit = options.max_parallel.unwrap_or(actions.len());
+            run_parallel(actions, limit, options.stop_on_failure, &run).await
+        }
+    };
+
+    let mut lines = Vec::with_capacity(results.len());
+    let (mut success_count, mut error_count, mut skipped_count) = (0, 0, 0);
+    for member in results {
+        match member.result {
+            Some(Ok(result)) => {
+                lines.push(format!("✓ {}: {}", member.name, result));
+                success_count += 1;
+            }
+            Some(Err(error)) => {
+                lines.push(format!("✗ {}: {}", member.name, error));
+                error_count += 1;
+            }
+            None => {
+                lines.push(format!("- {}: 已有子动作失败，未执行", member.name));
+                skipped_count += 1;
+            }
+        }
+    }
+
+    let mut summary = format!(
+        "group: executed {} actions (success: {}, failed: {}",
+        success_count + error_count,
+        success_count,
+        error_count
+    );
+    if skipped_count > 0 {
+        summary.push_str(&format!(", skipped: {}", skipped_count));
+    }
+    summary.push(')');
+
+    if error_count == 0 {
+        // 如果全部成功，返回简要信息
+        return Ok(ActionOutput::message(summary));
+    }
+    let details = format!("{}\nDetails:\n{}", summary, lines.join("\n"));
+    if options.stop_on_failure {
+        // 失败即停止时整个组合动作视为失败
+        Err(ActionOutput {
+            stdout: details.clone(),
+            stderr: details,
+            exit_code: None,
+        })
+    } else {
+        Ok(ActionOutput::message(details))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feat::testing::{mock_action, mock_output};
+    use std::sync::atomic::AtomicUsize;
+
+    /// 按 [`mock_output`] 的结果完成，执行时记录同时运行的最大数量
+    fn mock_run(
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+        started: Arc<AtomicUsize>,
+    ) -> impl Fn(Action, Option<&Result<String, String>>) -> std::pin::Pin<Box<dyn Future<Output = Result<String, String>> + Send>>
+    {
+        move |action, _| {
+            let (running, peak, started) = (running.clone(), peak.clone(), started.clone());
+            Box::pin(async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                mock_output(&action, &[])
+                    .map(|output| output.stdout)
+                    .map_err(|output| output.stderr)
+            })
+        }
+    }
+
+    fn counters() -> (Arc<AtomicUsize>, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        Default::default()
+    }
+
+    #[test]
+    fn group_options_accept_legacy_placeholder() {
+        assert_eq!(GroupOptions::parse("group").unwrap(), GroupOptions::default());
+        assert_eq!(GroupOptions::parse("").unwrap(), GroupOptions::default());
+
+        let options = GroupOptions::parse(r#"{"mode":"parallel","max_parallel":2}"#).unwrap();
+        assert_eq!(options.mode, GroupMode::Parallel);
+        assert_eq!(options.max_parallel, Some(2));
+        assert!(!options.stop_on_failure);
+
+        assert!(GroupOptions::parse(r#"{"mode":"parallel","max_parallel":0}"#).is_err());
+        assert!(GroupOptions::parse(r#"{"mode":"random"}"#).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn parallel_group_respects_max_parallel() {
+        let (running, peak, started) = counters();
+        let actions = (0..5).map(|i| mock_action(&format!("a{}", i), "ok")).collect();
+        let options = GroupOptions {
+            mode: GroupMode::Parallel,
+            max_parallel: Some(2),
+            stop_on_failure: false,
+        };
+
+        let output = run_group(actions, &options, mock_run(running, peak.clone(), started.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(output.stdout, "group: executed 5 actions (success: 5, failed: 0)");
+        assert_eq!(started.load(Ordering::SeqCst), 5);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn dropping_parallel_group_aborts_members() {
+        /// 与 RunGuard 一样在子动作被取消时执行清理
+        struct DropCounter(Arc<AtomicUsize>);
+        impl Drop for DropCounter {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let (dropped, finished, _) = counters();
+        let actions = (0..3).map(|i| mock_action(&format!("a{}", i), "ok")).collect();
+        let options = GroupOptions {
+            mode: GroupMode::Parallel,
+            ..Default::default()
+        };
+        let run = |_, _: Option<&Result<String, String>>| {
+            let (dropped, finished) = (dropped.clone(), finished.clone());
+            async move {
+                let _guard = DropCounter(dropped);
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, String>(String::new())
+            }
+        };
+
+        let group = run_group(actions, &options, run);
+        assert!(tokio::time::timeout(Duration::from_millis(50), group).await.is_err());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(dropped.load(Ordering::SeqCst), 3);
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn sequential_group_reports_every_member() {
+        let (running, peak, started) = counters();
+        let actions = vec![mock_action("a", "ok"), mock_action("b", "fail"), mock_action("c", "ok")];
+
+        let output = run_group(actions, &GroupOptions::default(), mock_run(running, peak.clone(), started))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output.stdout,
+            "group: executed 3 actions (success: 2, failed: 1)\nDetails:\n\
+             ✓ a: a done\n✗ b: b failed\n✓ c: c done"
+        );
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_on_failure_skips_remaining_members() {
+        let options = GroupOptions {
+            stop_on_failure: true,
+            ..Default::default()
+        };
+        let (running, peak, started) = counters();
+        let actions = vec![mock_action("a", "ok"), mock_action("b", "fail"), mock_action("c", "ok")];
+        let output = run_group(actions, &options, mock_run(running, peak, started.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+        assert!(output.stderr.starts_with("group: executed 2 actions (success: 1, failed: 1, skipped: 1)"));
+        assert!(output.stderr.ends_with("- c: 已有子动作失败，未执行"));
+
+        let options = GroupOptions {
+            mode: GroupMode::Parallel,
+            max_parallel: Some(1),
+            stop_on_failure: true,
+        };
+        let (running, peak, started) = counters();
+        let actions = vec![mock_action("a", "fail"), mock_action("b", "ok"), mock_action("c", "ok")];
+        let output = run_group(actions, &options, mock_run(running, peak, started.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+        assert!(output.stderr.starts_with("group: executed 1 actions (success: 0, failed: 1, skipped: 2)"));
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use reqwest::{Client, Method};
+
+use crate::{feat::action::ActionOutput, schema::HttpRequest};
+
+/// 发送请求，响应体作为输出；非 2xx 响应视为失败，`exit_code` 为 HTTP 状态码
+pub async fn send(request: &HttpRequest, timeout: Duration) -> Result<ActionOutput, ActionOutput> {
+    let method = Method::from_bytes(request.method.as_bytes())
+        .map_err(|_| ActionOutput::error(format!("不支持的请求方法: {}", request.method)))?;
+    let client = Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| ActionOutput::error(format!("创建 HTTP 客户端失败: {}", e)))?;
+    let mut builder = client.request(method, request.url.trim());
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = &request.body {
+        builder = builder.body(body.clone());
+    }
+
+    let response = builder.send().await.map_err(|e| {
+        if e.is_timeout() {
+            ActionOutput::error(format!("请求超时 ({}秒)", timeout.as_secs()))
+        } else {
+            ActionOutput::error(format!("请求失败: {}", e))
+        }
+    })?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| ActionOutput::error(format!("读取响应失败: {}", e)))?;
+    let mut output = ActionOutput {
+        stdout: body,
+        stderr: String::new(),
+        exit_code: Some(status.as_u16() as i32),
+    };
+    if !status.is_success() {
+        output.stderr = format!("HTTP {}: {}", status, output.stdout.trim());
+        return Err(output);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::BTreeMap, thread};
+    use tiny_http::{Header, Response, Server};
+
+    /// 记录收到的请求并按路径返回：`/fail` 返回 503，`/slow` 延迟响应，其他回显请求内容
+    fn mock_server() -> (String, thread::JoinHandle<Vec<String>>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let handle = thread::spawn(move || {
+            let mut seen = Vec::new();
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let token = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("X-Token"))
+                    .map(|header| header.value.to_string())
+                    .unwrap_or_default();
+                let url = request.url().to_string();
+                seen.push(format!("{} {} {} {}", request.method(), url, token, body));
+                let response = match url.as_str() {
+                    "/fail" => Response::from_string("unavailable").with_status_code(503),
+                    "/slow" => {
+                        thread::sleep(Duration::from_millis(1500));
+                        Response::from_string("late")
+                    }
+                    _ => Response::from_string(format!("{{\"received\":{:?}}}", body))
+                        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap()),
+                };
+                let _ = request.respond(response);
+                if url == "/stop" {
+                    break;
+                }
+            }
+            seen
+        });
+        (addr, handle)
+    }
+
+    fn request(url: String, method: &str, body: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            url,
+            method: method.to_string(),
+            headers: BTreeMap::from([("X-Token".to_string(), "secret".to_string())]),
+            body: body.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn http_requests_report_status_and_body() {
+        let (addr, server) = mock_server();
+        let timeout = Duration::from_secs(5);
+
+        let output = send(&request(format!("{addr}/hook"), "POST", Some("on")), timeout)
+            .await
+            .unwrap();
+        assert_eq!(output.stdout, r#"{"received":"on"}"#);
+        assert_eq!(output.exit_code, Some(200));
+
+        let err = send(&request(format!("{addr}/fail"), "GET", None), timeout)
+            .await
+            .unwrap_err();
+        assert_eq!(err.exit_code, Some(503));
+        assert_eq!(err.stdout, "unavailable");
+        assert!(err.stderr.starts_with("HTTP 503"));
+
+        let err = send(&request(format!("{addr}/slow"), "GET", None), Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(err.stderr.contains("超时"));
+
+        send(&request(format!("{addr}/stop"), "DELETE", None), timeout)
+            .await
+            .unwrap();
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "POST /hook secret on",
+                "GET /fail secret ",
+                "GET /slow secret ",
+                "DELETE /stop secret ",
+            ]
+        );
+    }
+}
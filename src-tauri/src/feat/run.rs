@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+};
+
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::Emitter;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::{core::handle::Handle, logging, utils::logging::Type};
+
+/// 前台命令每输出一行发送一次的事件
+pub const ACTION_OUTPUT_EVENT: &str = "action-output";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionOutputEvent {
+    pub run_id: String,
+    pub action_id: Option<String>,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// 正在执行的命令，`pid` 为进程组的首进程
+#[derive(Debug, Clone, Serialize)]
+pub struct RunInfo {
+    pub run_id: String,
+    pub pid: u32,
+    pub action_id: Option<String>,
+    pub action_name: String,
+    /// 由任务触发时所属的任务
+    pub task_id: Option<String>,
+    pub started_at: i64,
+    /// 独立运行的命令不等待结束，也没有输出事件
+    pub detached: bool,
+}
+
+struct RunningProcess {
+    info: RunInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+fn registry() -> &'static Mutex<HashMap<String, RunningProcess>> {
+    static INSTANCE: OnceLock<Mutex<HashMap<String, RunningProcess>>> = OnceLock::new();
+    INSTANCE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 登记的运行在释放时自动移除；进程结束前被释放（如执行超时）时会终止进程
+pub struct RunGuard {
+    run_id: String,
+    pid: u32,
+    cancelled: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl RunGuard {
+    /// 进程已经结束，返回是否是被取消的
+    pub fn finish(mut self) -> bool {
+        self.finished = true;
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        registry().lock().remove(&self.run_id);
+        if self.finished {
+            return;
+        }
+        match kill_tree(self.pid) {
+            Ok(()) => {
+                logging!(info, Type::Cmd, true, "运行 {} 未结束即被中断，已终止进程 {}", self.run_id, self.pid);
+            }
+            Err(e) => {
+                logging!(warn, Type::Cmd, true, "运行 {} 中断后终止进程失败: {}", self.run_id, e);
+            }
+        }
+    }
+}
+
+/// 登记已启动的命令，便于通过 [`cancel`] 终止
+pub fn register(info: RunInfo) -> RunGuard {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let guard = RunGuard {
+        run_id: info.run_id.clone(),
+        pid: info.pid,
+        cancelled: cancelled.clone(),
+        finished: false,
+    };
+    registry()
+        .lock()
+        .insert(info.run_id.clone(), RunningProcess { info, cancelled });
+    guard
+}
+
+/// 正在执行的命令，按启动时间排序
+pub fn list() -> Vec<RunInfo> {
+    let mut runs = registry()
+        .lock()
+        .values()
+        .map(|process| process.info.clone())
+        .collect::<Vec<_>>();
+    runs.sort_by(|a, b| a.started_at.cmp(&b.started_at).then_with(|| a.run_id.cmp(&b.run_id)));
+    runs
+}
+
+/// 终止运行中的命令及其启动的子进程
+pub fn cancel(run_id: &str) -> Result<()> {
+    let pid = match registry().lock().get(run_id) {
+        Some(process) => {
+            process.cancelled.store(true, Ordering::SeqCst);
+            process.info.pid
+        }
+        None => bail!("运行 {} 不存在或已结束", run_id),
+    };
+    kill_tree(pid)?;
+    logging!(info, Type::Cmd, true, "已取消运行 {} (pid: {})", run_id, pid);
+    Ok(())
+}
+
+/// 命令启动时放入了独立的进程组，按进程组终止即可覆盖其子进程
+#[cfg(unix)]
+fn kill_tree(pid: u32) -> Result<()> {
+    let status = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pid)])
+        .status()?;
+    if !status.success() {
+        bail!("终止进程组 {} 失败: {}", pid, status);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn kill_tree(pid: u32) -> Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .creation_flags(0x08000000)
+        .status()?;
+    if !status.success() {
+        bail!("终止进程树 {} 失败: {}", pid, status);
+    }
+    Ok(())
+}
+
+/// 逐行读取输出并发送事件，返回读取到的全部内容
+pub async fn stream_lines<R: AsyncRead + Unpin>(
+    pipe: R,
+    run_id: &str,
+    action_id: Option<&str>,
+    stream: OutputStream,
+) -> Vec<u8> {
+    let app_handle = Handle::global().app_handle();
+    let mut reader = BufReader::new(pipe);
+    let mut output = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) => break,
+            Ok(_) => {
+                output.extend_from_slice(&line);
+                if let Some(app_handle) = &app_handle {
+                    let event = ActionOutputEvent {
+                        run_id: run_id.to_string(),
+                        action_id: action_id.map(str::to_string),
+                        stream,
+                        line: String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string(),
+                    };
+                    let _ = app_handle.emit(ACTION_OUTPUT_EVENT, event);
+                }
+            }
+            Err(e) => {
+                logging!(warn, Type::Cmd, true, "读取运行 {} 的输出失败: {}", run_id, e);
+                break;
+            }
+        }
+    }
+    output
+}
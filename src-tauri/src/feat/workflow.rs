@@ -0,0 +1,231 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+};
+
+use crate::{
+    feat::action::ActionOutput,
+    schema::{Action, Transition, Workflow},
+};
+
+/// 单次执行最多运行的步骤数，防止跳转形成死循环
+pub const MAX_WORKFLOW_STEPS: usize = 100;
+
+/// 步骤可用的 `prev.stdout`、`steps.<id>.exit_code` 等模板变量
+fn step_variables(outputs: &HashMap<&str, ActionOutput>, prev: &ActionOutput) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    let mut insert = |prefix: String, output: &ActionOutput| {
+        vars.insert(format!("{}.stdout", prefix), output.stdout.trim_end().to_string());
+        vars.insert(format!("{}.stderr", prefix), output.stderr.trim_end().to_string());
+        vars.insert(
+            format!("{}.exit_code", prefix),
+            output.exit_code.map(|code| code.to_string()).unwrap_or_default(),
+        );
+    };
+    for (id, output) in outputs {
+        insert(format!("steps.{}", id), output);
+    }
+    insert("prev".to_string(), prev);
+    vars
+}
+
+fn first_line(text: &str) -> &str {
+    text.trim().lines().next().unwrap_or_default()
+}
+
+/// 按工作流定义执行步骤，`actions` 为步骤引用的动作，`run` 负责执行单个动作，
+/// 并在执行时用传入的步骤变量替换参数中的模板，参数只在执行时替换一次
+pub async fn run_workflow<F, Fut>(
+    workflow: &Workflow,
+    actions: &HashMap<String, Action>,
+    mut run: F,
+) -> Result<ActionOutput, ActionOutput>
+where
+    F: FnMut(Action, BTreeMap<String, String>) -> Fut,
+    Fut: Future<Output = Result<ActionOutput, ActionOutput>>,
+{
+    let positions: HashMap<&str, usize> = workflow
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| (step.id.as_str(), index))
+        .collect();
+    let mut outputs: HashMap<&str, ActionOutput> = HashMap::new();
+    let mut prev = ActionOutput::default();
+    let mut lines = Vec::new();
+    let mut executed = 0;
+    let mut current = 0;
+    let summary = |executed: usize, lines: &[String]| {
+        format!("workflow: executed {} steps\n{}", executed, lines.join("\n"))
+    };
+
+    while let Some(step) = workflow.steps.get(current) {
+        if let Some(condition) = &step.when {
+            if !condition.matches(&prev.stdout, prev.exit_code) {
+                lines.push(format!("- {}: 条件不满足，已跳过", step.id));
+                current += 1;
+                continue;
+            }
+        }
+        executed += 1;
+        if executed > MAX_WORKFLOW_STEPS {
+            return Err(ActionOutput {
+                stdout: summary(executed - 1, &lines),
+                stderr: format!("工作流执行超过 {} 个步骤，可能存在循环跳转", MAX_WORKFLOW_STEPS),
+                exit_code: None,
+            });
+        }
+
+        let result = match actions.get(&step.action_id) {
+            Some(action) => {
+                let mut action = action.clone();
+                if step.args.is_some() {
+                    action.args = step.args.clone();
+                }
+                run(action, step_variables(&outputs, &prev)).await
+            }
+            None => Err(ActionOutput::error(format!("动作不存在: {}", step.action_id))),
+        };
+        let (success, output) = match result {
+            Ok(output) => (true, output),
+            Err(output) => (false, output),
+        };
+        lines.push(if success {
+            format!("✓ {}: {}", step.id, first_line(&output.stdout))
+        } else {
+            format!("✗ {}: {}", step.id, first_line(&output.stderr))
+        });
+        outputs.insert(step.id.as_str(), output.clone());
+        prev = output;
+
+        match if success { &step.on_success } else { &step.on_failure } {
+            Transition::Next => current += 1,
+            Transition::End => break,
+            Transition::Fail => {
+                let stderr = if success {
+                    format!("工作流在步骤 {} 后以失败结束", step.id)
+                } else {
+                    format!("工作流在步骤 {} 失败: {}", step.id, prev.stderr.trim())
+                };
+                return Err(ActionOutput {
+                    stdout: summary(executed, &lines),
+                    stderr,
+                    exit_code: prev.exit_code,
+                });
+            }
+            Transition::Goto(target) => current = positions[target.as_str()],
+        }
+    }
+    Ok(ActionOutput {
+        stdout: summary(executed, &lines),
+        stderr: String::new(),
+        exit_code: prev.exit_code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        feat::testing::{mock_action, mock_output},
+        utils::template,
+    };
+    use std::future::ready;
+
+    fn mock_step(id: &str, command: &str) -> (String, Action) {
+        (id.to_string(), mock_action(id, command))
+    }
+
+    /// 替换参数中的步骤变量后按 [`mock_output`] 返回结果
+    fn mock_run(
+        calls: &mut Vec<(String, Vec<String>)>,
+        action: Action,
+        vars: BTreeMap<String, String>,
+    ) -> impl Future<Output = Result<ActionOutput, ActionOutput>> {
+        let args: Vec<String> = action
+            .args
+            .iter()
+            .flatten()
+            .map(|arg| template::render(arg, |name| vars.get(name).cloned()))
+            .collect();
+        calls.push((action.name.clone(), args.clone()));
+        ready(mock_output(&action, &args))
+    }
+
+    fn build_then_notify(build: &str) -> (Workflow, HashMap<String, Action>) {
+        let workflow = Workflow::parse(
+            r#"{"steps": [
+                {"id": "build", "action_id": "build", "on_failure": {"goto": "notify"}},
+                {"id": "deploy", "action_id": "deploy", "args": ["{{steps.build.stdout}}"],
+                 "when": {"stdout_contains": "done"}, "on_success": "end"},
+                {"id": "notify", "action_id": "notify",
+                 "args": ["{{prev.stderr}}", "code={{prev.exit_code}}"], "on_success": "fail"}
+            ]}"#,
+        )
+        .unwrap();
+        let actions = HashMap::from([
+            mock_step("build", build),
+            mock_step("deploy", "ok"),
+            mock_step("notify", "ok"),
+        ]);
+        (workflow, actions)
+    }
+
+    #[tokio::test]
+    async fn success_branch_passes_output_to_next_step() {
+        let (workflow, actions) = build_then_notify("ok");
+        let mut calls = Vec::new();
+        let output = run_workflow(&workflow, &actions, |action, vars| mock_run(&mut calls, action, vars))
+            .await
+            .unwrap();
+        assert_eq!(
+            calls,
+            vec![
+                ("build".to_string(), vec![]),
+                ("deploy".to_string(), vec!["build done".to_string()]),
+            ]
+        );
+        assert!(output.stdout.starts_with("workflow: executed 2 steps"));
+    }
+
+    #[tokio::test]
+    async fn failure_branch_runs_and_fails_workflow() {
+        let (workflow, actions) = build_then_notify("fail");
+        let mut calls = Vec::new();
+        let output = run_workflow(&workflow, &actions, |action, vars| mock_run(&mut calls, action, vars))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            calls,
+            vec![
+                ("build".to_string(), vec![]),
+                (
+                    "notify".to_string(),
+                    vec!["build failed".to_string(), "code=2".to_string()]
+                ),
+            ]
+        );
+        assert!(output.stdout.contains("✗ build: build failed"));
+        assert_eq!(output.stderr, "工作流在步骤 notify 后以失败结束");
+    }
+
+    #[tokio::test]
+    async fn unmet_condition_skips_step_and_loops_are_capped() {
+        let workflow = Workflow::parse(
+            r#"{"steps": [
+                {"id": "check", "action_id": "check"},
+                {"id": "skip", "action_id": "check", "when": {"exit_code": 1}},
+                {"id": "again", "action_id": "check", "on_success": {"goto": "again"}}
+            ]}"#,
+        )
+        .unwrap();
+        let actions = HashMap::from([mock_step("check", "ok")]);
+        let mut calls = Vec::new();
+        let output = run_workflow(&workflow, &actions, |action, vars| mock_run(&mut calls, action, vars))
+            .await
+            .unwrap_err();
+        assert_eq!(calls.len(), MAX_WORKFLOW_STEPS);
+        assert!(output.stdout.contains("- skip: 条件不满足，已跳过"));
+        assert!(output.stderr.contains("循环跳转"));
+    }
+}
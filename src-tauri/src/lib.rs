@@ -10,9 +10,10 @@ mod utils;
 use parking_lot::{Mutex, Once};
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 use tracing::Level;
 
-// use utils::logging::Type;
+use utils::logging::Type;
 
 use core::{app_init, handle::Handle};
 use schema::state::AppState;
@@ -81,6 +82,41 @@ impl AppHandleManager {
     }
 }
 
+/// 不需要应用上下文的命令行子命令在这里直接执行并返回退出码，其余情况返回 None 继续启动应用
+///
+/// 命令总是在调用方进程中执行，输出与退出码都交给调用方；修改数据后再通知正在运行的实例刷新调度与界面。
+/// 执行动作需要应用上下文，在 `run` 中以无界面方式处理。
+pub fn run_cli() -> Option<i32> {
+    let command = match core::cli::command_from_env() {
+        Ok(Some(command)) => command,
+        Ok(None) => return None,
+        Err(e) => {
+            core::cli::attach_console();
+            eprintln!("{:#}", e);
+            return Some(2);
+        }
+    };
+    core::cli::attach_console();
+    if let core::cli::CliCommand::Refresh { .. } = command {
+        // 没有正在运行的实例时无需刷新，有则交给单实例插件转发
+        return if core::cli::instance_running() { None } else { Some(0) };
+    }
+    if command.needs_app() {
+        return None;
+    }
+    match core::cli::run_direct(&command) {
+        Ok(output) => {
+            println!("{}", output);
+            core::cli::notify_running_instance(&command);
+            Some(0)
+        }
+        Err(e) => {
+            eprintln!("{:#}", e);
+            Some(1)
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let subscriber = tracing_subscriber::fmt()
@@ -103,14 +139,62 @@ pub fn run() {
         .setup(|app| {
             let local_data_dir = app.handle().path().app_data_dir().unwrap();
             std::fs::create_dir_all(&local_data_dir).expect("Failed to create app data dir");
-            let db = Database::new(local_data_dir).expect("Failed to initialize database");
+            // 先初始化句柄，数据库初始化失败时也能通过 Handle 提示用户
+            AppHandleManager::global().init(app.handle().clone());
+            Handle::global().init(app.handle().clone());
+            let db = match Database::new(local_data_dir.clone()) {
+                Ok(db) => db,
+                Err(e) => {
+                    logging!(error, Type::Database, true, "数据库初始化失败: {:#}", e);
+                    // 不能让用户在临时数据库中继续操作，退出前提示备份所在位置
+                    let message = format!(
+                        "数据库初始化失败，应用将退出以免数据丢失。\n{:#}\n\n升级前的备份位于: {}",
+                        e,
+                        local_data_dir.join("backups").display()
+                    );
+                    if matches!(core::cli::command_from_env(), Ok(Some(_))) {
+                        eprintln!("{}", message);
+                        app.handle().exit(1);
+                        return Ok(());
+                    }
+                    // 启动已经中止，不再等待界面加载，消息直接以系统通知展示
+                    Handle::global().mark_startup_completed();
+                    Handle::notice_message("Error", message.clone());
+                    let app_handle = app.handle().clone();
+                    app.dialog()
+                        .message(message)
+                        .title("Error")
+                        .kind(MessageDialogKind::Error)
+                        .show(move |_| app_handle.exit(1));
+                    return Ok(());
+                }
+            };
             {
                 let state = app.state::<AppState>();
                 let mut db_guard = state.db.lock();
                 *db_guard = db;
             }
-            AppHandleManager::global().init(app.handle().clone());
-            Handle::global().init(app.handle().clone());
+            // 执行动作等命令在这里执行，不启动界面；通过 app.exit 退出以便正常清理
+            if let Ok(Some(command)) = core::cli::command_from_env() {
+                let code = tauri::async_runtime::block_on(async {
+                    match core::cli::execute(&command, app.handle()).await {
+                        Ok(output) => {
+                            if !output.is_empty() {
+                                println!("{}", output);
+                            }
+                            core::cli::notify_running_instance(&command);
+                            0
+                        }
+                        Err(e) => {
+                            eprintln!("{:#}", e);
+                            1
+                        }
+                    }
+                });
+                app.handle().exit(code);
+                return Ok(());
+            }
+            core::cli::mark_instance_running(&local_data_dir);
             tauri::async_runtime::block_on(async move {
                 resolve::resolve_setup(app).await;
                 // 启动时检查周期性任务
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, bail, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::{
+    config::Config,
+    core::handle::Handle,
+    feat::action::execute_action_by_id,
+    get_app_handle, logging,
+    schema::{AppState, RunTrigger, WindowType},
+    service::dependency,
+    singleton,
+    store::module::TaskManager,
+    utils::{logging::Type, window_manager},
+};
+
+/// 全局快捷键触发的操作，以 `type` 区分
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HotkeyCommand {
+    /// 显示或隐藏窗口，`window` 为窗口标签，如 main、dashboard、action
+    ToggleWindow { window: String },
+    /// 打开新建任务窗口
+    QuickAddTask,
+    RunAction { action_id: String },
+    /// 将最早到期的未完成任务标记为已完成
+    CompleteNextTask,
+}
+
+impl HotkeyCommand {
+    fn validate(&self) -> Result<()> {
+        match self {
+            HotkeyCommand::ToggleWindow { window } => {
+                if WindowType::from_label(window).is_none() {
+                    bail!("未知的窗口: {}", window);
+                }
+            }
+            HotkeyCommand::RunAction { action_id } => {
+                if action_id.trim().is_empty() {
+                    bail!("未指定要执行的动作");
+                }
+            }
+            HotkeyCommand::QuickAddTask | HotkeyCommand::CompleteNextTask => {}
+        }
+        Ok(())
+    }
+}
+
+/// 快捷键配置中的一项
+struct Binding {
+    accelerator: String,
+    shortcut: Shortcut,
+    command: HotkeyCommand,
+}
+
+/// 解析快捷键配置，快捷键或操作无效、多个写法不同的快捷键实际相同时返回全部错误
+fn parse_hotkeys(hotkeys: &BTreeMap<String, HotkeyCommand>) -> Result<Vec<Binding>> {
+    let mut seen: HashMap<u32, &str> = HashMap::new();
+    let mut errors = Vec::new();
+    let mut bindings = Vec::with_capacity(hotkeys.len());
+    for (accelerator, command) in hotkeys {
+        let shortcut = match accelerator.parse::<Shortcut>() {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                errors.push(format!("快捷键 {} 无效: {}", accelerator, e));
+                continue;
+            }
+        };
+        if let Err(e) = command.validate() {
+            errors.push(format!("快捷键 {} 的操作无效: {}", accelerator, e));
+            continue;
+        }
+        if let Some(other) = seen.insert(shortcut.id(), accelerator) {
+            errors.push(format!("快捷键 {} 与 {} 冲突", accelerator, other));
+            continue;
+        }
+        bindings.push(Binding {
+            accelerator: accelerator.clone(),
+            shortcut,
+            command: command.clone(),
+        });
+    }
+    if !errors.is_empty() {
+        bail!("{}", errors.join("; "));
+    }
+    Ok(bindings)
+}
+
+/// 检查快捷键配置，供保存配置前使用
+pub fn validate_hotkeys(hotkeys: &BTreeMap<String, HotkeyCommand>) -> Result<()> {
+    parse_hotkeys(hotkeys).map(|_| ())
+}
+
+/// 按配置中的 `hotkeys` 注册全局快捷键
+pub struct Hotkey {
+    registered: Mutex<Vec<(String, Shortcut)>>,
+}
+
+impl Hotkey {
+    fn new() -> Self {
+        Self {
+            registered: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 注销之前注册的快捷键后按当前配置重新注册；配置有冲突时不注册任何快捷键，
+    /// 被其他程序占用的快捷键跳过并返回错误
+    pub fn apply_config(&self) -> Result<()> {
+        let hotkeys = Config::global().lock().hotkeys.clone().unwrap_or_default();
+        let app_handle = Handle::global()
+            .app_handle()
+            .ok_or_else(|| anyhow!("app_handle is None"))?;
+        let global_shortcut = app_handle.global_shortcut();
+
+        let mut registered = self.registered.lock();
+        for (accelerator, shortcut) in registered.drain(..) {
+            if let Err(e) = global_shortcut.unregister(shortcut) {
+                logging!(warn, Type::Hotkey, true, "注销快捷键 {} 失败: {}", accelerator, e);
+            }
+        }
+
+        let mut failures = Vec::new();
+        for binding in parse_hotkeys(&hotkeys)? {
+            let command = binding.command;
+            let result = global_shortcut.on_shortcut(binding.shortcut, move |_, _, event| {
+                if event.state == ShortcutState::Pressed {
+                    run_command(&command);
+                }
+            });
+            match result {
+                Ok(()) => {
+                    logging!(info, Type::Hotkey, true, "已注册快捷键 {}", binding.accelerator);
+                    registered.push((binding.accelerator, binding.shortcut));
+                }
+                Err(e) => failures.push(format!("快捷键 {} 注册失败，可能已被其他程序占用: {}", binding.accelerator, e)),
+            }
+        }
+        if !failures.is_empty() {
+            bail!("{}", failures.join("; "));
+        }
+        Ok(())
+    }
+}
+
+singleton!(Hotkey, HOTKEY_INSTANCE);
+
+fn run_command(command: &HotkeyCommand) {
+    logging!(info, Type::Hotkey, true, "触发快捷键操作: {:?}", command);
+    match command {
+        HotkeyCommand::ToggleWindow { window } => {
+            let _ = window_manager::toggle_window_by_label(window);
+        }
+        HotkeyCommand::QuickAddTask => {
+            window_manager::show_window_by_label(WindowType::Task.label(), Some(WindowType::Task.url()));
+        }
+        HotkeyCommand::RunAction { action_id } => {
+            let action_id = action_id.clone();
+            async_runtime::spawn(async move {
+                let app_handle = get_app_handle!();
+                if let Err(e) = execute_action_by_id(&app_handle, &action_id, RunTrigger::Hotkey).await {
+                    logging!(error, Type::Hotkey, true, "快捷键执行动作 {} 失败: {}", action_id, e);
+                }
+            });
+        }
+        HotkeyCommand::CompleteNextTask => {
+            async_runtime::spawn(async {
+                if let Err(e) = complete_next_task().await {
+                    logging!(error, Type::Hotkey, true, "快捷键完成任务失败: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// 将最早到期的未完成任务标记为已完成，并调度因此解除阻塞的任务
+async fn complete_next_task() -> Result<()> {
+    let app_handle = get_app_handle!();
+    let task = {
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        let task = db
+            .get_tasks_by_status(false)?
+            .into_iter()
+            .min_by_key(|task| task.due_to);
+        if let Some(task) = &task {
+            db.update_task_status(&task.id, true)?;
+        }
+        task
+    };
+    let Some(task) = task else {
+        logging!(info, Type::Hotkey, true, "没有未完成的任务");
+        return Ok(());
+    };
+    logging!(info, Type::Hotkey, true, "已通过快捷键完成任务 {}", task.name);
+    dependency::on_tasks_status_changed(vec![task.id], true).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkeys(entries: &[(&str, HotkeyCommand)]) -> BTreeMap<String, HotkeyCommand> {
+        entries
+            .iter()
+            .map(|(accelerator, command)| (accelerator.to_string(), command.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn hotkeys_reject_invalid_entries_and_conflicts() {
+        let config: BTreeMap<String, HotkeyCommand> = serde_json::from_str(
+            r#"{
+                "CmdOrCtrl+Shift+D": {"type": "toggle_window", "window": "dashboard"},
+                "Alt+N": {"type": "quick_add_task"},
+                "Alt+R": {"type": "run_action", "action_id": "act123"},
+                "Alt+Enter": {"type": "complete_next_task"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parse_hotkeys(&config).unwrap().len(), 4);
+
+        // 修饰键顺序与大小写不同但实际相同
+        let err = validate_hotkeys(&hotkeys(&[
+            ("Shift+Alt+K", HotkeyCommand::QuickAddTask),
+            ("alt+shift+k", HotkeyCommand::CompleteNextTask),
+        ]))
+        .unwrap_err();
+        assert!(err.to_string().contains("冲突"), "{}", err);
+
+        let err = validate_hotkeys(&hotkeys(&[
+            ("Alt+NotAKey", HotkeyCommand::QuickAddTask),
+            (
+                "Alt+W",
+                HotkeyCommand::ToggleWindow {
+                    window: "unknown".to_string(),
+                },
+            ),
+            (
+                "Alt+X",
+                HotkeyCommand::RunAction {
+                    action_id: " ".to_string(),
+                },
+            ),
+        ]))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("Alt+NotAKey"), "{}", err);
+        assert!(err.contains("未知的窗口"), "{}", err);
+        assert!(err.contains("未指定要执行的动作"), "{}", err);
+    }
+}
@@ -0,0 +1,99 @@
+use std::{sync::Arc, thread};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{config::Config, logging, service::ical, singleton, utils::logging::Type};
+
+pub const DEFAULT_ICS_FEED_PORT: u16 = 18230;
+const ICS_FEED_PATH: &str = "/ducker.ics";
+
+/// 本地日历订阅服务，仅监听 127.0.0.1，供日历客户端订阅 `http://127.0.0.1:端口/ducker.ics`
+pub struct IcsFeed {
+    server: Mutex<Option<(u16, Arc<Server>)>>,
+}
+
+impl IcsFeed {
+    fn new() -> Self {
+        Self {
+            server: Mutex::new(None),
+        }
+    }
+
+    /// 按配置启动、重启或关闭订阅服务
+    pub fn apply_config(&self) -> Result<()> {
+        let (enabled, port) = {
+            let config = Config::global().lock();
+            (
+                config.ics_feed_enabled.unwrap_or(false),
+                config.ics_feed_port.unwrap_or(DEFAULT_ICS_FEED_PORT),
+            )
+        };
+        let mut guard = self.server.lock();
+        if let Some((running_port, _)) = guard.as_ref() {
+            if enabled && *running_port == port {
+                return Ok(());
+            }
+        }
+        if let Some((running_port, server)) = guard.take() {
+            server.unblock();
+            logging!(info, Type::Service, true, "日历订阅服务已停止: {}", running_port);
+        }
+        if !enabled {
+            return Ok(());
+        }
+
+        let server = Arc::new(
+            Server::http(("127.0.0.1", port))
+                .map_err(|e| anyhow!("日历订阅服务监听端口 {} 失败: {}", port, e))?,
+        );
+        let worker = server.clone();
+        thread::Builder::new()
+            .name("ics-feed".into())
+            .spawn(move || {
+                for request in worker.incoming_requests() {
+                    handle_request(request);
+                }
+            })?;
+        logging!(
+            info,
+            Type::Service,
+            true,
+            "日历订阅服务已启动: http://127.0.0.1:{}{}",
+            port,
+            ICS_FEED_PATH
+        );
+        *guard = Some((port, server));
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some((_, server)) = self.server.lock().take() {
+            server.unblock();
+        }
+    }
+}
+
+singleton!(IcsFeed, ICS_FEED_INSTANCE);
+
+fn handle_request(request: Request) {
+    let path = request.url().split('?').next().unwrap_or_default();
+    let response = if request.method() != &Method::Get || path != ICS_FEED_PATH {
+        Response::from_string("Not Found").with_status_code(404)
+    } else {
+        match ical::build_calendar() {
+            Ok(calendar) => Response::from_string(calendar).with_header(
+                Header::from_bytes("Content-Type", "text/calendar; charset=utf-8")
+                    .expect("valid header"),
+            ),
+            Err(e) => {
+                logging!(error, Type::Service, true, "生成日历失败: {}", e);
+                Response::from_string("Internal Server Error").with_status_code(500)
+            }
+        }
+    };
+    if let Err(e) = request.respond(response) {
+        logging!(warn, Type::Service, "日历订阅响应失败: {}", e);
+    }
+}
@@ -1,2 +1,5 @@
 pub mod lightweight;
 pub mod auto_launch;
+pub mod hotkey;
+pub mod ics_feed;
+pub mod rest_api;
@@ -0,0 +1,470 @@
+use std::{io::Read, sync::Arc, thread};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{async_runtime, Emitter, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    config::Config,
+    core::timer::Timer,
+    feat::action::{execute_action_by_id, ActionVars},
+    get_app_handle, logging,
+    schema::{
+        Action, AppState, PeriodicTask, PeriodicTaskData, RunTrigger, TaskData, TaskView,
+    },
+    service::{dependency, execute::execute_plural_actions, hub::Hub, periodic},
+    singleton,
+    store::module::{ActionManager, PeriodicTaskManager, TaskManager},
+    utils::{help::random_string, logging::Type},
+};
+
+pub const DEFAULT_API_PORT: u16 = 18231;
+/// 请求体大小上限
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// 本地 HTTP API，仅监听 127.0.0.1，接口与前端使用的 IPC 命令一一对应
+///
+/// 所有请求都需要携带 `Authorization: Bearer <api_token>`。
+pub struct RestApi {
+    server: Mutex<Option<(u16, Arc<Server>)>>,
+}
+
+impl RestApi {
+    fn new() -> Self {
+        Self {
+            server: Mutex::new(None),
+        }
+    }
+
+    /// 按配置启动、重启或关闭 API 服务，启用时若未设置令牌则自动生成
+    pub fn apply_config(&self) -> Result<()> {
+        let (enabled, port) = {
+            let mut config = Config::global().lock();
+            let enabled = config.api_enabled.unwrap_or(false);
+            if enabled && config.api_token.as_deref().map_or(true, str::is_empty) {
+                config.api_token = Some(random_string(32));
+                config.save()?;
+                logging!(info, Type::Service, true, "已为本地 API 生成访问令牌");
+            }
+            (enabled, config.api_port.unwrap_or(DEFAULT_API_PORT))
+        };
+        let mut guard = self.server.lock();
+        if let Some((running_port, _)) = guard.as_ref() {
+            if enabled && *running_port == port {
+                return Ok(());
+            }
+        }
+        if let Some((running_port, server)) = guard.take() {
+            server.unblock();
+            logging!(info, Type::Service, true, "本地 API 已停止: {}", running_port);
+        }
+        if !enabled {
+            return Ok(());
+        }
+
+        let server = Arc::new(
+            Server::http(("127.0.0.1", port))
+                .map_err(|e| anyhow!("本地 API 监听端口 {} 失败: {}", port, e))?,
+        );
+        let worker = server.clone();
+        thread::Builder::new()
+            .name("rest-api".into())
+            .spawn(move || {
+                for request in worker.incoming_requests() {
+                    // 执行动作可能耗时较长，每个请求单独处理
+                    async_runtime::spawn(handle_request(request));
+                }
+            })?;
+        logging!(info, Type::Service, true, "本地 API 已启动: http://127.0.0.1:{}/api", port);
+        *guard = Some((port, server));
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some((_, server)) = self.server.lock().take() {
+            server.unblock();
+        }
+    }
+}
+
+singleton!(RestApi, REST_API_INSTANCE);
+
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        let status = match e.downcast_ref::<rusqlite::Error>() {
+            Some(rusqlite::Error::QueryReturnedNoRows) => 404,
+            _ => 500,
+        };
+        Self::new(status, e.to_string())
+    }
+}
+
+type ApiResult = Result<(u16, Value), ApiError>;
+
+fn ok<T: Serialize>(value: T) -> ApiResult {
+    Ok((200, serde_json::to_value(value).map_err(anyhow::Error::from)?))
+}
+
+fn created(id: String) -> ApiResult {
+    Ok((201, json!({ "id": id })))
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::new(400, format!("请求体格式错误: {}", e)))
+}
+
+/// 按字节异或后再判断，比较耗时与令牌内容无关
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn authorized(request: &Request, token: Option<&str>) -> bool {
+    let Some(token) = token.filter(|t| !t.is_empty()) else {
+        return false;
+    };
+    request.headers().iter().any(|header| {
+        header.field.equiv("Authorization")
+            && header
+                .value
+                .as_str()
+                .strip_prefix("Bearer ")
+                .is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes()))
+    })
+}
+
+async fn handle_request(request: Request) {
+    let token = Config::global().lock().api_token.clone();
+    respond(request, token.as_deref()).await;
+}
+
+/// 校验令牌后分发请求，`token` 为空时拒绝所有请求
+async fn respond(mut request: Request, token: Option<&str>) {
+    let method = request.method().clone();
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path.to_string(), query.to_string()),
+        None => (request.url().to_string(), String::new()),
+    };
+    let mut body = String::new();
+    let read = request.as_reader().take(MAX_BODY_BYTES).read_to_string(&mut body);
+    let result = if !authorized(&request, token) {
+        Err(ApiError::new(401, "未授权"))
+    } else if let Err(e) = read {
+        Err(ApiError::new(400, format!("读取请求体失败: {}", e)))
+    } else {
+        route(&method, &path, &query, &body).await
+    };
+    let (status, value) = match result {
+        Ok(response) => response,
+        Err(e) => {
+            if e.status >= 500 {
+                logging!(error, Type::Service, true, "本地 API {} {} 失败: {}", method, path, e.message);
+            }
+            (e.status, json!({ "error": e.message }))
+        }
+    };
+    let response = Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes("Content-Type", "application/json; charset=utf-8")
+                .expect("valid header"),
+        );
+    if let Err(e) = request.respond(response) {
+        logging!(warn, Type::Service, "本地 API 响应失败: {}", e);
+    }
+}
+
+async fn route(method: &Method, path: &str, query: &str, body: &str) -> ApiResult {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let response = match (method, segments.as_slice()) {
+        (Method::Get, ["api", "tasks"]) => list_tasks(query),
+        (Method::Post, ["api", "tasks"]) => create_task(parse_body(body)?),
+        (Method::Get, ["api", "tasks", id]) => get_task(id),
+        (Method::Put, ["api", "tasks", id]) => update_task(id, parse_body(body)?),
+        (Method::Delete, ["api", "tasks", id]) => delete_task(id),
+        (Method::Put, ["api", "tasks", id, "status"]) => {
+            return update_task_status(id, parse_body(body)?).await
+        }
+        (Method::Post, ["api", "tasks", id, "execute"]) => return execute_task(id).await,
+
+        (Method::Get, ["api", "actions"]) => list_actions(),
+        (Method::Post, ["api", "actions"]) => create_action(parse_body(body)?),
+        (Method::Get, ["api", "actions", id]) => get_action(id),
+        (Method::Put, ["api", "actions", id]) => update_action(id, parse_body(body)?),
+        (Method::Delete, ["api", "actions", id]) => delete_action(id),
+        (Method::Post, ["api", "actions", id, "run"]) => return run_action(id).await,
+
+        (Method::Get, ["api", "periodic"]) => list_periodic_tasks(),
+        (Method::Post, ["api", "periodic"]) => create_periodic_task(parse_body(body)?),
+        (Method::Get, ["api", "periodic", id]) => get_periodic_task(id),
+        (Method::Put, ["api", "periodic", id]) => update_periodic_task(id, parse_body(body)?),
+        (Method::Delete, ["api", "periodic", id]) => delete_periodic_task(id),
+
+        (Method::Get, ["api", "schedule", "weekly"]) => ok(periodic::get_weekly_tasks()),
+        (Method::Get, ["api", "schedule", "monthly"]) => ok(periodic::get_monthly_tasks()),
+        _ => Err(ApiError::new(404, format!("未知接口: {} {}", method, path))),
+    };
+    // 写操作后刷新调度，并通知前端重新加载
+    if response.is_ok() && *method != Method::Get {
+        Hub::global().refresh().await;
+        Timer::global().refresh()?;
+        get_app_handle!()
+            .emit("task-changed", None::<()>)
+            .map_err(anyhow::Error::from)?;
+    }
+    response
+}
+
+fn with_state<T>(f: impl FnOnce(&AppState) -> Result<T>) -> Result<T, ApiError> {
+    let app_handle = get_app_handle!();
+    let state = app_handle.state::<AppState>();
+    Ok(f(state.inner())?)
+}
+
+fn list_tasks(query: &str) -> ApiResult {
+    let completed = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "completed")
+        .map(|(_, value)| value == "true");
+    let tasks = with_state(|state| {
+        let records = {
+            let db = state.db.lock();
+            match completed {
+                Some(completed) => db.get_tasks_by_status(completed)?,
+                None => db.get_all_tasks()?,
+            }
+        };
+        records
+            .iter()
+            .map(|record| TaskView::try_from((record, state)))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    ok(tasks)
+}
+
+fn get_task(id: &str) -> ApiResult {
+    let task = with_state(|state| {
+        let record = state.db.lock().get_task(id)?;
+        TaskView::try_from((&record, state))
+    })?;
+    ok(task)
+}
+
+fn create_task(task: TaskData) -> ApiResult {
+    let record = with_state(|state| state.db.lock().create_task(&task))?;
+    created(record.id)
+}
+
+fn update_task(id: &str, task: TaskData) -> ApiResult {
+    let record = with_state(|state| {
+        let db = state.db.lock();
+        let record = db.get_task(id)?;
+        let updated = db.update_task(id, &task)?;
+        // 与 IPC 一致：更新成功后，从周期任务转换为普通任务时删除周期规则
+        if let (Some(periodic_id), None) = (&record.periodic, &task.periodic) {
+            db.delete_periodic_task(periodic_id)?;
+        }
+        Ok(updated)
+    })?;
+    ok(record)
+}
+
+#[derive(Deserialize)]
+struct StatusBody {
+    completed: bool,
+}
+
+async fn update_task_status(id: &str, body: StatusBody) -> ApiResult {
+    with_state(|state| state.db.lock().update_task_status(id, body.completed))?;
+    // 会刷新调度并通知前端
+    dependency::on_tasks_status_changed(vec![id.to_string()], body.completed).await?;
+    get_app_handle!()
+        .emit("task-changed", None::<()>)
+        .map_err(anyhow::Error::from)?;
+    ok(json!({ "completed": body.completed }))
+}
+
+fn delete_task(id: &str) -> ApiResult {
+    with_state(|state| state.db.lock().delete_task(id))?;
+    ok(json!({ "id": id }))
+}
+
+async fn execute_task(id: &str) -> ApiResult {
+    let task = with_state(|state| {
+        let record = state.db.lock().get_task(id)?;
+        TaskView::try_from((&record, state))
+    })?;
+    let vars = ActionVars::for_task(&task);
+    let output = execute_plural_actions(task.actions.unwrap_or_default(), RunTrigger::Api, &vars)
+        .await
+        .map_err(|e| ApiError::new(422, e))?;
+    ok(json!({ "output": output }))
+}
+
+fn list_actions() -> ApiResult {
+    let actions = with_state(|state| state.db.lock().get_all_actions())?;
+    ok(actions.into_iter().map(Action::from).collect::<Vec<_>>())
+}
+
+fn get_action(id: &str) -> ApiResult {
+    let action = with_state(|state| state.db.lock().get_action(id))?;
+    ok(Action::from(action))
+}
+
+fn create_action(action: Action) -> ApiResult {
+    let record = with_state(|state| state.db.lock().create_action(&action))?;
+    created(record.id)
+}
+
+fn update_action(id: &str, action: Action) -> ApiResult {
+    let record = with_state(|state| state.db.lock().update_action(id, &action))?;
+    ok(Action::from(record))
+}
+
+fn delete_action(id: &str) -> ApiResult {
+    with_state(|state| state.db.lock().delete_action(id))?;
+    ok(json!({ "id": id }))
+}
+
+async fn run_action(id: &str) -> ApiResult {
+    // 先确认动作存在，以便区分 404 与执行失败
+    with_state(|state| state.db.lock().get_action(id))?;
+    let output = execute_action_by_id(&get_app_handle!(), id, RunTrigger::Api)
+        .await
+        .map_err(|e| ApiError::new(422, e))?;
+    ok(json!({ "output": output }))
+}
+
+fn list_periodic_tasks() -> ApiResult {
+    let tasks = with_state(|state| {
+        let records = state.db.lock().get_enabled_periodic_tasks()?;
+        records
+            .iter()
+            .map(|record| PeriodicTask::try_from((record, state)))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    ok(tasks)
+}
+
+fn get_periodic_task(id: &str) -> ApiResult {
+    let task = with_state(|state| {
+        let record = state.db.lock().get_periodic_task(id)?;
+        PeriodicTask::try_from((&record, state))
+    })?;
+    ok(task)
+}
+
+fn create_periodic_task(task: PeriodicTaskData) -> ApiResult {
+    let record = with_state(|state| state.db.lock().create_periodic_task(&task))?;
+    created(record.id)
+}
+
+fn update_periodic_task(id: &str, mut task: PeriodicTaskData) -> ApiResult {
+    let periodic = with_state(|state| {
+        let record = {
+            let db = state.db.lock();
+            let record = db.get_task(id)?;
+            task.task.id = Some(id.to_string());
+            db.update_task(id, &task.task)?;
+            match &record.periodic {
+                Some(periodic_id) => db.update_periodic_task(periodic_id, &task)?,
+                // 普通任务转换为周期任务时只创建周期规则
+                None => db.create_periodic_rule_only(&task)?,
+            }
+        };
+        PeriodicTask::try_from((&record, state))
+    })?;
+    ok(periodic)
+}
+
+fn delete_periodic_task(id: &str) -> ApiResult {
+    with_state(|state| state.db.lock().delete_periodic_task(id))?;
+    ok(json!({ "id": id }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 由测试中的服务端处理一个请求，返回状态码与响应体
+    async fn call(
+        server: &Arc<Server>,
+        method: reqwest::Method,
+        path: &str,
+        auth: Option<&str>,
+        body: &str,
+    ) -> (u16, Value) {
+        let url = format!("http://{}{}", server.server_addr().to_ip().unwrap(), path);
+        let mut builder = reqwest::Client::new().request(method, url).body(body.to_string());
+        if let Some(auth) = auth {
+            builder = builder.header("Authorization", auth);
+        }
+        let response = tokio::spawn(builder.send());
+        let receiver = server.clone();
+        let request = tokio::task::spawn_blocking(move || receiver.recv().unwrap())
+            .await
+            .unwrap();
+        respond(request, Some("secret-token")).await;
+        let response = response.await.unwrap().unwrap();
+        let status = response.status().as_u16();
+        (status, serde_json::from_str(&response.text().await.unwrap()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn requests_are_authorized_and_routed() {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let get = reqwest::Method::GET;
+
+        let (status, body) = call(&server, get.clone(), "/api/tasks", None, "").await;
+        assert_eq!(status, 401);
+        assert_eq!(body["error"], "未授权");
+        for auth in ["Bearer secret-tokeX", "Bearer secret", "secret-token", "Bearer "] {
+            let (status, _) = call(&server, get.clone(), "/api/tasks", Some(auth), "").await;
+            assert_eq!(status, 401, "{}", auth);
+        }
+
+        let auth = Some("Bearer secret-token");
+        let (status, body) = call(&server, get.clone(), "/api/unknown", auth, "").await;
+        assert_eq!(status, 404);
+        assert!(body["error"].as_str().unwrap().contains("/api/unknown"));
+        // 方法不匹配的已知路径同样找不到接口
+        let (status, _) = call(&server, reqwest::Method::PATCH, "/api/tasks", auth, "").await;
+        assert_eq!(status, 404);
+
+        let (status, body) = call(&server, reqwest::Method::POST, "/api/tasks", auth, "not json").await;
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("请求体格式错误"));
+        let (status, _) = call(&server, reqwest::Method::PUT, "/api/tasks/t1/status", auth, "{}").await;
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn token_comparison_and_error_status() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+
+        let missing = ApiError::from(anyhow::Error::from(rusqlite::Error::QueryReturnedNoRows));
+        assert_eq!(missing.status, 404);
+        assert_eq!(ApiError::from(anyhow!("boom")).status, 500);
+    }
+}
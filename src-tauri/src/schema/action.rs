@@ -1,6 +1,11 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+use super::{RetryPolicy, Workflow};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Action {
     pub id: Option<String>,
     pub name: String,
@@ -13,6 +18,48 @@ pub struct Action {
     pub command: String,
     pub args: Option<Vec<String>>,
     pub count: Option<usize>,
+    /// 命令的工作目录，仅 Command 类型使用
+    pub cwd: Option<String>,
+    /// 追加到命令进程中的环境变量
+    pub env: Option<BTreeMap<String, String>>,
+    /// 启动命令后写入其标准输入的内容
+    pub stdin: Option<String>,
+    /// 命令的标准输出与标准错误追加写入的文件，适用于长时间运行的任务
+    pub output_file: Option<String>,
+    /// 命令动作的执行策略，未设置时直接执行
+    pub trust: Option<TrustLevel>,
+    /// 重试的等待方式与条件，未设置时每次间隔 1 秒、任何失败都重试
+    pub retry_policy: Option<RetryPolicy>,
+}
+
+/// 命令与请求动作的执行策略
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustLevel {
+    #[default]
+    Always,
+    /// 每次执行前弹窗确认
+    Confirm,
+    Deny,
+}
+
+impl TrustLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrustLevel::Always => "always",
+            TrustLevel::Confirm => "confirm",
+            TrustLevel::Deny => "deny",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "always" => Some(TrustLevel::Always),
+            "confirm" => Some(TrustLevel::Confirm),
+            "deny" => Some(TrustLevel::Deny),
+            _ => None,
+        }
+    }
 }
 
 impl From<ActionRecord> for Action {
@@ -20,34 +67,25 @@ impl From<ActionRecord> for Action {
     fn from(value: ActionRecord) -> Self {
         let (command, args) = match value.typ {
             ActionType::Notice => {
-                // Notice类型：command字段代表标题，args字段代表body（多个arg用逗号分割表示换行）
-                let title = value.command;
-                let body_parts: Vec<String> = if value.args.is_empty() {
-                    vec![]
-                } else {
-                    value.args.split(",").map(|s| s.trim().to_string()).collect()
-                };
-                (title, Some(body_parts))
+                // Notice类型：command字段代表标题，args字段代表body
+                (value.command, Some(value.args))
             },
             ActionType::Group => {
-                // Group类型：command字段置"group"占位，args字段为组内各action的id
-                let group_placeholder = "group".to_string();
-                let action_ids: Vec<String> = if value.args.is_empty() {
-                    vec![]
+                // Group类型：command字段为执行方式（旧数据为"group"占位），args字段为组内各action的id
+                let group_placeholder = if value.command.trim().is_empty() {
+                    GROUP_PLACEHOLDER.to_string()
                 } else {
-                    value.args.split(",").map(|s| s.trim().to_string()).collect()
+                    value.command
                 };
-                (group_placeholder, Some(action_ids))
+                (group_placeholder, Some(value.args))
             },
             _ => {
-                // 其他类型保持原有逻辑：args按逗号分割
-                let command = value.command;
                 let args = if value.args.is_empty() {
                     None
                 } else {
-                    Some(value.args.split(",").map(|s| s.trim().to_string()).collect())
+                    Some(value.args)
                 };
-                (command, args)
+                (value.command, args)
             }
         };
 
@@ -62,6 +100,12 @@ impl From<ActionRecord> for Action {
             retry: value.retry,
             timeout: value.timeout,
             count: value.count,
+            cwd: value.cwd,
+            env: value.env,
+            stdin: value.stdin,
+            output_file: value.output_file,
+            trust: value.trust,
+            retry_policy: value.retry_policy,
         };
         action.typ = value.typ.into();
 
@@ -69,7 +113,47 @@ impl From<ActionRecord> for Action {
     }
 }
 
-#[derive(Deserialize, Debug)]
+/// 旧版组合动作 command 字段中的占位符
+pub const GROUP_PLACEHOLDER: &str = "group";
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupMode {
+    /// 依次执行，每个子动作结束后等待其 `wait` 毫秒
+    #[default]
+    Sequential,
+    /// 同时执行，忽略子动作的 `wait`
+    Parallel,
+}
+
+/// 组合动作的执行方式，以 JSON 保存在组合动作的 command 字段中
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct GroupOptions {
+    pub mode: GroupMode,
+    /// 并行时最多同时执行的子动作数量，None 表示不限制
+    pub max_parallel: Option<usize>,
+    /// 有子动作失败后不再启动剩余的子动作，组合动作视为失败
+    pub stop_on_failure: bool,
+}
+
+impl GroupOptions {
+    /// 空值与旧数据中的占位符都按默认的依次执行处理
+    pub fn parse(command: &str) -> Result<Self> {
+        let command = command.trim();
+        if command.is_empty() || command == GROUP_PLACEHOLDER {
+            return Ok(Self::default());
+        }
+        let options: GroupOptions =
+            serde_json::from_str(command).context("组合动作的执行方式不是有效的 JSON")?;
+        if options.max_parallel == Some(0) {
+            bail!("并行数量至少为 1");
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ActionRecord {
     pub id: String,
     #[serde(rename = "type")]
@@ -80,11 +164,76 @@ pub struct ActionRecord {
     pub retry: Option<usize>,
     pub desc: String,
     pub command: String,
-    pub args: String,
+    /// 兼容旧版导出文件中以逗号拼接的字符串
+    #[serde(deserialize_with = "deserialize_args")]
+    pub args: Vec<String>,
     pub count: Option<usize>,
+    pub cwd: Option<String>,
+    pub env: Option<BTreeMap<String, String>>,
+    pub stdin: Option<String>,
+    pub output_file: Option<String>,
+    pub trust: Option<TrustLevel>,
+    pub retry_policy: Option<RetryPolicy>,
+}
+
+/// 旧版 args 列以逗号拼接各参数，参数本身无法包含逗号
+pub fn split_legacy_args(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    text.split(',').map(|arg| arg.trim().to_string()).collect()
+}
+
+/// args 列以 JSON 数组保存，参数可以包含逗号与换行
+pub fn encode_args(args: &[String]) -> String {
+    serde_json::to_string(args).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 读取 args 列，不是 JSON 数组时按旧版的逗号格式解析
+pub fn decode_args(text: &str) -> Vec<String> {
+    serde_json::from_str(text).unwrap_or_else(|_| split_legacy_args(text))
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// env 列以 JSON 对象保存，未设置时为 NULL
+pub fn encode_env(env: Option<&BTreeMap<String, String>>) -> Option<String> {
+    env.and_then(|env| serde_json::to_string(env).ok())
+}
+
+fn deserialize_args<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Args {
+        List(Vec<String>),
+        Legacy(String),
+    }
+    Ok(match Args::deserialize(deserializer)? {
+        Args::List(args) => args,
+        Args::Legacy(text) => split_legacy_args(&text),
+    })
+}
+
+impl ActionRecord {
+    /// 组合动作与工作流引用的其他动作 id
+    pub fn member_ids(&self) -> Vec<String> {
+        match self.typ {
+            ActionType::Group => self
+                .args
+                .iter()
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect(),
+            ActionType::Workflow => Workflow::parse(&self.command)
+                .map(|workflow| workflow.action_ids())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[repr(u8)]
 pub enum ActionType {
     Directory = 0,
@@ -92,7 +241,11 @@ pub enum ActionType {
     Url = 2,
     Command = 3,
     Notice = 4,
+    /// 发送 HTTP 请求，定义见 [`HttpRequest`](super::HttpRequest)
+    Http = 5,
     Group = 11,
+    /// 按条件与成功、失败分支依次执行其他动作，定义见 [`Workflow`]
+    Workflow = 12,
 }
 impl From<ActionType> for u8 {
     fn from(action_type: ActionType) -> Self {
@@ -111,7 +264,9 @@ impl TryFrom<&str> for ActionType {
             "url" => Ok(ActionType::Url),
             "command" => Ok(ActionType::Command),
             "notice" => Ok(ActionType::Notice),
+            "http" => Ok(ActionType::Http),
             "group" => Ok(ActionType::Group),
+            "workflow" => Ok(ActionType::Workflow),
             _ => Err(anyhow::anyhow!("无效的 ActionType 值: {}", value)),
         }
     }
@@ -124,7 +279,9 @@ impl From<ActionType> for String {
             ActionType::Url => "url".to_string(),
             ActionType::Command => "command".to_string(),
             ActionType::Notice => "notice".to_string(),
+            ActionType::Http => "http".to_string(),
             ActionType::Group => "group".to_string(),
+            ActionType::Workflow => "workflow".to_string(),
         }
     }
 }
@@ -138,7 +295,9 @@ impl TryFrom<u8> for ActionType {
             2 => Ok(ActionType::Url),
             3 => Ok(ActionType::Command),   
             4 => Ok(ActionType::Notice),
+            5 => Ok(ActionType::Http),
             11 => Ok(ActionType::Group),
+            12 => Ok(ActionType::Workflow),
             _ => Err(anyhow::anyhow!("无效的 ActionType 值: {}", value)),
         }
     }
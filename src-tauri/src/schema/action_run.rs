@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// 动作执行记录中 stdout/stderr 的最大保存长度（字节）
+pub const MAX_RUN_OUTPUT_BYTES: usize = 8 * 1024;
+
+/// 动作由谁触发
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum RunTrigger {
+    Manual = 0,
+    TaskDue = 1,
+    StartupPeriodic = 2,
+    Tray = 3,
+    GroupParent = 4,
+    Api = 5,
+    Cli = 6,
+    Hotkey = 7,
+}
+
+impl From<RunTrigger> for u8 {
+    fn from(trigger: RunTrigger) -> Self {
+        trigger as u8
+    }
+}
+
+impl TryFrom<u8> for RunTrigger {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RunTrigger::Manual),
+            1 => Ok(RunTrigger::TaskDue),
+            2 => Ok(RunTrigger::StartupPeriodic),
+            3 => Ok(RunTrigger::Tray),
+            4 => Ok(RunTrigger::GroupParent),
+            5 => Ok(RunTrigger::Api),
+            6 => Ok(RunTrigger::Cli),
+            7 => Ok(RunTrigger::Hotkey),
+            _ => Err(anyhow::anyhow!("无效的 RunTrigger 值: {}", value)),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ActionRunRecord {
+    pub id: String,
+    pub action_id: Option<String>,
+    pub action_name: String,
+    pub trigger: RunTrigger,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub duration_ms: u64,
+    pub attempts: usize,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// 查询执行记录的过滤条件，所有字段均可省略
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ActionRunFilter {
+    pub action_id: Option<String>,
+    pub trigger: Option<RunTrigger>,
+    pub success: Option<bool>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// 按字节上限截断输出，保证不切断 UTF-8 字符
+pub fn truncate_output(output: &str) -> String {
+    if output.len() <= MAX_RUN_OUTPUT_BYTES {
+        return output.to_string();
+    }
+    let mut end = MAX_RUN_OUTPUT_BYTES;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n...(truncated {} bytes)", &output[..end], output.len() - end)
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::{ActionRecord, PeriodicTaskRecord, TaskRecord};
+
+/// 导出文件的格式版本，结构发生不兼容变化时递增
+pub const BUNDLE_FORMAT_VERSION: u32 = 2;
+
+/// 可在不同设备之间迁移的任务、动作与周期规则集合
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Bundle {
+    pub version: u32,
+    pub exported_at: i64,
+    #[serde(default)]
+    pub actions: Vec<ActionRecord>,
+    #[serde(default)]
+    pub tasks: Vec<TaskRecord>,
+    #[serde(default)]
+    pub periodic_tasks: Vec<PeriodicTaskRecord>,
+}
+
+/// 导出范围，不指定任何 id 时导出全部数据
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// 要导出的任务，子任务会一并导出
+    pub task_ids: Option<Vec<String>>,
+    /// 额外导出的动作，任务引用的动作总会导出
+    pub action_ids: Option<Vec<String>>,
+    pub exclude_completed: bool,
+}
+
+/// 导入时动作名称与已有动作重复的处理方式
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// 沿用已有动作，导入的任务指向已有动作
+    #[default]
+    Skip,
+    /// 以 `名称 (2)` 的形式另存一份
+    Rename,
+    /// 用导入内容覆盖已有动作
+    Overwrite,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ImportOptions {
+    pub conflict: ConflictStrategy,
+    /// 仅预览导入结果，不写入数据库
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportItemKind {
+    Action,
+    Task,
+    PeriodicTask,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    Created,
+    Renamed,
+    Overwritten,
+    Skipped,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ImportItem {
+    pub kind: ImportItemKind,
+    pub name: String,
+    /// 导出文件中的 id
+    pub source_id: String,
+    /// 导入后在本机数据库中的 id
+    pub target_id: String,
+    pub outcome: ImportOutcome,
+    pub renamed_to: Option<String>,
+    /// 该项被跳过或部分导入的原因
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub items: Vec<ImportItem>,
+    pub warnings: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn count(&self, kind: ImportItemKind, outcome: ImportOutcome) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.kind == kind && item.outcome == outcome)
+            .count()
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Http 类型动作的请求定义，以 JSON 保存在动作的 command 字段中
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+impl HttpRequest {
+    pub fn parse(command: &str) -> Result<Self> {
+        let request: HttpRequest =
+            serde_json::from_str(command).context("请求定义不是有效的 JSON")?;
+        request.validate()?;
+        Ok(request)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let url = self.url.trim();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            bail!("请求地址需要以 http:// 或 https:// 开头: {}", self.url);
+        }
+        if !METHODS.contains(&self.method.to_ascii_uppercase().as_str()) {
+            bail!("不支持的请求方法: {}", self.method);
+        }
+        Ok(())
+    }
+
+    /// 替换地址、请求头与请求体中的模板变量
+    pub fn render(&self, render: impl Fn(&str) -> String) -> Self {
+        Self {
+            url: render(&self.url),
+            method: self.method.to_ascii_uppercase(),
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), render(value)))
+                .collect(),
+            body: self.body.as_deref().map(&render),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_default_to_get_and_reject_invalid_definitions() {
+        let request = HttpRequest::parse(r#"{"url": "http://localhost/hook"}"#).unwrap();
+        assert_eq!(request.method, "GET");
+        assert!(request.headers.is_empty());
+        assert_eq!(request.body, None);
+
+        assert!(HttpRequest::parse(r#"{"url": "ftp://localhost"}"#).is_err());
+        assert!(HttpRequest::parse(r#"{"url": "http://localhost", "method": "FETCH"}"#).is_err());
+        assert!(HttpRequest::parse("http://localhost").is_err());
+    }
+}
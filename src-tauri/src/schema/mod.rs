@@ -1,8 +1,15 @@
 pub mod action;
+pub mod action_run;
+pub mod bundle;
 pub mod dto;
+pub mod http;
+pub mod search;
 pub mod state;
+pub mod tag;
 pub mod task;
 pub mod window;
+pub mod workflow;
 pub mod periodic;
+pub mod retry;
 
-pub use self::{action::*, state::*, task::*, window::*, periodic::*};
+pub use self::{action::*, action_run::*, bundle::*, http::*, search::*, state::*, tag::*, task::*, window::*, workflow::*, periodic::*, retry::*};
@@ -8,6 +8,7 @@ pub struct  PeriodicTask {
     pub id: Option<String>,
     pub name: String,
     pub interval: Period,
+    pub rule: Option<String>,
     pub task: TaskView,
     pub last_period: Option<u64>,
     pub next_period: Option<u64>,
@@ -29,6 +30,7 @@ impl TryFrom<(&PeriodicTaskRecord,&AppState)> for PeriodicTask {
             7 => Period::Weekly,
             30 => Period::Monthly,
             100 => Period::OnceStarted,
+            CUSTOM_PERIOD_INTERVAL => Period::Custom,
             _ => return Err(anyhow::anyhow!("Invalid interval")),
         };
 
@@ -36,6 +38,7 @@ impl TryFrom<(&PeriodicTaskRecord,&AppState)> for PeriodicTask {
             id: Some(record.id.clone()),
             name: record.name.clone(),
             interval,
+            rule: record.rule.clone(),
             task,
             last_period: record.last_period,
             next_period: record.next_period,
@@ -43,6 +46,8 @@ impl TryFrom<(&PeriodicTaskRecord,&AppState)> for PeriodicTask {
     }
 }
 
+/// 自定义周期的 interval 值，具体规则保存在 `rule` 字段中
+pub const CUSTOM_PERIOD_INTERVAL: u8 = 200;
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(into = "u8", try_from = "u8")]
@@ -53,6 +58,7 @@ pub enum Period {
     Weekly = 7,
     Monthly = 30,
     OnceStarted = 100,
+    Custom = CUSTOM_PERIOD_INTERVAL,
 }
 
 impl From<Period> for u8 {
@@ -71,6 +77,7 @@ impl TryFrom<u8> for Period {
             7 => Ok(Period::Weekly),
             30 => Ok(Period::Monthly),
             100 => Ok(Period::OnceStarted),
+            CUSTOM_PERIOD_INTERVAL => Ok(Period::Custom),
             _ => Err(format!("Invalid period value: {}", value)),
         }
     }
@@ -80,14 +87,18 @@ impl TryFrom<u8> for Period {
 pub struct PeriodicTaskData {
     pub name: String,
     pub interval: u8,
+    /// 自定义周期规则：cron 表达式或 `@every 2h`，仅在 interval 为 Custom 时使用
+    #[serde(default)]
+    pub rule: Option<String>,
     pub task: TaskData,
 }
 
-
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PeriodicTaskRecord {
     pub id: String,
     pub name: String,
     pub interval: u8,
+    pub rule: Option<String>,
     pub last_period: Option<u64>,
     pub next_period: Option<u64>,
 }
\ No newline at end of file
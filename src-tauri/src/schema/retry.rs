@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// 两次尝试之间等待时间的增长方式
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Backoff {
+    #[default]
+    Fixed,
+    /// 第 n 次重试等待 n 倍的基础时间
+    Linear,
+    /// 每次重试的等待时间翻倍
+    Exponential,
+}
+
+/// 哪些失败需要重试
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryOn {
+    /// 任何失败
+    #[default]
+    Failure,
+    /// 仅执行超时
+    Timeout,
+    /// 退出码在列表中，Http 动作为响应的状态码
+    ExitCodes(Vec<i32>),
+}
+
+/// 动作的重试策略，重试次数仍由动作的 `retry` 决定
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub backoff: Backoff,
+    /// 第一次重试前等待的毫秒数
+    pub delay_ms: u64,
+    /// 等待时间的上限，None 表示不限制
+    pub max_delay_ms: Option<u64>,
+    /// 在计算出的等待时间的一半到全部之间随机取值，避免多个动作同时重试
+    pub jitter: bool,
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            backoff: Backoff::Fixed,
+            delay_ms: 1000,
+            max_delay_ms: None,
+            jitter: false,
+            retry_on: RetryOn::Failure,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn validate(&self) -> Result<()> {
+        if matches!(&self.retry_on, RetryOn::ExitCodes(codes) if codes.is_empty()) {
+            bail!("按退出码重试时至少需要指定一个退出码");
+        }
+        Ok(())
+    }
+
+    /// 第 `retry` 次重试（从 1 开始）前等待的时间
+    pub fn delay(&self, retry: usize) -> Duration {
+        let retry = retry.max(1);
+        let delay = match self.backoff {
+            Backoff::Fixed => self.delay_ms,
+            Backoff::Linear => self.delay_ms.saturating_mul(retry as u64),
+            Backoff::Exponential => self
+                .delay_ms
+                .saturating_mul(2u64.saturating_pow((retry - 1).min(63) as u32)),
+        };
+        let delay = self.max_delay_ms.map_or(delay, |max| delay.min(max));
+        let delay = if self.jitter && delay > 1 {
+            rand::thread_rng().gen_range(delay / 2..=delay)
+        } else {
+            delay
+        };
+        Duration::from_millis(delay)
+    }
+
+    /// 本次失败是否需要重试，`exit_code` 为失败时的退出码
+    pub fn should_retry(&self, timed_out: bool, exit_code: Option<i32>) -> bool {
+        match &self.retry_on {
+            RetryOn::Failure => true,
+            RetryOn::Timeout => timed_out,
+            RetryOn::ExitCodes(codes) => exit_code.is_some_and(|code| codes.contains(&code)),
+        }
+    }
+}
+
+/// retry_policy 列以 JSON 保存，未设置时为 NULL
+pub fn encode_retry_policy(policy: Option<&RetryPolicy>) -> Option<String> {
+    policy.and_then(|policy| serde_json::to_string(policy).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delays_grow_and_respect_max_delay() {
+        let mut policy: RetryPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(policy, RetryPolicy::default());
+        assert_eq!(policy.delay(3), Duration::from_millis(1000));
+
+        policy.backoff = Backoff::Linear;
+        policy.delay_ms = 200;
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(600));
+
+        policy.backoff = Backoff::Exponential;
+        policy.max_delay_ms = Some(1000);
+        let delays = (1..=5).map(|retry| policy.delay(retry).as_millis()).collect::<Vec<_>>();
+        assert_eq!(delays, vec![200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.delay(200), Duration::from_millis(1000));
+
+        policy.jitter = true;
+        for _ in 0..20 {
+            let delay = policy.delay(3).as_millis();
+            assert!((400..=800).contains(&delay), "{}", delay);
+        }
+    }
+
+    #[test]
+    fn retry_conditions_match_timeouts_and_exit_codes() {
+        let policy: RetryPolicy = serde_json::from_str(r#"{"retry_on": "timeout"}"#).unwrap();
+        assert!(policy.should_retry(true, None));
+        assert!(!policy.should_retry(false, Some(1)));
+
+        let policy: RetryPolicy = serde_json::from_str(r#"{"retry_on": {"exit_codes": [75, 503]}}"#).unwrap();
+        assert!(policy.should_retry(false, Some(503)));
+        assert!(!policy.should_retry(false, Some(1)));
+        assert!(!policy.should_retry(true, None));
+
+        assert!(RetryPolicy::default().should_retry(false, None));
+        let empty = RetryPolicy {
+            retry_on: RetryOn::ExitCodes(Vec::new()),
+            ..Default::default()
+        };
+        assert!(empty.validate().is_err());
+    }
+}
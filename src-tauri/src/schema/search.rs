@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Task,
+    Action,
+    Periodic,
+}
+
+impl SearchKind {
+    /// 索引表 `kind` 列中的取值
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "task" => Some(SearchKind::Task),
+            "action" => Some(SearchKind::Action),
+            "periodic" => Some(SearchKind::Periodic),
+            _ => None,
+        }
+    }
+}
+
+/// 检索命中项，`highlight` 与 `snippet` 中命中的部分以 `<mark>` 与 `</mark>` 包裹
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    pub id: String,
+    /// 原始名称
+    pub title: String,
+    /// 高亮后的名称
+    pub highlight: String,
+    /// 动作描述的摘要，任务与周期规则没有描述
+    pub snippet: Option<String>,
+    /// 仅任务有完成状态
+    pub completed: Option<bool>,
+    /// 相关度，越大越相关
+    pub score: f64,
+}
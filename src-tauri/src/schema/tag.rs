@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    /// 前端展示用的颜色，如 `#3b82f6`
+    pub color: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TagData {
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// 按标签筛选任务，`tag_ids` 为空时不做筛选
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TagFilter {
+    pub tag_ids: Vec<String>,
+    /// true 时需要同时带有全部标签，否则带有任一标签即可
+    pub match_all: bool,
+}
+
+impl TagFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tag_ids.is_empty()
+    }
+}
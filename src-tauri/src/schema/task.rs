@@ -1,10 +1,10 @@
 use crate::{
-    schema::AppState, store::module::{ActionManager, TaskManager}, utils::{
+    schema::AppState, store::module::{ActionManager, DependencyManager, TagManager, TaskManager}, utils::{
         date::{str_to_datetime, to_datetime_str},
         help::get_uid,
     }
 };
-use super::Action;
+use super::{Action, Tag};
 use chrono::{Duration, Local};
 use serde::{Deserialize, Serialize};
 
@@ -48,6 +48,9 @@ impl TryFrom<(&TaskRecord, &AppState)> for TaskView {
         let created_at = to_datetime_str(record.created_at);
         let due_to = Some(to_datetime_str(record.due_to));
         let reminder = record.reminder.map(to_datetime_str);
+        let tags = db.get_task_tags(&record.id)?;
+        let blocked_by = db.get_task_blockers(&record.id)?;
+        let blocked = db.is_task_blocked(&record.id)?;
 
         Ok(Self {
             id: record.id.clone(),
@@ -61,6 +64,9 @@ impl TryFrom<(&TaskRecord, &AppState)> for TaskView {
             created_at,
             due_to,
             reminder,
+            tags,
+            blocked_by,
+            blocked,
         })
     }
 }
@@ -116,6 +122,12 @@ pub struct TaskData {
     pub created_at: Option<String>,
     pub due_to: Option<String>,
     pub reminder: Option<String>,
+    /// 任务的标签 id，None 表示不修改已有标签
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// 阻塞该任务的任务 id，None 表示不修改已有依赖
+    #[serde(default)]
+    pub blocked_by: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -131,4 +143,17 @@ pub struct TaskView {
     pub created_at: String,
     pub due_to: Option<String>,
     pub reminder: Option<String>,
+    pub tags: Vec<Tag>,
+    pub blocked_by: Vec<String>,
+    /// 仍有未完成的前置任务，自动任务不会被调度执行
+    pub blocked: bool,
+}
+
+/// `task-status-changed` 事件的内容
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TaskStatusChange {
+    pub ids: Vec<String>,
+    pub completed: bool,
+    /// 因本次完成而解除阻塞的任务 id
+    pub unblocked: Vec<String>,
 }
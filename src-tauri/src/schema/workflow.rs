@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// 工作流动作的定义，以 JSON 形式保存在动作的 `command` 字段中。
+/// 从第一个步骤开始执行，每一步按执行结果选择 `on_success` 或 `on_failure` 指定的去向
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Workflow {
+    pub steps: Vec<WorkflowStep>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct WorkflowStep {
+    /// 步骤 id，供跳转与 `{{steps.<id>.stdout}}` 引用
+    pub id: String,
+    pub action_id: String,
+    /// 执行条件，针对上一个执行的步骤，不满足时跳过本步骤并进入下一个步骤
+    #[serde(default)]
+    pub when: Option<StepCondition>,
+    /// 覆盖动作自身的参数；参数中可使用 `{{prev.stdout}}`、`{{prev.exit_code}}`、
+    /// `{{steps.<id>.stdout}}` 等变量
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    #[serde(default = "Transition::on_success")]
+    pub on_success: Transition,
+    #[serde(default = "Transition::on_failure")]
+    pub on_failure: Transition,
+}
+
+/// 步骤结束后的去向
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    /// 按顺序执行下一个步骤，已是最后一步时工作流成功结束
+    Next,
+    /// 工作流成功结束
+    End,
+    /// 工作流以失败结束
+    Fail,
+    /// 跳转到指定 id 的步骤
+    Goto(String),
+}
+
+impl Transition {
+    fn on_success() -> Self {
+        Transition::Next
+    }
+
+    fn on_failure() -> Self {
+        Transition::Fail
+    }
+}
+
+/// 所有设置的条件同时满足才算满足，`negate` 对整体结果取反
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct StepCondition {
+    pub exit_code: Option<i32>,
+    pub stdout_contains: Option<String>,
+    pub negate: bool,
+}
+
+impl StepCondition {
+    pub fn matches(&self, stdout: &str, exit_code: Option<i32>) -> bool {
+        let matched = self.exit_code.map_or(true, |code| exit_code == Some(code))
+            && self
+                .stdout_contains
+                .as_ref()
+                .map_or(true, |text| stdout.contains(text.as_str()));
+        matched != self.negate
+    }
+}
+
+impl Workflow {
+    pub fn parse(text: &str) -> Result<Self> {
+        let workflow: Workflow = serde_json::from_str(text).context("工作流定义不是有效的 JSON")?;
+        workflow.validate()?;
+        Ok(workflow)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.steps.is_empty() {
+            bail!("工作流至少需要一个步骤");
+        }
+        let mut ids = HashSet::new();
+        for step in &self.steps {
+            if step.id.trim().is_empty() {
+                bail!("工作流步骤 id 不能为空");
+            }
+            if !ids.insert(step.id.as_str()) {
+                bail!("工作流步骤 id 重复: {}", step.id);
+            }
+        }
+        for step in &self.steps {
+            for transition in [&step.on_success, &step.on_failure] {
+                if let Transition::Goto(target) = transition {
+                    if !ids.contains(target.as_str()) {
+                        bail!("工作流步骤 {} 跳转的步骤不存在: {}", step.id, target);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 各步骤引用的动作 id，按首次出现的顺序去重
+    pub fn action_ids(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.steps
+            .iter()
+            .filter(|step| seen.insert(step.action_id.as_str()))
+            .map(|step| step.action_id.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workflow_json_is_parsed_and_validated() {
+        let workflow = Workflow::parse(
+            r#"{"steps": [
+                {"id": "build", "action_id": "act1", "on_failure": {"goto": "notify"}},
+                {"id": "deploy", "action_id": "act2", "on_success": "end"},
+                {"id": "notify", "action_id": "act3", "args": ["{{prev.stderr}}"], "on_success": "fail"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(workflow.steps[0].on_success, Transition::Next);
+        assert_eq!(workflow.steps[0].on_failure, Transition::Goto("notify".to_string()));
+        assert_eq!(workflow.steps[1].on_failure, Transition::Fail);
+        assert_eq!(workflow.action_ids(), vec!["act1", "act2", "act3"]);
+
+        assert!(Workflow::parse(r#"{"steps": []}"#).is_err());
+        assert!(Workflow::parse(
+            r#"{"steps": [{"id": "a", "action_id": "x"}, {"id": "a", "action_id": "y"}]}"#
+        )
+        .is_err());
+        assert!(Workflow::parse(
+            r#"{"steps": [{"id": "a", "action_id": "x", "on_success": {"goto": "b"}}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn conditions_check_exit_code_and_stdout() {
+        let condition = StepCondition {
+            exit_code: Some(0),
+            stdout_contains: Some("changed".to_string()),
+            negate: false,
+        };
+        assert!(condition.matches("3 files changed", Some(0)));
+        assert!(!condition.matches("3 files changed", Some(1)));
+        assert!(!condition.matches("up to date", Some(0)));
+        let negated = StepCondition {
+            negate: true,
+            ..condition
+        };
+        assert!(negated.matches("up to date", Some(0)));
+        assert!(StepCondition::default().matches("", None));
+    }
+}
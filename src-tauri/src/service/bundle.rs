@@ -0,0 +1,78 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use tauri::{Emitter, Manager};
+
+use crate::{
+    core::timer::Timer,
+    get_app_handle, logging,
+    schema::{AppState, Bundle, ExportOptions, ImportOptions, ImportReport},
+    service::hub::Hub,
+    store::module::BundleManager,
+    utils::logging::Type,
+};
+
+/// 导出文件格式，按扩展名判断：.yaml/.yml 为 YAML，其余为 JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Json,
+    Yaml,
+}
+
+impl BundleFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                BundleFormat::Yaml
+            }
+            _ => BundleFormat::Json,
+        }
+    }
+
+    pub fn serialize(self, bundle: &Bundle) -> Result<String> {
+        Ok(match self {
+            BundleFormat::Json => serde_json::to_string_pretty(bundle)?,
+            BundleFormat::Yaml => serde_yaml::to_string(bundle)?,
+        })
+    }
+
+    pub fn deserialize(self, content: &str) -> Result<Bundle> {
+        Ok(match self {
+            BundleFormat::Json => serde_json::from_str(content)?,
+            BundleFormat::Yaml => serde_yaml::from_str(content)?,
+        })
+    }
+}
+
+pub fn export_to_file(path: &Path, options: &ExportOptions) -> Result<Bundle> {
+    let bundle = {
+        let app_handle = get_app_handle!();
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        db.export_bundle(options)?
+    };
+    let content = BundleFormat::from_path(path).serialize(&bundle)?;
+    fs::write(path, content).with_context(|| format!("写入导出文件失败: {}", path.display()))?;
+    logging!(info, Type::Service, true, "数据已导出到 {}", path.display());
+    Ok(bundle)
+}
+
+pub async fn import_from_file(path: &Path, options: &ImportOptions) -> Result<ImportReport> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("读取导入文件失败: {}", path.display()))?;
+    let bundle = BundleFormat::from_path(path)
+        .deserialize(&content)
+        .with_context(|| format!("导入文件格式错误: {}", path.display()))?;
+    let report = {
+        let app_handle = get_app_handle!();
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        db.import_bundle(&bundle, options)?
+    };
+    if !report.dry_run {
+        Hub::global().refresh().await;
+        Timer::global().refresh()?;
+        get_app_handle!().emit("task-changed", None::<()>)?;
+    }
+    Ok(report)
+}
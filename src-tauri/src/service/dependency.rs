@@ -0,0 +1,67 @@
+use std::{future::Future, pin::Pin};
+
+use anyhow::Result;
+use tauri::{async_runtime, Emitter, Manager};
+
+use crate::{
+    core::timer::Timer,
+    get_app_handle, logging,
+    schema::{AppState, TaskStatusChange, TaskView},
+    service::{execute, hub::Hub, schedule::schedule_window},
+    store::module::{DependencyManager, TaskManager},
+    utils::logging::Type,
+};
+
+/// 任务状态变化后处理依赖：通知前端并重新调度，
+/// 因前置任务未完成而错过执行时间的自动任务在解除阻塞后立即执行
+pub async fn on_tasks_status_changed(ids: Vec<String>, completed: bool) -> Result<()> {
+    let app_handle = get_app_handle!();
+    let state = app_handle.state::<AppState>();
+    let (unblocked, overdue) = {
+        let db = state.db.lock();
+        let unblocked = if completed {
+            db.get_unblocked_dependents(&ids)?
+        } else {
+            Vec::new()
+        };
+        let (window_start, _) = schedule_window();
+        let overdue: Vec<_> = db
+            .get_tasks(&unblocked)?
+            .into_iter()
+            .filter(|task| task.auto && task.due_to < window_start)
+            .collect();
+        (unblocked, overdue)
+    };
+    if !unblocked.is_empty() {
+        logging!(info, Type::Service, true, "任务已解除阻塞: {}", unblocked.join(","));
+    }
+    app_handle.emit(
+        "task-status-changed",
+        TaskStatusChange {
+            ids,
+            completed,
+            unblocked,
+        },
+    )?;
+
+    Hub::global().refresh().await;
+    Timer::global().refresh()?;
+
+    if !overdue.is_empty() {
+        let tasks = overdue
+            .iter()
+            .map(|task| TaskView::try_from((task, state.inner())))
+            .collect::<Result<Vec<_>>>()?;
+        async_runtime::spawn(execute_unblocked(tasks));
+    }
+    Ok(())
+}
+
+/// 执行完成后会再次进入 `on_tasks_status_changed`，需要装箱打断递归的 Future 类型
+fn execute_unblocked(tasks: Vec<TaskView>) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        if let Err(e) = execute::execute_task_views(tasks).await {
+            logging!(error, Type::Service, true, "执行解除阻塞的任务失败: {}", e);
+        }
+    })
+}
@@ -1,4 +1,4 @@
-use super::schedule::create_scheduled_tasks;
+use super::schedule::{create_scheduled_reminders, create_scheduled_tasks};
 use crate::{schema::TaskView, singleton};
 use std::{
     collections::HashMap,
@@ -7,6 +7,8 @@ use std::{
 
 pub struct Hub {
     pub schedule: Arc<RwLock<HashMap<i64, Vec<TaskView>>>>,
+    /// 以提醒时间为键的任务索引
+    pub reminders: Arc<RwLock<HashMap<i64, Vec<TaskView>>>>,
 }
 singleton!(Hub, INSTANCE);
 
@@ -14,6 +16,7 @@ impl Hub {
     pub fn new() -> Self {
         let hub = Hub {
             schedule: Arc::new(RwLock::new(create_scheduled_tasks())),
+            reminders: Arc::new(RwLock::new(create_scheduled_reminders())),
         };
         hub
     }
@@ -35,9 +38,28 @@ impl Hub {
         })
     }
 
+    pub fn latest_reminders(&self) -> Option<HashMap<i64, Vec<TaskView>>> {
+        let reminders = self.reminders.read().unwrap();
+        if reminders.is_empty() {
+            None
+        } else {
+            Some(reminders.clone())
+        }
+    }
+
+    pub fn get_reminder(&self, id: &str, ts: i64) -> Option<Vec<TaskView>> {
+        let reminders = self.reminders.read().unwrap();
+        reminders.get(&ts).map(|v| v.iter().filter(|t| t.id == id).cloned().collect())
+    }
+
     pub async fn refresh(&self) {
         let schedule = create_scheduled_tasks();
-        let mut schedule_map = self.schedule.write().unwrap();
-        *schedule_map = schedule;
+        let reminders = create_scheduled_reminders();
+        {
+            let mut schedule_map = self.schedule.write().unwrap();
+            *schedule_map = schedule;
+        }
+        let mut reminder_map = self.reminders.write().unwrap();
+        *reminder_map = reminders;
     }
 }
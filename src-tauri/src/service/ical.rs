@@ -0,0 +1,868 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use tauri::{Emitter, Manager};
+
+use crate::{
+    core::timer::Timer,
+    get_app_handle, logging,
+    schema::{
+        AppState, ImportItem, ImportItemKind, ImportOptions, ImportOutcome, ImportReport,
+        PeriodicTaskData, PeriodicTaskRecord, TaskData, TaskRecord,
+    },
+    service::{hub::Hub, periodic::calculate_periodic_task_occurrences},
+    store::module::{PeriodicTaskManager, TaskManager},
+    utils::{
+        date::to_datetime_str,
+        logging::Type,
+        recurrence::{period_from_rrule, Recurrence},
+    },
+};
+
+pub const ICS_PRODID: &str = "-//Ducker//Ducker Tasks//ZH";
+/// 无法转换为 RRULE 的周期规则，在日历中向后展开的天数
+const EXPANDED_OCCURRENCE_DAYS: i64 = 60;
+
+/// 按 RFC 5545 输出内容行：转义文本、75 字节折行、CRLF 结尾
+#[derive(Default)]
+struct IcsWriter {
+    out: String,
+}
+
+impl IcsWriter {
+    fn line(&mut self, content: &str) {
+        let mut width = 0;
+        for c in content.chars() {
+            if width + c.len_utf8() > 75 {
+                self.out.push_str("\r\n ");
+                width = 1;
+            }
+            self.out.push(c);
+            width += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+    }
+
+    fn text(&mut self, name: &str, value: &str) {
+        self.line(&format!("{}:{}", name, escape_text(value)));
+    }
+}
+
+pub fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn format_utc(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// 不带时区的浮动时间，RRULE 中的 BYHOUR 等按本地时间解释
+fn format_floating(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local)
+        .format("%Y%m%dT%H%M%S")
+        .to_string()
+}
+
+fn write_alarm(writer: &mut IcsWriter, name: &str, trigger: &str) {
+    writer.line("BEGIN:VALARM");
+    writer.line("ACTION:DISPLAY");
+    writer.text("DESCRIPTION", name);
+    writer.line(trigger);
+    writer.line("END:VALARM");
+}
+
+fn write_todo(writer: &mut IcsWriter, task: &TaskRecord, now: i64) {
+    writer.line("BEGIN:VTODO");
+    writer.line(&format!("UID:{}@ducker", task.id));
+    writer.line(&format!("DTSTAMP:{}", format_utc(now)));
+    writer.line(&format!("CREATED:{}", format_utc(task.created_at)));
+    writer.text("SUMMARY", &task.name);
+    writer.line(&format!("DUE:{}", format_utc(task.due_to)));
+    writer.line(if task.completed {
+        "STATUS:COMPLETED"
+    } else {
+        "STATUS:NEEDS-ACTION"
+    });
+    if let Some(parent_id) = &task.parent_id {
+        writer.line(&format!("RELATED-TO:{}@ducker", parent_id));
+    }
+    if let Some(reminder) = task.reminder {
+        write_alarm(
+            writer,
+            &task.name,
+            &format!("TRIGGER;VALUE=DATE-TIME:{}", format_utc(reminder)),
+        );
+    }
+    writer.line("END:VTODO");
+}
+
+fn write_periodic_events(
+    writer: &mut IcsWriter,
+    periodic: &PeriodicTaskRecord,
+    task: Option<&TaskRecord>,
+    now: i64,
+) {
+    let recurrence = match Recurrence::from_record(periodic) {
+        Ok(recurrence) => recurrence,
+        Err(e) => {
+            logging!(warn, Type::Service, "周期任务 {} 无法导出到日历: {}", periodic.id, e);
+            return;
+        }
+    };
+    // 提醒相对截止时间的提前量沿用到每一次重复
+    let alarm_offset = task.and_then(|task| task.reminder.map(|reminder| task.due_to - reminder));
+    let write_event = |writer: &mut IcsWriter, uid: String, dtstart: String, rrule: Option<String>| {
+        writer.line("BEGIN:VEVENT");
+        writer.line(&format!("UID:{}", uid));
+        writer.line(&format!("DTSTAMP:{}", format_utc(now)));
+        writer.line(&dtstart);
+        writer.text("SUMMARY", &periodic.name);
+        if let Some(rrule) = rrule {
+            writer.line(&format!("RRULE:{}", rrule));
+        }
+        if let Some(offset) = alarm_offset {
+            let sign = if offset >= 0 { "-" } else { "" };
+            write_alarm(
+                writer,
+                &periodic.name,
+                &format!("TRIGGER:{}PT{}M", sign, offset.abs() / 60),
+            );
+        }
+        writer.line("END:VEVENT");
+    };
+
+    match recurrence.to_rrule() {
+        Some(rrule) => {
+            let Some(start) = periodic.next_period.or(periodic.last_period) else {
+                return;
+            };
+            write_event(
+                writer,
+                format!("{}-series@ducker", periodic.id),
+                format!("DTSTART:{}", format_floating(start as i64)),
+                Some(rrule),
+            );
+        }
+        None => {
+            let end = now + EXPANDED_OCCURRENCE_DAYS * 24 * 3600;
+            for occurrence in calculate_periodic_task_occurrences(periodic, now, end) {
+                write_event(
+                    writer,
+                    format!("{}-{}@ducker", periodic.id, occurrence),
+                    format!("DTSTART:{}", format_utc(occurrence)),
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// 将任务输出为 VTODO，周期规则输出为带 RRULE 的 VEVENT
+pub fn render_calendar(tasks: &[TaskRecord], periodic_tasks: &[PeriodicTaskRecord], now: i64) -> String {
+    let mut writer = IcsWriter::default();
+    writer.line("BEGIN:VCALENDAR");
+    writer.line("VERSION:2.0");
+    writer.line(&format!("PRODID:{}", ICS_PRODID));
+    writer.line("CALSCALE:GREGORIAN");
+    writer.line("X-WR-CALNAME:Ducker");
+    for task in tasks {
+        write_todo(&mut writer, task, now);
+    }
+    for periodic in periodic_tasks {
+        // 启动时任务没有具体时间，不出现在日历中
+        if periodic.interval == 0 || periodic.interval == 100 {
+            continue;
+        }
+        let task = tasks.iter().find(|task| task.id == periodic.id);
+        write_periodic_events(&mut writer, periodic, task, now);
+    }
+    writer.line("END:VCALENDAR");
+    writer.out
+}
+
+pub fn build_calendar() -> Result<String> {
+    let (tasks, periodic_tasks) = {
+        let app_handle = get_app_handle!();
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        (db.get_all_tasks()?, db.get_enabled_periodic_tasks()?)
+    };
+    Ok(render_calendar(&tasks, &periodic_tasks, Local::now().timestamp()))
+}
+
+pub fn export_to_file(path: &Path) -> Result<()> {
+    let calendar = build_calendar()?;
+    fs::write(path, calendar).with_context(|| format!("写入日历文件失败: {}", path.display()))?;
+    logging!(info, Type::Service, true, "日历已导出到 {}", path.display());
+    Ok(())
+}
+
+/// 日历文件中的一行内容：属性名、参数与值
+#[derive(Debug, Clone)]
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Component {
+    kind: String,
+    properties: Vec<ContentLine>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&ContentLine> {
+        self.properties
+            .iter()
+            .find(|line| line.name.eq_ignore_ascii_case(name))
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(|line| unescape_text(&line.value))
+            .filter(|value| !value.trim().is_empty())
+    }
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn parse_content_line(line: &str) -> Option<ContentLine> {
+    // 参数值可能带引号，引号内的 `:` 与 `;` 不作为分隔符
+    let mut in_quotes = false;
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                segments.push(&line[start..index]);
+                start = index + 1;
+            }
+            ':' if !in_quotes => {
+                segments.push(&line[start..index]);
+                let mut segments = segments.into_iter();
+                let name = segments.next()?.trim().to_ascii_uppercase();
+                let params = segments
+                    .filter_map(|param| param.split_once('='))
+                    .map(|(key, value)| (key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+                    .collect();
+                return Some(ContentLine {
+                    name,
+                    params,
+                    value: line[index + 1..].to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 展开折行并解析出 VCALENDAR 中的全部组件
+fn parse_components(content: &str) -> Result<Vec<Component>> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+
+    let mut stack: Vec<Component> = Vec::new();
+    let mut calendars = Vec::new();
+    for line in &lines {
+        let Some(line) = parse_content_line(line) else {
+            continue;
+        };
+        match line.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                kind: line.value.trim().to_ascii_uppercase(),
+                ..Default::default()
+            }),
+            "END" => {
+                let component = stack.pop().context("日历文件格式错误：多余的 END")?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(component),
+                    None => calendars.push(component),
+                }
+            }
+            _ => {
+                if let Some(component) = stack.last_mut() {
+                    component.properties.push(line);
+                }
+            }
+        }
+    }
+    if !stack.is_empty() {
+        anyhow::bail!("日历文件格式错误：组件未结束");
+    }
+    let components: Vec<Component> = calendars
+        .into_iter()
+        .filter(|calendar| calendar.kind == "VCALENDAR")
+        .flat_map(|calendar| calendar.children)
+        .collect();
+    if components.is_empty() {
+        anyhow::bail!("日历文件中没有可导入的内容");
+    }
+    Ok(components)
+}
+
+/// 解析 DATE 与 DATE-TIME，带 `Z` 的为 UTC，其余按本地时间处理
+fn parse_ics_datetime(line: &ContentLine) -> Result<i64> {
+    let value = line.value.trim();
+    let datetime = if let Some(utc) = value.strip_suffix('Z') {
+        NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map(|naive| Utc.from_utc_datetime(&naive).timestamp())
+    } else if value.len() == 8 {
+        NaiveDate::parse_from_str(value, "%Y%m%d").map(|date| {
+            local_timestamp(date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        })
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map(local_timestamp)
+    };
+    datetime.with_context(|| format!("{} 时间格式无效: {}", line.name, value))
+}
+
+fn local_timestamp(naive: NaiveDateTime) -> i64 {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive).with_timezone(&Local))
+        .timestamp()
+}
+
+/// 解析 `-P1DT2H30M` 形式的时长，返回秒数
+fn parse_ics_duration(value: &str) -> Result<i64> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim_start_matches('+')),
+    };
+    let rest = rest
+        .strip_prefix('P')
+        .with_context(|| format!("时长格式无效: {}", value))?;
+    let mut total: i64 = 0;
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount: i64 = number
+                    .parse()
+                    .with_context(|| format!("时长格式无效: {}", value))?;
+                number.clear();
+                let unit = match c {
+                    'W' => 7 * 24 * 3600,
+                    'D' => 24 * 3600,
+                    'H' => 3600,
+                    'M' => 60,
+                    _ => 1,
+                };
+                total = amount
+                    .checked_mul(unit)
+                    .and_then(|seconds| total.checked_add(seconds))
+                    .with_context(|| format!("时长超出范围: {}", value))?;
+            }
+            _ => anyhow::bail!("时长格式无效: {}", value),
+        }
+    }
+    Ok(sign * total)
+}
+
+/// 从日历组件转换得到的待导入项
+#[derive(Debug)]
+pub enum IcsItem {
+    Task(TaskData),
+    Periodic(PeriodicTaskData),
+}
+
+#[derive(Debug)]
+pub struct IcsEntry {
+    pub uid: Option<String>,
+    /// RELATED-TO 指向的父任务 UID
+    pub parent_uid: Option<String>,
+    pub name: String,
+    /// None 表示该组件无法导入
+    pub item: Option<IcsItem>,
+    pub notes: Vec<String>,
+}
+
+fn convert_component(component: &Component, now: i64) -> IcsEntry {
+    let mut notes = Vec::new();
+    let name = component.text("SUMMARY").unwrap_or_else(|| {
+        notes.push("缺少标题，已使用默认名称".to_string());
+        "未命名任务".to_string()
+    });
+    let mut entry = IcsEntry {
+        uid: component.text("UID"),
+        parent_uid: component.text("RELATED-TO"),
+        name: name.clone(),
+        item: None,
+        notes: Vec::new(),
+    };
+    if !matches!(component.kind.as_str(), "VTODO" | "VEVENT") {
+        notes.push(format!("不支持导入 {} 组件", component.kind));
+        entry.notes = notes;
+        return entry;
+    }
+
+    let mut time = |property: &str| {
+        let line = component.property(property)?;
+        if let Some(tzid) = line.param("TZID") {
+            notes.push(format!("{} 的时区 {} 按本地时间处理", property, tzid));
+        }
+        match parse_ics_datetime(line) {
+            Ok(timestamp) => Some(timestamp),
+            Err(e) => {
+                notes.push(e.to_string());
+                None
+            }
+        }
+    };
+    let start = time("DTSTART");
+    let due = time("DUE");
+    let dtend = time("DTEND");
+    let created_at = time("CREATED");
+    let end = dtend.or(due).or_else(|| {
+        let duration = component.property("DURATION")?;
+        let start = start?;
+        let end = parse_ics_duration(&duration.value).and_then(|offset| {
+            start
+                .checked_add(offset)
+                .with_context(|| format!("时长超出范围: {}", duration.value))
+        });
+        match end {
+            Ok(end) => Some(end),
+            Err(e) => {
+                notes.push(e.to_string());
+                None
+            }
+        }
+    });
+    let mut due_to = if component.kind == "VTODO" {
+        due.or(start)
+    } else {
+        start.or(due)
+    };
+    if due_to.is_none() {
+        notes.push("没有 DUE/DTSTART，使用默认截止时间".to_string());
+    }
+
+    let alarms: Vec<&Component> = component
+        .children
+        .iter()
+        .filter(|child| child.kind == "VALARM")
+        .collect();
+    if alarms.len() > 1 {
+        notes.push(format!("共有 {} 个提醒，仅导入第一个", alarms.len()));
+    }
+    let mut reminder = alarms.first().and_then(|alarm| {
+        let trigger = alarm.property("TRIGGER")?;
+        let result = if trigger.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE-TIME")) {
+            parse_ics_datetime(trigger)
+        } else {
+            let related_end = trigger
+                .param("RELATED")
+                .is_some_and(|related| related.eq_ignore_ascii_case("END"));
+            let base = if related_end { end.or(due_to) } else { start.or(due_to) };
+            parse_ics_duration(&trigger.value).and_then(|offset| {
+                base.context("提醒缺少参照时间")?
+                    .checked_add(offset)
+                    .with_context(|| format!("时长超出范围: {}", trigger.value))
+            })
+        };
+        match result {
+            Ok(timestamp) => Some(timestamp),
+            Err(e) => {
+                notes.push(format!("提醒无法导入: {}", e));
+                None
+            }
+        }
+    });
+
+    let completed = component.property("COMPLETED").is_some()
+        || component
+            .text("STATUS")
+            .is_some_and(|status| status.eq_ignore_ascii_case("COMPLETED"));
+
+    let mut period = None;
+    if let Some(rrule) = component.property("RRULE") {
+        match due_to.map(|due_to| period_from_rrule(&rrule.value, due_to)) {
+            Some(Ok((interval, rule))) => {
+                if component.property("EXDATE").is_some() || component.property("RDATE").is_some() {
+                    notes.push("已忽略 EXDATE/RDATE".to_string());
+                }
+                let recurrence = Recurrence::new(interval, rule.as_deref());
+                // 起始时间已过时从下一次重复开始，提醒保持相同的提前量
+                if let (Ok(recurrence), Some(anchor)) = (recurrence, due_to) {
+                    if anchor <= now {
+                        if let Some(next) = recurrence.first_at_or_after(anchor, now + 1) {
+                            reminder = reminder.map(|reminder| reminder + next - anchor);
+                            due_to = Some(next);
+                        }
+                    }
+                }
+                period = Some((interval, rule));
+            }
+            Some(Err(e)) => notes.push(format!(
+                "重复规则 {} 无法表示（{}），已作为单次任务导入",
+                rrule.value, e
+            )),
+            None => notes.push("重复规则缺少开始时间，已作为单次任务导入".to_string()),
+        }
+    }
+
+    let task = TaskData {
+        id: None,
+        name: name.clone(),
+        value: None,
+        completed,
+        auto: false,
+        parent_id: None,
+        periodic: None,
+        actions: Vec::new(),
+        created_at: created_at.map(to_datetime_str),
+        due_to: due_to.map(to_datetime_str),
+        reminder: reminder.map(to_datetime_str),
+        tags: None,
+        blocked_by: None,
+    };
+    entry.item = Some(match period {
+        Some((interval, rule)) => IcsItem::Periodic(PeriodicTaskData {
+            name,
+            interval,
+            rule,
+            task,
+        }),
+        None => IcsItem::Task(task),
+    });
+    entry.notes = notes;
+    entry
+}
+
+/// 解析日历内容，VTIMEZONE 等辅助组件不计入结果
+pub fn parse_calendar(content: &str, now: i64) -> Result<Vec<IcsEntry>> {
+    let entries = parse_components(content)?
+        .iter()
+        .filter(|component| component.kind != "VTIMEZONE")
+        .map(|component| convert_component(component, now))
+        .collect();
+    Ok(entries)
+}
+
+pub async fn import_from_file(path: &Path, options: &ImportOptions) -> Result<ImportReport> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("读取日历文件失败: {}", path.display()))?;
+    let mut entries = parse_calendar(&content, Local::now().timestamp())
+        .with_context(|| format!("日历文件格式错误: {}", path.display()))?;
+    // 先导入父任务，子任务才能通过 RELATED-TO 找到新的父任务 id
+    entries.sort_by_key(|entry| entry.parent_uid.is_some());
+
+    let mut report = ImportReport {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+    {
+        let app_handle = get_app_handle!();
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        let mut uid_map: HashMap<String, String> = HashMap::new();
+        for mut entry in entries {
+            let (kind, result) = match entry.item.take() {
+                None => (ImportItemKind::Task, None),
+                Some(IcsItem::Task(mut task)) => {
+                    task.parent_id = entry
+                        .parent_uid
+                        .as_ref()
+                        .and_then(|uid| uid_map.get(uid).cloned());
+                    if entry.parent_uid.is_some() && task.parent_id.is_none() {
+                        entry.notes.push("父任务不在日历文件中，已作为顶层任务导入".to_string());
+                    }
+                    let result = if options.dry_run {
+                        Ok(String::new())
+                    } else {
+                        db.create_task(&task).and_then(|record| {
+                            if task.completed {
+                                db.update_task_status(&record.id, true)?;
+                            }
+                            Ok(record.id)
+                        })
+                    };
+                    (ImportItemKind::Task, Some(result))
+                }
+                Some(IcsItem::Periodic(periodic)) => {
+                    let result = if options.dry_run {
+                        Recurrence::new(periodic.interval, periodic.rule.as_deref()).map(|_| String::new())
+                    } else {
+                        db.create_periodic_task(&periodic).map(|record| record.id)
+                    };
+                    (ImportItemKind::PeriodicTask, Some(result))
+                }
+            };
+            let (target_id, outcome) = match result {
+                Some(Ok(id)) => {
+                    if let Some(uid) = &entry.uid {
+                        uid_map.insert(uid.clone(), id.clone());
+                    }
+                    (id, ImportOutcome::Created)
+                }
+                Some(Err(e)) => {
+                    entry.notes.push(format!("写入失败: {}", e));
+                    (String::new(), ImportOutcome::Skipped)
+                }
+                None => (String::new(), ImportOutcome::Skipped),
+            };
+            report.items.push(ImportItem {
+                kind,
+                name: entry.name,
+                source_id: entry.uid.unwrap_or_default(),
+                target_id,
+                outcome,
+                renamed_to: None,
+                note: (!entry.notes.is_empty()).then(|| entry.notes.join("；")),
+            });
+        }
+    }
+
+    logging!(
+        info,
+        Type::Service,
+        true,
+        "导入日历{}: {} 项, {} 项跳过",
+        if options.dry_run { "（预览）" } else { "" },
+        report.items.len(),
+        report
+            .items
+            .iter()
+            .filter(|item| item.outcome == ImportOutcome::Skipped)
+            .count()
+    );
+    if !report.dry_run {
+        Hub::global().refresh().await;
+        Timer::global().refresh()?;
+        get_app_handle!().emit("task-changed", None::<()>)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema::CUSTOM_PERIOD_INTERVAL, utils::date::str_to_datetime};
+
+    fn task(id: &str, name: &str, due_to: i64) -> TaskRecord {
+        TaskRecord {
+            id: id.to_string(),
+            value: 0.0,
+            completed: false,
+            auto: false,
+            parent_id: None,
+            periodic: None,
+            name: name.to_string(),
+            actions: vec![],
+            created_at: due_to - 3600,
+            due_to,
+            reminder: None,
+        }
+    }
+
+    #[test]
+    fn calendar_contains_todos_alarms_and_rrules() {
+        let due = 1_750_000_000;
+        let mut weekly = task("task-weekly", "周报, 提交", due);
+        weekly.reminder = Some(due - 900);
+        weekly.periodic = Some("task-weekly".to_string());
+        let mut child = task("task-child", "子任务", due);
+        child.parent_id = Some("task-weekly".to_string());
+        child.completed = true;
+        let periodic = PeriodicTaskRecord {
+            id: "task-weekly".to_string(),
+            name: "周报".to_string(),
+            interval: 7,
+            rule: None,
+            last_period: Some(due as u64),
+            next_period: Some((due + 7 * 24 * 3600) as u64),
+        };
+
+        let calendar = render_calendar(&[weekly, child], &[periodic], due);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("SUMMARY:周报\\, 提交\r\n"));
+        assert!(calendar.contains(&format!("DUE:{}\r\n", format_utc(due))));
+        assert!(calendar.contains(&format!("TRIGGER;VALUE=DATE-TIME:{}", format_utc(due - 900))));
+        assert!(calendar.contains("STATUS:COMPLETED"));
+        assert!(calendar.contains("RELATED-TO:task-weekly@ducker"));
+        assert!(calendar.contains("RRULE:FREQ=WEEKLY\r\n"));
+        assert!(calendar.contains("TRIGGER:-PT15M"));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let mut writer = IcsWriter::default();
+        writer.text("SUMMARY", &"任".repeat(40));
+        let lines: Vec<&str> = writer.out.split("\r\n").filter(|l| !l.is_empty()).collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+    }
+
+    #[test]
+    fn calendar_components_convert_to_tasks() {
+        let now = Local.with_ymd_and_hms(2025, 6, 1, 8, 0, 0).unwrap().timestamp();
+        let content = [
+            "BEGIN:VCALENDAR",
+            "VERSION:2.0",
+            "BEGIN:VTODO",
+            "UID:parent@example",
+            "SUMMARY:写周报\\, 发给团队",
+            "DUE:20250603T100000Z",
+            "STATUS:COMPLETED",
+            "BEGIN:VALARM",
+            "TRIGGER;RELATED=END:-PT30M",
+            "END:VALARM",
+            "END:VTODO",
+            "BEGIN:VTODO",
+            "UID:child@example",
+            "RELATED-TO:parent@example",
+            "SUMMARY:整理",
+            " 数据",
+            "DTSTART:20250602",
+            "END:VTODO",
+            "BEGIN:VEVENT",
+            "UID:standup@example",
+            "SUMMARY:站会",
+            "DTSTART;TZID=Asia/Shanghai:20250602T093000",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:limited@example",
+            "SUMMARY:限次会议",
+            "DTSTART:20250602T090000",
+            "RRULE:FREQ=DAILY;COUNT=3",
+            "END:VEVENT",
+            "BEGIN:VJOURNAL",
+            "SUMMARY:日记",
+            "END:VJOURNAL",
+            "END:VCALENDAR",
+        ]
+        .join("\r\n");
+
+        let entries = parse_calendar(&content, now).unwrap();
+        assert_eq!(entries.len(), 5);
+
+        let Some(IcsItem::Task(todo)) = &entries[0].item else {
+            panic!("VTODO 应导入为任务");
+        };
+        assert_eq!(todo.name, "写周报, 发给团队");
+        assert!(todo.completed);
+        let due = str_to_datetime(todo.due_to.as_deref().unwrap()).timestamp();
+        assert_eq!(due, Utc.with_ymd_and_hms(2025, 6, 3, 10, 0, 0).unwrap().timestamp());
+        let reminder = str_to_datetime(todo.reminder.as_deref().unwrap()).timestamp();
+        assert_eq!(reminder, due - 1800);
+
+        assert_eq!(entries[1].name, "整理数据");
+        assert_eq!(entries[1].parent_uid.as_deref(), Some("parent@example"));
+
+        let Some(IcsItem::Periodic(standup)) = &entries[2].item else {
+            panic!("可表示的 RRULE 应导入为周期任务");
+        };
+        assert_eq!(standup.interval, CUSTOM_PERIOD_INTERVAL);
+        assert_eq!(standup.rule.as_deref(), Some("30 9 * * 1,3,5"));
+        assert!(entries[2].notes[0].contains("Asia/Shanghai"));
+
+        assert!(matches!(entries[3].item, Some(IcsItem::Task(_))));
+        assert!(entries[3].notes[0].contains("COUNT"));
+
+        assert!(entries[4].item.is_none());
+        assert!(entries[4].notes[0].contains("VJOURNAL"));
+    }
+
+    #[test]
+    fn oversized_durations_are_reported_per_item() {
+        let now = Local.with_ymd_and_hms(2025, 6, 1, 8, 0, 0).unwrap().timestamp();
+        let content = [
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT",
+            "UID:huge@example",
+            "SUMMARY:超长会议",
+            "DTSTART:20250602T090000",
+            "DURATION:P99999999999999999W",
+            "BEGIN:VALARM",
+            "TRIGGER;RELATED=END:-P99999999999999999W",
+            "END:VALARM",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]
+        .join("\r\n");
+
+        let entries = parse_calendar(&content, now).unwrap();
+        let Some(IcsItem::Task(task)) = &entries[0].item else {
+            panic!("时长无效时仍应导入任务");
+        };
+        assert!(task.reminder.is_none());
+        let notes = entries[0].notes.join("\n");
+        assert!(notes.contains("时长超出范围: P99999999999999999W"), "{}", notes);
+        assert!(notes.contains("提醒无法导入"), "{}", notes);
+        assert!(parse_ics_duration("PT9223372036854775807S").is_ok());
+        assert!(parse_ics_duration("P1DT9223372036854775807S").is_err());
+    }
+
+    #[test]
+    fn rendered_calendar_parses_back() {
+        let due = 1_750_000_000;
+        let mut record = task("task-a", "多行\n备注; 含分号", due);
+        record.reminder = Some(due - 600);
+        let calendar = render_calendar(&[record], &[], due - 3600);
+        let entries = parse_calendar(&calendar, due - 3600).unwrap();
+        let Some(IcsItem::Task(task)) = &entries[0].item else {
+            panic!("导出的 VTODO 应能重新导入");
+        };
+        assert_eq!(task.name, "多行\n备注; 含分号");
+        assert_eq!(str_to_datetime(task.due_to.as_deref().unwrap()).timestamp(), due);
+        assert_eq!(
+            str_to_datetime(task.reminder.as_deref().unwrap()).timestamp(),
+            due - 600
+        );
+        assert!(parse_calendar("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\n", due).is_err());
+    }
+}
@@ -1,4 +1,8 @@
+pub mod bundle;
+pub mod dependency;
 pub mod execute;
 pub mod hub;
 pub mod schedule;
-pub mod periodic;
\ No newline at end of file
+pub mod periodic;
+pub mod reminder;
+pub mod ical;
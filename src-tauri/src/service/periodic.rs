@@ -5,12 +5,15 @@ use tauri::Manager;
 
 use crate::{
     core::handle::Handle, logging, schema::{
-        AppState, PeriodicTaskRecord, TaskRecord, TaskView
+        AppState, PeriodicTaskRecord, TaskRecord, TaskView, CUSTOM_PERIOD_INTERVAL
     }, store::module::{
         PeriodicTaskManager, TaskManager
-    }, utils::{date::{self, calculate_next_period, calculate_next_period_from_now}, logging::Type}
+    }, utils::{date::{self, calculate_next_period, calculate_next_period_from_now}, logging::Type, recurrence::Recurrence}
 };
 
+/// 自定义规则在单个时间范围内最多展开的次数，避免 `@every 1m` 之类的规则生成过多条目
+const MAX_CUSTOM_OCCURRENCES: usize = 1000;
+
 /// 计算重复任务在指定时间范围内的所有执行时间，主要是计算周循环任务和月度任务
 /// 
 /// # 参数
@@ -20,7 +23,7 @@ use crate::{
 /// 
 /// # 返回
 /// 返回在时间范围内的所有执行时间戳列表
-fn calculate_periodic_task_occurrences(
+pub fn calculate_periodic_task_occurrences(
     periodic_task: &PeriodicTaskRecord,
     start_timestamp: i64,
     end_timestamp: i64,
@@ -30,6 +33,9 @@ fn calculate_periodic_task_occurrences(
     // 跳过特殊类型的任务
     match periodic_task.interval {
         0 | 100 => return occurrences, // OnStart 和 OnceStarted - 不在时间范围计算中
+        CUSTOM_PERIOD_INTERVAL => {
+            return calculate_custom_occurrences(periodic_task, start_timestamp, end_timestamp)
+        }
         _ => {}
     };
     
@@ -96,6 +102,34 @@ fn calculate_periodic_task_occurrences(
     occurrences
 }
 
+/// 按 cron 表达式或自定义间隔展开执行时间，从 next_period 开始推算
+fn calculate_custom_occurrences(
+    periodic_task: &PeriodicTaskRecord,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Vec<i64> {
+    let mut occurrences = Vec::new();
+    let recurrence = match Recurrence::from_record(periodic_task) {
+        Ok(recurrence) => recurrence,
+        Err(e) => {
+            logging!(warn, Type::Service, "周期任务 {} 的重复规则无效: {}", periodic_task.id, e);
+            return occurrences;
+        }
+    };
+    let anchor = periodic_task
+        .next_period
+        .map(|next_period| next_period as i64)
+        .unwrap_or(start_timestamp);
+    let Some(mut current) = recurrence.first_at_or_after(anchor, start_timestamp) else {
+        return occurrences;
+    };
+    while current <= end_timestamp && occurrences.len() < MAX_CUSTOM_OCCURRENCES {
+        occurrences.push(current);
+        current = recurrence.next_after(current);
+    }
+    occurrences
+}
+
 /// 获取指定时间范围内的重复任务及其执行时间
 /// 
 /// # 参数
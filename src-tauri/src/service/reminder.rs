@@ -0,0 +1,65 @@
+use anyhow::Result;
+use chrono::Local;
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{
+    core::timer::Timer,
+    get_app_handle, logging,
+    schema::{AppState, WindowType},
+    service::hub::Hub,
+    store::module::TaskManager,
+    utils::logging::Type,
+};
+
+/// 提醒事件名，主窗口收到后展示带「稍后提醒」「完成」按钮的提醒
+pub const REMINDER_ACTION_TYPE: &str = "task-reminder";
+/// 默认的稍后提醒间隔（分钟）
+pub const DEFAULT_SNOOZE_MINUTES: i64 = 10;
+
+/// 触发指定任务在 `ts` 时刻的提醒：弹出系统通知，并通知主窗口展示操作按钮
+pub async fn notify_task_reminders(id: &str, ts: i64) -> Result<String, String> {
+    let tasks = Hub::global().get_reminder(id, ts).unwrap_or_default();
+    if tasks.is_empty() {
+        return Ok("".to_string());
+    }
+    let app_handle = get_app_handle!();
+    for task in tasks {
+        let body = match &task.due_to {
+            Some(due_to) => format!("截止时间: {}", due_to),
+            None => "任务提醒".to_string(),
+        };
+        // 桌面端的系统通知无法携带按钮，按钮由常驻置顶的主窗口收到 task-reminder 事件后展示
+        if let Err(e) = app_handle
+            .notification()
+            .builder()
+            .title(&task.name)
+            .body(body)
+            .action_type_id(REMINDER_ACTION_TYPE)
+            .extra("task_id", &task.id)
+            .show()
+        {
+            logging!(error, Type::Service, true, "发送任务 {} 提醒通知失败: {}", task.id, e);
+        }
+        logging!(info, Type::Service, true, "任务 {} 提醒已触发", task.id);
+        app_handle
+            .emit_to(WindowType::Main.label(), REMINDER_ACTION_TYPE, &task)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok("".to_string())
+}
+
+/// 将任务的提醒推迟 `minutes` 分钟，返回新的提醒时间戳
+pub async fn snooze_task_reminder(id: &str, minutes: i64) -> Result<i64> {
+    let reminder = Local::now().timestamp() + minutes * 60;
+    {
+        let app_handle = get_app_handle!();
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock();
+        db.update_task_reminder(id, Some(reminder))?;
+    }
+    // 立即重建索引并重新布置定时器，避免等待下一次自动刷新
+    Hub::global().refresh().await;
+    Timer::global().refresh()?;
+    Ok(reminder)
+}
@@ -7,7 +7,7 @@ use crate::{
         state::AppState,
         task::{TaskRecord, TaskView},
     },
-    store::module::TaskManager,
+    store::module::{DependencyManager, TaskManager},
     utils::logging::Type,
 };
 use chrono::{Datelike, Local, TimeZone};
@@ -32,7 +32,27 @@ fn get_uncompleted_tasks_until_end_of_day(
     })
 }
 
-pub fn create_scheduled_tasks() -> HashMap<i64, Vec<TaskView>> {
+fn get_uncompleted_reminders_until_end_of_day(
+    start_date: i64,
+    end_date: i64,
+) -> Vec<TaskRecord> {
+    let app_handle = Handle::global().app_handle().unwrap();
+    let state = app_handle.state::<AppState>();
+    let db_guard = state.db.lock();
+    let res = db_guard.get_uncompleted_tasks_by_reminder_range(start_date, end_date);
+    res.unwrap_or_else(|e| {
+        logging!(
+            warn,
+            Type::Database,
+            "get_uncompleted_tasks_by_reminder_range error: {}",
+            e
+        );
+        Vec::new()
+    })
+}
+
+/// 调度窗口：从半分钟前到今天结束
+pub fn schedule_window() -> (i64, i64) {
     let now = Local::now();
     // 获取半分钟前开始的任务，防止某些任务未执行而因刷新被删除
     let start_date = now.timestamp() - 30;
@@ -40,6 +60,11 @@ pub fn create_scheduled_tasks() -> HashMap<i64, Vec<TaskView>> {
         .with_ymd_and_hms(now.year(), now.month(), now.day(), 23, 59, 59)
         .unwrap()
         .timestamp();
+    (start_date, end_of_day)
+}
+
+pub fn create_scheduled_tasks() -> HashMap<i64, Vec<TaskView>> {
+    let (start_date, end_of_day) = schedule_window();
     // 考虑一下这一秒的刷新
     if start_date == end_of_day {
         return HashMap::new();
@@ -49,11 +74,20 @@ pub fn create_scheduled_tasks() -> HashMap<i64, Vec<TaskView>> {
     let mut t2i_map = HashMap::new();
     let app_handle = Handle::global().app_handle().unwrap();
     let app_state = app_handle.state::<AppState>();
+    let blocked = app_state.db.lock().get_blocked_task_ids().unwrap_or_else(|e| {
+        logging!(warn, Type::Database, "获取被阻塞的任务失败: {}", e);
+        Default::default()
+    });
     for task in task_records.iter() {
         // 既然这里没办法判断任务是否是启动时任务，那就只能让启动时任务都非自动任务
         if !task.auto {
             continue;   
         }
+        // 前置任务未完成时不自动执行，前置任务完成后会重新调度
+        if blocked.contains(&task.id) {
+            logging!(debug, Type::Service, "任务 {} 被前置任务阻塞，暂不调度", task.id);
+            continue;
+        }
         let task_view = TaskView::try_from((task, app_state.inner())).unwrap();
         t2i_map
             .entry(task.due_to)
@@ -63,3 +97,33 @@ pub fn create_scheduled_tasks() -> HashMap<i64, Vec<TaskView>> {
 
     t2i_map
 }
+
+/// 按提醒时间分组的任务，普通任务、周期任务实体和子任务都会被纳入
+pub fn create_scheduled_reminders() -> HashMap<i64, Vec<TaskView>> {
+    let (start_date, end_of_day) = schedule_window();
+    if start_date == end_of_day {
+        return HashMap::new();
+    }
+    let task_records = get_uncompleted_reminders_until_end_of_day(start_date, end_of_day);
+    let mut r2i_map = HashMap::new();
+    let app_handle = Handle::global().app_handle().unwrap();
+    let app_state = app_handle.state::<AppState>();
+    for task in task_records.iter() {
+        let Some(reminder) = task.reminder else {
+            continue;
+        };
+        match TaskView::try_from((task, app_state.inner())) {
+            Ok(task_view) => {
+                r2i_map
+                    .entry(reminder)
+                    .or_insert_with(Vec::new)
+                    .push(task_view);
+            }
+            Err(e) => {
+                logging!(warn, Type::Service, "构建提醒任务视图失败: {}, {}", task.id, e);
+            }
+        }
+    }
+
+    r2i_map
+}
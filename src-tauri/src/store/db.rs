@@ -5,7 +5,7 @@ use rusqlite::Connection;
 use tauri::Emitter;
 use std::path::PathBuf;
 
-use super::module::*;
+use super::{migration, module::*};
 use crate::{
     logging,get_app_handle,
     schema::{
@@ -13,13 +13,10 @@ use crate::{
         TaskRecord,
     },
     utils::{
-        date::{
-            calculate_next_period, 
-            calculate_next_period_from_now, 
-            next_month, to_datetime_str
-        }, 
-        help::random_string, 
-        logging::Type
+        date::{next_month, to_datetime_str},
+        help::random_string,
+        logging::Type,
+        recurrence::Recurrence,
     },
 };
 pub struct Database {
@@ -29,73 +26,9 @@ unsafe impl Send for Database {}
 unsafe impl Sync for Database {}
 impl Database {
     pub fn new(db_path: PathBuf) -> Result<Self> {
-        let conn = Connection::open(db_path.join("ducker.db"))?;
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                value REAL DEFAULT 0,
-                completed INTEGER DEFAULT 0,
-                auto INTEGER DEFAULT 0,
-                parent_id TEXT,
-                periodic TEXT,
-                name TEXT NOT NULL,
-                actions TEXT,
-                created_at INTEGER,
-                due_to INTEGER,
-                reminder INTEGER
-            )",
-            [],
-        )?;
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS actions (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                desc TEXT,
-                command TEXT NOT NULL,
-                args TEXT,
-                type INTEGER NOT NULL,
-                wait INTEGER NOT NULL DEFAULT 0,
-                retry INTEGER  DEFAULT 0,
-                timeout INTEGER,
-                count INTEGER DEFAULT 0
-            )",
-            [],
-        )?;
-
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS periodic_tasks (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                interval INTEGER NOT NULL,
-                last_period INTEGER,
-                next_period INTEGER
-            )",
-            [],
-        )?;
-
-        // 创建索引以提升查询性能
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks(created_at)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_actions_name ON actions(name)",
-            [],
-        )?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_periodic_tasks_last_period ON periodic_tasks(last_period)",
-            [],
-        )?;
+        let mut conn = Connection::open(db_path.join("ducker.db"))?;
+        // 表结构统一由迁移维护，升级前会备份到 backups 目录
+        migration::run_migrations(&mut conn, &db_path.join("backups"))?;
 
         Ok(Self {
             conn: RwLock::new(conn),
@@ -140,6 +73,7 @@ impl Database {
             interval: row.get(2)?,
             last_period: row.get(3)?,
             next_period: row.get(4)?,
+            rule: row.get(5)?,
         })
     }
 
@@ -175,9 +109,10 @@ impl Database {
             .next_period
             .ok_or_else(|| anyhow!("周期性任务缺少 next_period: {}", current_periodic_task_id))?;
         let period = res.interval;
+        let recurrence = Recurrence::from_record(&res)?;
 
         let current_due_to = current_periodic_task.due_to as u64;
-        let expected_from_current = calculate_next_period(current_periodic_task.due_to, period) as u64;
+        let expected_from_current = recurrence.next_after(current_periodic_task.due_to) as u64;
 
         // 允许两种合法状态：
         // 1. 普通状态：current_task.due_to + interval == current_next_period
@@ -186,8 +121,8 @@ impl Database {
             (current_next_period as i64, current_next_period as i64)
         } else if current_due_to == current_next_period {
             (
-                calculate_next_period(current_next_period as i64, period),
-                calculate_next_period(current_next_period as i64, period),
+                recurrence.next_after(current_next_period as i64),
+                recurrence.next_after(current_next_period as i64),
             )
         } else {
             return Err(anyhow!("周期性任务已过期，无法创建下一个周期任务"));
@@ -217,7 +152,7 @@ impl Database {
         }
 
         // 如果原本应创建的时间点已经过去，则直接跳到未来最近的一次。
-        let future_due_to = calculate_next_period_from_now(created_due_to, period);
+        let future_due_to = recurrence.next_after_now(created_due_to);
         if future_due_to > created_due_to {
             created_due_to = future_due_to;
         }
@@ -234,6 +169,14 @@ impl Database {
             reminder: current_periodic_task.reminder.map(|reminder| to_datetime_str(created_due_to - (current_periodic_task.due_to - reminder)).into()),
             value: current_periodic_task.value.into(),
             periodic: current_periodic_task_id.clone().into(),
+            // 下一个周期沿用当前任务的标签
+            tags: Some(
+                self.get_task_tags(&current_periodic_task.id)?
+                    .into_iter()
+                    .map(|tag| tag.id)
+                    .collect(),
+            ),
+            blocked_by: None,
         };
         // 创建下一个周期任务实体
         self.create_task(&next_task)?;
@@ -244,11 +187,12 @@ impl Database {
             Some(created_due_to)
         };
         self.update_periodic_task_last_period(&current_periodic_task_id, synced_period)?;
-        let upcoming_period = calculate_next_period(created_due_to, period);
+        let upcoming_period = recurrence.next_after(created_due_to);
         Ok(PeriodicTaskRecord {
             id: current_periodic_task_id,
             name: next_task.name.clone(),   
             interval: period,
+            rule: res.rule.clone(),
             last_period: Some(created_due_to as u64),
             next_period: Some(upcoming_period as u64),
         })
@@ -259,7 +203,16 @@ impl Database {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::store::module::{PeriodicTaskManager, TaskManager};
+    use crate::schema::{
+        Action, ActionRecord, ActionRunFilter, ActionRunRecord, ActionType, ConflictStrategy, ExportOptions, ImportItemKind,
+        Backoff, ImportOptions, ImportOutcome, RetryOn, RetryPolicy, RunTrigger, SearchKind, TagData,
+        TagFilter, TrustLevel, Workflow,
+    };
+    use crate::store::module::{
+        ActionManager, ActionRunManager, BundleManager, DependencyManager, PeriodicTaskManager,
+        SearchManager, TagManager, TaskManager,
+    };
+    use crate::utils::date::{calculate_next_period, calculate_next_period_from_now};
     use chrono::{Duration, TimeZone};
     use std::{fs, path::PathBuf};
 
@@ -309,6 +262,8 @@ mod tests {
             created_at: Some(to_datetime_str(due_to - 3600)),
             due_to: Some(to_datetime_str(due_to)),
             reminder: None,
+            tags: None,
+            blocked_by: None,
         }
     }
 
@@ -463,9 +418,12 @@ mod tests {
                 created_at: None,
                 due_to: None,
                 reminder: None,
+                tags: None,
+                blocked_by: None,
             },
             name: "新名称".to_string(),
             interval: 7,
+            rule: None,
         };
 
         let updated = db.update_periodic_task("p-update", &periodic_data).unwrap();
@@ -518,4 +476,744 @@ mod tests {
                 && task.due_to == expected_next_due
         }));
     }
+
+    #[test]
+    fn create_next_periodic_task_follows_custom_rule() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+        let periodic_id = "periodic-every-2h";
+
+        let due = Local::now().timestamp() + Duration::days(1).num_seconds();
+        db.create_task(&build_task("task-custom", periodic_id, "巡检", due))
+            .unwrap();
+        insert_periodic_rule(
+            db,
+            periodic_id,
+            "巡检",
+            crate::schema::CUSTOM_PERIOD_INTERVAL,
+            Some(due),
+            Some(due + 7200),
+        );
+        db.conn
+            .write()
+            .execute(
+                "UPDATE periodic_tasks SET rule = '@every 2h' WHERE id = ?1",
+                [periodic_id],
+            )
+            .unwrap();
+
+        let current_task = db.get_task("task-custom").unwrap();
+        db.create_next_periodic_task(&current_task).unwrap();
+
+        let periodic = db.get_periodic_task(periodic_id).unwrap();
+        assert_eq!(periodic.rule.as_deref(), Some("@every 2h"));
+        assert_eq!(periodic.next_period, Some((due + 14400) as u64));
+        let tasks = db.get_all_tasks().unwrap();
+        assert!(tasks.iter().any(|task| {
+            task.id != "task-custom"
+                && task.periodic.as_deref() == Some(periodic_id)
+                && task.due_to == due + 7200
+        }));
+    }
+
+    #[test]
+    fn reminder_range_covers_regular_child_and_periodic_tasks() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+
+        let due = local_ts(2025, 6, 1, 9, 0, 0);
+        let reminder = due - 600;
+        let mut regular = build_task("task-regular", "unused", "普通任务", due);
+        regular.periodic = None;
+        regular.reminder = Some(to_datetime_str(reminder));
+        let mut child = regular.clone();
+        child.id = Some("task-child".to_string());
+        child.parent_id = Some("task-regular".to_string());
+        let mut periodic = build_task("task-periodic", "task-periodic", "周期任务", due);
+        periodic.reminder = Some(to_datetime_str(reminder));
+        let mut completed = regular.clone();
+        completed.id = Some("task-completed".to_string());
+        completed.completed = true;
+        let mut silent = regular.clone();
+        silent.id = Some("task-silent".to_string());
+        silent.reminder = None;
+        for task in [&regular, &child, &periodic, &completed, &silent] {
+            db.create_task(task).unwrap();
+        }
+        db.update_task_status("task-completed", true).unwrap();
+
+        let tasks = db
+            .get_uncompleted_tasks_by_reminder_range(reminder - 60, reminder + 60)
+            .unwrap();
+        let mut ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["task-child", "task-periodic", "task-regular"]);
+
+        db.update_task_reminder("task-child", Some(reminder + 3600)).unwrap();
+        let tasks = db
+            .get_uncompleted_tasks_by_reminder_range(reminder - 60, reminder + 60)
+            .unwrap();
+        assert!(tasks.iter().all(|t| t.id != "task-child"));
+        assert!(db.update_task_reminder("nonexistent", None).is_err());
+    }
+
+    #[test]
+    fn action_runs_are_filtered_and_ordered_by_start_time() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+
+        let base = local_ts(2025, 6, 1, 9, 0, 0);
+        let runs = [
+            ("run-1", "action-a", RunTrigger::Manual, base, true),
+            ("run-2", "action-a", RunTrigger::TaskDue, base + 60, false),
+            ("run-3", "action-b", RunTrigger::Tray, base + 120, true),
+        ];
+        for (id, action_id, trigger, started_at, success) in runs {
+            db.create_action_run(&ActionRunRecord {
+                id: id.to_string(),
+                action_id: Some(action_id.to_string()),
+                action_name: action_id.to_string(),
+                trigger,
+                started_at,
+                ended_at: started_at + 1,
+                duration_ms: 1000,
+                attempts: 1,
+                success,
+                exit_code: Some(if success { 0 } else { 1 }),
+                stdout: "out".to_string(),
+                stderr: String::new(),
+            })
+            .unwrap();
+        }
+
+        let all = db.get_action_runs(&ActionRunFilter::default()).unwrap();
+        let ids: Vec<&str> = all.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["run-3", "run-2", "run-1"]);
+
+        let filter = ActionRunFilter {
+            action_id: Some("action-a".to_string()),
+            success: Some(false),
+            ..Default::default()
+        };
+        let failed = db.get_action_runs(&filter).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].trigger, RunTrigger::TaskDue);
+        assert_eq!(failed[0].exit_code, Some(1));
+
+        let filter = ActionRunFilter {
+            start_time: Some(base + 30),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let latest = db.get_action_runs(&filter).unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].id, "run-3");
+
+        assert_eq!(db.delete_action_runs_before(base + 60).unwrap(), 1);
+        assert!(db.get_action_run("run-1").is_err());
+        assert_eq!(db.get_action_run("run-2").unwrap().action_name, "action-a");
+    }
+
+    fn build_action(name: &str, typ: &str, command: &str, args: Option<Vec<String>>) -> Action {
+        Action {
+            name: name.to_string(),
+            typ: typ.to_string(),
+            command: command.to_string(),
+            args,
+            ..Default::default()
+        }
+    }
+
+    fn seed_bundle_source(db: &Database) -> Vec<String> {
+        let open = db
+            .create_action(&build_action("打开文档", "url", "https://example.com", None))
+            .unwrap();
+        let notice = db
+            .create_action(&build_action("提醒", "notice", "标题", Some(vec!["正文".to_string()])))
+            .unwrap();
+        let group = db
+            .create_action(&build_action(
+                "组合",
+                "group",
+                "group",
+                Some(vec![open.id.clone(), notice.id.clone()]),
+            ))
+            .unwrap();
+
+        let due = local_ts(2025, 6, 1, 9, 0, 0);
+        let mut parent = build_task("task-parent", "task-parent", "父任务", due);
+        parent.actions = vec![group.id.clone()];
+        let mut child = build_task("task-child", "unused", "子任务", due);
+        child.parent_id = Some("task-parent".to_string());
+        child.periodic = None;
+        child.actions = vec![open.id.clone()];
+        db.create_task(&parent).unwrap();
+        db.create_task(&child).unwrap();
+        insert_periodic_rule(db, "task-parent", "父任务", 1, Some(due), Some(due + 86400));
+        vec![open.id, notice.id, group.id]
+    }
+
+    #[test]
+    fn bundle_round_trip_remaps_ids() {
+        let source = TestDb::new();
+        let source_action_ids = seed_bundle_source(&source.db);
+        let bundle = source
+            .db
+            .export_bundle(&ExportOptions {
+                task_ids: Some(vec!["task-parent".to_string()]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(bundle.tasks.len(), 2);
+        assert_eq!(bundle.actions.len(), 3);
+        assert_eq!(bundle.periodic_tasks.len(), 1);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let bundle: crate::schema::Bundle = serde_json::from_str(&json).unwrap();
+
+        let target = TestDb::new();
+        let report = target
+            .db
+            .import_bundle(&bundle, &ImportOptions::default())
+            .unwrap();
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.count(ImportItemKind::Action, ImportOutcome::Created), 3);
+        assert_eq!(report.count(ImportItemKind::Task, ImportOutcome::Created), 2);
+
+        let tasks = target.db.get_all_tasks().unwrap();
+        let parent = tasks.iter().find(|t| t.name == "父任务").unwrap();
+        let child = tasks.iter().find(|t| t.name == "子任务").unwrap();
+        assert_ne!(parent.id, "task-parent");
+        assert_eq!(child.parent_id.as_deref(), Some(parent.id.as_str()));
+        assert_eq!(parent.periodic.as_deref(), Some(parent.id.as_str()));
+        let periodic = target.db.get_periodic_task(&parent.id).unwrap();
+        assert_eq!(periodic.interval, 1);
+
+        let group = target.db.get_action(&parent.actions[0]).unwrap();
+        assert_eq!(group.name, "组合");
+        let members = group.args;
+        assert_eq!(members.len(), 2);
+        assert!(members.iter().all(|id| !source_action_ids.contains(id)));
+        assert_eq!(target.db.get_actions(&members).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn bundle_import_handles_name_conflicts_and_dry_run() {
+        let source = TestDb::new();
+        seed_bundle_source(&source.db);
+        let bundle = source.db.export_bundle(&ExportOptions::default()).unwrap();
+
+        let target = TestDb::new();
+        let existing = target
+            .db
+            .create_action(&build_action("打开文档", "url", "https://old.example.com", None))
+            .unwrap();
+
+        let preview = target
+            .db
+            .import_bundle(
+                &bundle,
+                &ImportOptions {
+                    conflict: ConflictStrategy::Rename,
+                    dry_run: true,
+                },
+            )
+            .unwrap();
+        assert!(preview.dry_run);
+        let renamed = preview
+            .items
+            .iter()
+            .find(|item| item.outcome == ImportOutcome::Renamed)
+            .unwrap();
+        assert_eq!(renamed.renamed_to.as_deref(), Some("打开文档 (2)"));
+        assert_eq!(target.db.get_all_actions().unwrap().len(), 1);
+        assert!(target.db.get_all_tasks().unwrap().is_empty());
+
+        let skipped = target
+            .db
+            .import_bundle(&bundle, &ImportOptions::default())
+            .unwrap();
+        assert_eq!(skipped.count(ImportItemKind::Action, ImportOutcome::Skipped), 1);
+        let child = target
+            .db
+            .get_all_tasks()
+            .unwrap()
+            .into_iter()
+            .find(|t| t.name == "子任务")
+            .unwrap();
+        assert_eq!(child.actions, vec![existing.id.clone()]);
+
+        target
+            .db
+            .import_bundle(
+                &bundle,
+                &ImportOptions {
+                    conflict: ConflictStrategy::Overwrite,
+                    dry_run: false,
+                },
+            )
+            .unwrap();
+        let overwritten = target.db.get_action(&existing.id).unwrap();
+        assert_eq!(overwritten.command, "https://example.com");
+        assert_eq!(target.db.get_all_actions().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn bundle_import_rejects_cycles_and_drops_invalid_periodic_rules() {
+        let source = TestDb::new();
+        seed_bundle_source(&source.db);
+        let bundle = source.db.export_bundle(&ExportOptions::default()).unwrap();
+
+        // 手动编辑后的导入文件中两个组合动作互相引用
+        let mut cyclic = bundle.clone();
+        let group_id = cyclic.actions.iter().find(|a| a.name == "组合").unwrap().id.clone();
+        let open = cyclic.actions.iter_mut().find(|a| a.name == "打开文档").unwrap();
+        open.typ = ActionType::Group;
+        open.command = "group".to_string();
+        open.args = vec![group_id];
+        let target = TestDb::new();
+        let err = target
+            .db
+            .import_bundle(&cyclic, &ImportOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("循环引用"), "{}", err);
+        assert!(target.db.get_all_actions().unwrap().is_empty());
+        assert!(target.db.get_all_tasks().unwrap().is_empty());
+
+        let mut invalid = bundle;
+        invalid.periodic_tasks[0].interval = crate::schema::CUSTOM_PERIOD_INTERVAL;
+        invalid.periodic_tasks[0].rule = None;
+        let report = target
+            .db
+            .import_bundle(&invalid, &ImportOptions::default())
+            .unwrap();
+        assert_eq!(report.count(ImportItemKind::PeriodicTask, ImportOutcome::Skipped), 1);
+        let parent = target
+            .db
+            .get_all_tasks()
+            .unwrap()
+            .into_iter()
+            .find(|t| t.name == "父任务")
+            .unwrap();
+        assert_eq!(parent.periodic, None);
+    }
+
+    #[test]
+    fn tasks_are_filtered_by_tags() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+        let tag = |name: &str| {
+            db.create_tag(&TagData {
+                name: name.to_string(),
+                color: None,
+            })
+            .unwrap()
+        };
+        let work = tag("工作");
+        let urgent = tag("紧急");
+        let home = tag("家庭");
+        assert!(db.create_tag(&TagData { name: "工作".to_string(), color: None }).is_err());
+
+        let due = Local::now().timestamp() + 3600;
+        let mut report = build_task("t-report", "", "周报", due);
+        report.periodic = None;
+        report.tags = Some(vec![work.id.clone(), urgent.id.clone()]);
+        db.create_task(&report).unwrap();
+        let mut meeting = build_task("t-meeting", "", "例会", due);
+        meeting.periodic = None;
+        meeting.tags = Some(vec![work.id.clone()]);
+        db.create_task(&meeting).unwrap();
+        let mut shopping = build_task("t-shopping", "", "买菜", due);
+        shopping.periodic = None;
+        shopping.tags = Some(vec![home.id.clone()]);
+        db.create_task(&shopping).unwrap();
+
+        let filter = |ids: Vec<&str>, match_all: bool| {
+            let mut result: Vec<String> = db
+                .get_task_ids_by_tags(&TagFilter {
+                    tag_ids: ids.into_iter().map(String::from).collect(),
+                    match_all,
+                })
+                .unwrap()
+                .into_iter()
+                .collect();
+            result.sort();
+            result
+        };
+        assert_eq!(filter(vec![&work.id], false), vec!["t-meeting", "t-report"]);
+        assert_eq!(filter(vec![&work.id, &urgent.id], true), vec!["t-report"]);
+        assert_eq!(
+            filter(vec![&urgent.id, &home.id], false),
+            vec!["t-report", "t-shopping"]
+        );
+
+        // 不传 tags 时更新任务不影响已有标签
+        meeting.name = "周例会".to_string();
+        meeting.tags = None;
+        db.update_task("t-meeting", &meeting).unwrap();
+        assert_eq!(db.get_task_tags("t-meeting").unwrap(), vec![work.clone()]);
+
+        db.delete_tag(&work.id).unwrap();
+        assert_eq!(db.get_task_tags("t-report").unwrap(), vec![urgent]);
+        assert!(db.get_task_tags("t-meeting").unwrap().is_empty());
+        assert!(db.set_task_tags("t-meeting", &[work.id.clone()]).is_err());
+    }
+
+    #[test]
+    fn search_finds_tasks_and_actions_by_cjk_and_prefix() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+        let due = Local::now().timestamp() + 3600;
+        let mut report = build_task("t-report", "", "编写周报 Weekly", due);
+        report.periodic = None;
+        db.create_task(&report).unwrap();
+        let mut plan = build_task("t-plan", "", "整理月度计划", due);
+        plan.periodic = None;
+        db.create_task(&plan).unwrap();
+        db.update_task_status("t-report", true).unwrap();
+        let mut backup = build_action("数据库备份", "command", "backup.sh", None);
+        backup.desc = "每晚备份周报与附件".to_string();
+        let backup = db.create_action(&backup).unwrap();
+
+        let hits = db.search("周报", 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        // 名称命中的权重高于描述
+        assert_eq!(hits[0].id, "t-report");
+        assert_eq!(hits[0].kind, SearchKind::Task);
+        assert_eq!(hits[0].completed, Some(true));
+        assert_eq!(hits[0].title, "编写周报 Weekly");
+        assert_eq!(hits[0].highlight, "编写<mark>周报</mark> Weekly");
+        assert_eq!(hits[1].id, backup.id);
+        assert_eq!(hits[1].kind, SearchKind::Action);
+        assert_eq!(hits[1].snippet.as_deref(), Some("每晚备份<mark>周报</mark>与附件"));
+
+        // 前缀匹配且不区分大小写，多个词需同时命中
+        let hits = db.search("week 编写", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].highlight, "<mark>编写</mark>周报 <mark>Weekly</mark>");
+        assert!(db.search("周计划", 10).unwrap().is_empty());
+        assert!(db.search("  ", 10).unwrap().is_empty());
+
+        // 改名与删除后索引同步更新
+        plan.name = "整理季度计划".to_string();
+        db.update_task("t-plan", &plan).unwrap();
+        assert!(db.search("月度", 10).unwrap().is_empty());
+        assert_eq!(db.search("季度", 10).unwrap()[0].id, "t-plan");
+        db.delete_action(&backup.id).unwrap();
+        assert_eq!(db.search("附件", 10).unwrap().len(), 0);
+
+        // 其他工具直接写入数据库时不依赖应用注册的函数，检索时再更新索引
+        let external = rusqlite::Connection::open(test_db.path.join("ducker.db")).unwrap();
+        external
+            .execute("UPDATE tasks SET name = '外部修改的计划' WHERE id = 't-plan'", [])
+            .unwrap();
+        external.execute("DELETE FROM tasks WHERE id = 't-report'", []).unwrap();
+        drop(external);
+        assert_eq!(db.search("外部", 10).unwrap()[0].id, "t-plan");
+        assert!(db.search("季度", 10).unwrap().is_empty());
+        assert!(db.search("Weekly", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn task_dependencies_block_until_blockers_complete() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+        let due = Local::now().timestamp() + 3600;
+        let create = |id: &str, blocked_by: Option<Vec<String>>| {
+            let mut task = build_task(id, "", id, due);
+            task.periodic = None;
+            task.blocked_by = blocked_by;
+            db.create_task(&task).unwrap();
+        };
+        create("design", None);
+        create("review", None);
+        create("build", Some(vec!["design".to_string(), "review".to_string()]));
+        create("release", Some(vec!["build".to_string()]));
+
+        assert_eq!(db.get_task_blockers("build").unwrap(), vec!["design", "review"]);
+        assert_eq!(db.get_task_dependents("build").unwrap(), vec!["release"]);
+        let mut blocked: Vec<_> = db.get_blocked_task_ids().unwrap().into_iter().collect();
+        blocked.sort();
+        assert_eq!(blocked, vec!["build", "release"]);
+
+        // 自身依赖、直接与间接循环、不存在的前置任务都会被拒绝，且不改动已有依赖
+        assert!(db.set_task_blockers("design", &["design".to_string()]).is_err());
+        assert!(db.set_task_blockers("build", &["release".to_string()]).is_err());
+        assert!(db.set_task_blockers("design", &["release".to_string()]).is_err());
+        assert!(db.set_task_blockers("design", &["missing".to_string()]).is_err());
+        assert!(db.get_task_blockers("design").unwrap().is_empty());
+
+        // 创建或更新任务时前置任务无效，任务本身与标签也不会写入
+        let tag = db.create_tag(&TagData { name: "后端".to_string(), color: None }).unwrap();
+        let mut invalid = build_task("deploy", "", "deploy", due);
+        invalid.periodic = None;
+        invalid.tags = Some(vec![tag.id.clone()]);
+        invalid.blocked_by = Some(vec!["missing".to_string()]);
+        assert!(db.create_task(&invalid).is_err());
+        assert!(db.get_task("deploy").is_err());
+        let mut cyclic = build_task("review", "", "review", due);
+        cyclic.periodic = None;
+        cyclic.name = "改名".to_string();
+        cyclic.tags = Some(vec![tag.id.clone()]);
+        cyclic.blocked_by = Some(vec!["release".to_string()]);
+        assert!(db.update_task("review", &cyclic).is_err());
+        assert_eq!(db.get_task("review").unwrap().name, "review");
+        let tagged = db
+            .get_task_ids_by_tags(&TagFilter {
+                tag_ids: vec![tag.id],
+                match_all: false,
+            })
+            .unwrap();
+        assert!(tagged.is_empty());
+        assert!(db.get_task_blockers("review").unwrap().is_empty());
+
+        db.update_task_status("design", true).unwrap();
+        assert!(db.get_unblocked_dependents(&["design".to_string()]).unwrap().is_empty());
+        db.update_task_status("review", true).unwrap();
+        assert_eq!(
+            db.get_unblocked_dependents(&["review".to_string()]).unwrap(),
+            vec!["build"]
+        );
+        assert!(!db.is_task_blocked("build").unwrap());
+        assert!(db.is_task_blocked("release").unwrap());
+
+        // 删除前置任务后不再阻塞
+        db.delete_task("build").unwrap();
+        assert!(db.get_task_blockers("release").unwrap().is_empty());
+        assert!(db.get_blocked_task_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn workflow_actions_are_validated_and_remapped_on_import() {
+        let source = TestDb::new();
+        let build = source
+            .db
+            .create_action(&build_action("构建", "command", "make", None))
+            .unwrap();
+        let notify = source
+            .db
+            .create_action(&build_action("通知", "notice", "构建失败", None))
+            .unwrap();
+        let definition = |build_id: &str, notify_id: &str| {
+            format!(
+                r#"{{"steps": [
+                    {{"id": "build", "action_id": "{build_id}", "on_failure": {{"goto": "notify"}}, "on_success": "end"}},
+                    {{"id": "notify", "action_id": "{notify_id}", "args": ["{{{{prev.stderr}}}}"]}}
+                ]}}"#
+            )
+        };
+        assert!(source
+            .db
+            .create_action(&build_action("坏流程", "workflow", &definition(&build.id, "missing"), None))
+            .is_err());
+        assert!(source
+            .db
+            .create_action(&build_action("坏流程", "workflow", "not json", None))
+            .is_err());
+        let workflow = source
+            .db
+            .create_action(&build_action("构建流程", "workflow", &definition(&build.id, &notify.id), None))
+            .unwrap();
+        assert!(source
+            .db
+            .update_action(
+                &workflow.id,
+                &build_action("构建流程", "workflow", &definition(&workflow.id, &notify.id), None)
+            )
+            .is_err());
+
+        // 只导出工作流时，引用的动作会一并导出，导入后改写为新的 id
+        let bundle = source
+            .db
+            .export_bundle(&ExportOptions {
+                task_ids: Some(Vec::new()),
+                action_ids: Some(vec![workflow.id.clone()]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(bundle.actions.len(), 3);
+        let target = TestDb::new();
+        let report = target.db.import_bundle(&bundle, &ImportOptions::default()).unwrap();
+        assert!(report.warnings.is_empty());
+        let imported = target
+            .db
+            .get_all_actions()
+            .unwrap()
+            .into_iter()
+            .find(|action| action.name == "构建流程")
+            .unwrap();
+        let steps = Workflow::parse(&imported.command).unwrap().steps;
+        let action_ids: Vec<String> = target
+            .db
+            .get_actions(&[steps[0].action_id.clone(), steps[1].action_id.clone()])
+            .unwrap()
+            .into_iter()
+            .map(|action| action.name)
+            .collect();
+        assert_eq!(action_ids, vec!["构建", "通知"]);
+        assert_eq!(steps[1].args, Some(vec!["{{prev.stderr}}".to_string()]));
+    }
+
+    #[test]
+    fn group_actions_reject_missing_members_and_cycles() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+        let echo = db
+            .create_action(&build_action("输出", "command", "echo", None))
+            .unwrap();
+        let group = |name: &str, members: &[&str]| {
+            let members = members.iter().map(|id| id.to_string()).collect();
+            build_action(name, "group", "group", Some(members))
+        };
+
+        assert!(db.create_action(&group("坏组合", &["missing"])).is_err());
+        let inner = db.create_action(&group("内层", &[&echo.id])).unwrap();
+        let outer = db.create_action(&group("外层", &[&inner.id, &echo.id])).unwrap();
+
+        // 直接引用自身与经由其他组合动作的间接循环都会被拒绝
+        assert!(db.update_action(&inner.id, &group("内层", &[&inner.id])).is_err());
+        let err = db
+            .update_action(&inner.id, &group("内层", &[&echo.id, &outer.id]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("检测到循环引用: {} -> {} -> {}", inner.id, outer.id, inner.id)
+        );
+        assert_eq!(db.get_action(&inner.id).unwrap().args, vec![echo.id.clone()]);
+
+        // 多个组合动作共用同一个子动作不算循环
+        let shared = db.create_action(&group("共用", &[&echo.id])).unwrap();
+        db.update_action(&inner.id, &group("内层", &[&echo.id, &shared.id]))
+            .unwrap();
+        db.update_action(&outer.id, &group("外层", &[&inner.id, &shared.id]))
+            .unwrap();
+    }
+
+    #[test]
+    fn action_args_round_trip_with_commas_and_newlines() {
+        let test_db = TestDb::new();
+        let db = &test_db.db;
+        let body = vec!["构建完成, 耗时 3 分钟\n第二行".to_string()];
+        let notice = db
+            .create_action(&build_action("通知", "notice", "标题", Some(body.clone())))
+            .unwrap();
+        let args = vec!["-c".to_string(), "echo a,b".to_string(), " 保留空格 ".to_string()];
+        let env: std::collections::BTreeMap<String, String> =
+            [("MODE".to_string(), "a=b, c".to_string())].into();
+        let retry_policy = RetryPolicy {
+            backoff: Backoff::Exponential,
+            max_delay_ms: Some(30_000),
+            jitter: true,
+            retry_on: RetryOn::ExitCodes(vec![75]),
+            ..Default::default()
+        };
+        let command = db
+            .create_action(&Action {
+                cwd: Some("/tmp".to_string()),
+                env: Some(env.clone()),
+                stdin: Some("yes\n".to_string()),
+                output_file: Some("/tmp/job.log".to_string()),
+                trust: Some(TrustLevel::Confirm),
+                retry_policy: Some(retry_policy.clone()),
+                ..build_action("命令", "command", "sh", Some(args.clone()))
+            })
+            .unwrap();
+
+        assert_eq!(Action::from(db.get_action(&notice.id).unwrap()).args, Some(body));
+        let stored = Action::from(db.get_action(&command.id).unwrap());
+        assert_eq!(stored.args, Some(args.clone()));
+        assert_eq!(stored.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(stored.env, Some(env.clone()));
+        assert_eq!(stored.stdin.as_deref(), Some("yes\n"));
+        assert_eq!(stored.output_file.as_deref(), Some("/tmp/job.log"));
+        assert_eq!(stored.retry_policy, Some(retry_policy.clone()));
+
+        // 编辑器只提交基本字段，未提供的字段保持原值，空字符串表示清空
+        let edited = Action {
+            timeout: Some(60),
+            ..build_action("命令", "command", "sh", Some(args.clone()))
+        };
+        let updated = Action::from(db.update_action(&command.id, &edited).unwrap());
+        assert_eq!(updated.timeout, Some(60));
+        assert_eq!(updated.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(updated.env, Some(env));
+        assert_eq!(updated.stdin.as_deref(), Some("yes\n"));
+        assert_eq!(updated.output_file.as_deref(), Some("/tmp/job.log"));
+        assert_eq!(updated.trust, Some(TrustLevel::Confirm));
+        assert_eq!(updated.retry_policy, Some(retry_policy));
+        let cleared = Action {
+            cwd: Some(String::new()),
+            ..edited
+        };
+        assert_eq!(db.update_action(&command.id, &cleared).unwrap().cwd, None);
+        assert!(db.update_action("missing", &cleared).is_err());
+        let empty = db
+            .create_action(&build_action("无参数", "command", "ls", None))
+            .unwrap();
+        assert_eq!(Action::from(db.get_action(&empty.id).unwrap()).args, None);
+
+        // 旧版导出文件中的 args 为逗号拼接的字符串
+        let legacy: ActionRecord = serde_json::from_str(
+            r#"{"id": "a1", "type": "Group", "name": "组合", "wait": 0, "timeout": null, "retry": null,
+                "desc": "", "command": "group", "args": "a2, a3", "count": 0}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.args, vec!["a2", "a3"]);
+    }
+
+    #[test]
+    fn imported_command_and_http_actions_require_confirmation() {
+        let source = TestDb::new();
+        let trusted = source
+            .db
+            .create_action(&build_action("部署", "command", "deploy.sh", None))
+            .unwrap();
+        let denied = source
+            .db
+            .create_action(&Action {
+                trust: Some(TrustLevel::Deny),
+                ..build_action("清理", "command", "rm -rf /tmp/cache", None)
+            })
+            .unwrap();
+        let request = source
+            .db
+            .create_action(&build_action(
+                "上报",
+                "http",
+                r#"{"url":"https://example.com/?key={{env.AWS_SECRET_ACCESS_KEY}}"}"#,
+                None,
+            ))
+            .unwrap();
+        let notice = source
+            .db
+            .create_action(&build_action("提醒", "notice", "标题", None))
+            .unwrap();
+        assert_eq!(source.db.get_action(&trusted.id).unwrap().trust, None);
+        assert_eq!(source.db.get_action(&denied.id).unwrap().trust, Some(TrustLevel::Deny));
+
+        let bundle = source
+            .db
+            .export_bundle(&ExportOptions {
+                task_ids: Some(Vec::new()),
+                action_ids: Some(vec![trusted.id, denied.id, request.id, notice.id]),
+                ..Default::default()
+            })
+            .unwrap();
+        let target = TestDb::new();
+        target.db.import_bundle(&bundle, &ImportOptions::default()).unwrap();
+        let trust_of = |name: &str| {
+            target
+                .db
+                .get_all_actions()
+                .unwrap()
+                .into_iter()
+                .find(|action| action.name == name)
+                .unwrap()
+                .trust
+        };
+        assert_eq!(trust_of("部署"), Some(TrustLevel::Confirm));
+        assert_eq!(trust_of("清理"), Some(TrustLevel::Deny));
+        assert_eq!(trust_of("上报"), Some(TrustLevel::Confirm));
+        assert_eq!(trust_of("提醒"), None);
+    }
 }
@@ -1,30 +1,154 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use crate::{
-    schema::{Action, ActionRecord, ActionType},
+    logging,
+    schema::{decode_args, encode_args, encode_env, encode_retry_policy, Action, ActionRecord, ActionType, GroupOptions, HttpRequest, TrustLevel, Workflow},
     store::{db::Database, module::ActionManager},
-    utils::help::random_string,
+    utils::{help::random_string, logging::Type},
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
+
+impl Database {
+    pub fn build_action_record_from_row(row: &rusqlite::Row) -> rusqlite::Result<ActionRecord> {
+        let args_text: String = row.get(4)?;
+        let typ_number: u8 = row.get(5)?;
+        let env_json: Option<String> = row.get(11)?;
+        let trust: Option<String> = row.get(14)?;
+        let retry_policy_json: Option<String> = row.get(15)?;
+        let env = match env_json {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                logging!(warn, Type::Database, "动作环境变量反序列化失败，已忽略: {e}");
+                None
+            }),
+            None => None,
+        };
+        let retry_policy = match retry_policy_json {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                logging!(warn, Type::Database, "动作重试策略反序列化失败，已忽略: {e}");
+                None
+            }),
+            None => None,
+        };
+        Ok(ActionRecord {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            desc: row.get(2)?,
+            command: row.get(3)?,
+            args: decode_args(&args_text),
+            typ: ActionType::try_from(typ_number).unwrap_or(ActionType::Command),
+            wait: row.get(6)?,
+            retry: row.get(7)?,
+            timeout: row.get(8)?,
+            count: row.get(9)?,
+            cwd: row.get(10)?,
+            env,
+            stdin: row.get(12)?,
+            output_file: row.get(13)?,
+            trust: trust.as_deref().and_then(TrustLevel::parse),
+            retry_policy,
+        })
+    }
+
+    /// 请求、组合动作与工作流的定义需要合法，引用的动作都已存在且不能形成循环
+    fn validate_definition(&self, id: Option<&str>, action: &Action) -> Result<()> {
+        if let Some(policy) = &action.retry_policy {
+            policy.validate()?;
+        }
+        let member_ids = match ActionType::try_from(action.typ.as_str())? {
+            ActionType::Http => {
+                HttpRequest::parse(&action.command)?;
+                return Ok(());
+            }
+            ActionType::Group => {
+                GroupOptions::parse(&action.command)?;
+                action
+                    .args
+                    .iter()
+                    .flatten()
+                    .map(|id| id.trim().to_string())
+                    .filter(|id| !id.is_empty())
+                    .collect::<Vec<_>>()
+            }
+            ActionType::Workflow => Workflow::parse(&action.command)?.action_ids(),
+            _ => return Ok(()),
+        };
+        if id.is_some_and(|id| member_ids.iter().any(|member| member == id)) {
+            bail!("动作 {} 不能引用自身", action.name);
+        }
+        let found = self.get_actions(&member_ids)?;
+        if let Some(missing) = member_ids
+            .iter()
+            .find(|id| !found.iter().any(|record| &record.id == *id))
+        {
+            bail!("动作 {} 引用的动作不存在: {}", action.name, missing);
+        }
+        // 新建的动作还没有被其他动作引用，只有更新时才可能形成循环
+        if let Some(id) = id {
+            if let Some(path) = self.find_member_path(&member_ids, id)? {
+                bail!("检测到循环引用: {} -> {}", id, path.join(" -> "));
+            }
+        }
+        Ok(())
+    }
+
+    /// 从 `start` 出发沿组合动作、工作流的成员查找 `target`，找到时返回经过的动作 id
+    fn find_member_path(&self, start: &[String], target: &str) -> Result<Option<Vec<String>>> {
+        find_member_path(start, target, |id| {
+            Ok(self
+                .get_actions(&[id.to_string()])?
+                .iter()
+                .flat_map(ActionRecord::member_ids)
+                .collect())
+        })
+    }
+}
+
+/// 从 `start` 出发沿 `members_of` 给出的成员查找 `target`，找到时返回经过的动作 id
+pub(crate) fn find_member_path(
+    start: &[String],
+    target: &str,
+    mut members_of: impl FnMut(&str) -> Result<Vec<String>>,
+) -> Result<Option<Vec<String>>> {
+    let mut parents: HashMap<String, Option<String>> =
+        start.iter().map(|id| (id.clone(), None)).collect();
+    let mut pending: VecDeque<String> = start.iter().cloned().collect();
+    while let Some(current) = pending.pop_front() {
+        if current == target {
+            let mut path = vec![current];
+            while let Some(Some(parent)) = path.last().and_then(|id| parents.get(id)) {
+                path.push(parent.clone());
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+        for member in members_of(&current)? {
+            if !parents.contains_key(&member) {
+                parents.insert(member.clone(), Some(current.clone()));
+                pending.push_back(member);
+            }
+        }
+    }
+    Ok(None)
+}
 
 impl ActionManager for Database {
     fn create_action(&self, action: &Action) -> Result<ActionRecord> {
+        self.validate_definition(None, action)?;
         let conn = self.conn.write();
-        let mut args_text = String::new();
-        if let Some(args) = &action.args {
-            args_text = args.join(",");
-        }
+        let args = action.args.clone().unwrap_or_default();
+        let args_text = encode_args(&args);
         let action_id = format!("act{}", random_string(6));
         let data = action.clone();
         let typ: ActionType = ActionType::try_from(data.typ.as_str())?;
-        let wait = if matches!(typ, ActionType::Group) {
+        let wait = if matches!(typ, ActionType::Group | ActionType::Workflow) {
             0
         } else {
             data.wait
         };
         conn.execute(
-            "INSERT INTO actions (id, name, desc, command, args, type, wait, retry, timeout, count)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO actions (id, name, desc, command, args, type, wait, retry, timeout, count,
+            cwd, env, stdin, output_file, trust, retry_policy)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             (
                 &action_id,
                 &action.name,
@@ -36,6 +160,12 @@ impl ActionManager for Database {
                 data.retry,
                 data.timeout,
                 0,
+                &data.cwd,
+                encode_env(data.env.as_ref()),
+                &data.stdin,
+                &data.output_file,
+                data.trust.map(|trust| trust.as_str()),
+                encode_retry_policy(data.retry_policy.as_ref()),
             ),
         )?;
         let record = ActionRecord {
@@ -44,24 +174,31 @@ impl ActionManager for Database {
             desc: data.desc,
             wait: data.wait,
             command: data.command,
-            args: args_text.clone(),
+            args,
             typ,
             retry: data.retry,
             timeout: data.timeout,
             count: data.count,
+            cwd: data.cwd,
+            env: data.env,
+            stdin: data.stdin,
+            output_file: data.output_file,
+            trust: data.trust,
+            retry_policy: data.retry_policy,
         };
         Ok(record)
     }
 
+    /// 未提供的 cwd、env、stdin、output_file、trust、retry_policy 保持原值，cwd 等文本字段传入空字符串时清空
     fn update_action(&self, id: &str, action: &Action) -> Result<ActionRecord> {
-        let conn = self.conn.write();
-        let mut args_text = String::new();
-        if let Some(args) = &action.args {
-            args_text = args.join(",");
-        }
+        self.validate_definition(Some(id), action)?;
+        let args_text = encode_args(action.args.as_deref().unwrap_or_default());
         let count = action.count.unwrap_or(0);
-        conn.execute(
-            "UPDATE actions SET name = ?1, desc = ?2, command = ?3, args = ?4, type = ?5,wait = ?6, retry = ?7, timeout =?8, count = ?9
+        let updated = self.conn.write().execute(
+            "UPDATE actions SET name = ?1, desc = ?2, command = ?3, args = ?4, type = ?5,wait = ?6, retry = ?7, timeout =?8, count = ?9,
+            cwd = NULLIF(COALESCE(?11, cwd), ''), env = COALESCE(?12, env), stdin = NULLIF(COALESCE(?13, stdin), ''),
+            output_file = NULLIF(COALESCE(?14, output_file), ''), trust = COALESCE(?15, trust),
+            retry_policy = COALESCE(?16, retry_policy)
             WHERE id = ?10",
             (
                 &action.name,
@@ -74,20 +211,17 @@ impl ActionManager for Database {
                 &action.timeout,
                 &count,
                 id,
+                &action.cwd,
+                encode_env(action.env.as_ref()),
+                &action.stdin,
+                &action.output_file,
+                action.trust.map(|trust| trust.as_str()),
+                encode_retry_policy(action.retry_policy.as_ref()),
             ))?;
-        let record = ActionRecord {
-            id: id.to_string(),
-            name: action.name.clone(),
-            desc: action.desc.clone(),
-            wait: action.wait,
-            command: action.command.clone(),
-            args: args_text.clone(),
-            typ: ActionType::try_from(action.typ.as_str())?,
-            retry: action.retry,
-            timeout: action.timeout,
-            count: action.count,
-        };
-        Ok(record)
+        if updated == 0 {
+            bail!("动作不存在: {}", id);
+        }
+        self.get_action(id)
     }
 
     fn update_action_count(&self, id: &str) -> Result<()> {
@@ -109,34 +243,11 @@ impl ActionManager for Database {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
             "SELECT 
-            id, name, desc, command, args, type, wait, retry, timeout, count
+            id, name, desc, command, args, type, wait, retry, timeout, count,
+            cwd, env, stdin, output_file, trust, retry_policy
             FROM actions WHERE id = ?1",
         )?;
-        let action = stmt.query_row([id], |row| {
-            let id = row.get(0)?;
-            let name = row.get(1)?;
-            let desc = row.get(2)?;
-            let command = row.get(3)?;
-            let args_text: String = row.get(4)?;
-            let typ_number: u8 = row.get(5)?;
-            let typ = ActionType::try_from(typ_number).unwrap_or(ActionType::Command);
-            let wait = row.get(6)?;
-            let retry: Option<usize> = row.get(7)?;
-            let timeout: Option<u64> = row.get(8)?;
-            let count: Option<usize> = row.get(9)?;
-            Ok(ActionRecord {
-                id,
-                name,
-                desc,
-                wait,
-                command,
-                args: args_text,
-                typ,
-                retry,
-                timeout,
-                count,
-            })
-        })?;
+        let action = stmt.query_row([id], Database::build_action_record_from_row)?;
         Ok(action)
     }
 
@@ -148,7 +259,8 @@ impl ActionManager for Database {
         let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let query = format!(
             "SELECT 
-            id, name, desc, command, args, type, wait, retry, timeout, count
+            id, name, desc, command, args, type, wait, retry, timeout, count,
+            cwd, env, stdin, output_file, trust, retry_policy
             FROM actions WHERE id IN ({})",
             placeholders
         );
@@ -159,31 +271,7 @@ impl ActionManager for Database {
             .map(|id| id as &dyn rusqlite::ToSql)
             .collect::<Vec<_>>();
 
-        let action_iter = stmt.query_map(params.as_slice(), |row| {
-            let id = row.get(0)?;
-            let name = row.get(1)?;
-            let desc = row.get(2)?;
-            let command = row.get(3)?;
-            let args_text: String = row.get(4)?;
-            let typ_number: u8 = row.get(5)?;
-            let typ = ActionType::try_from(typ_number).unwrap_or(ActionType::Command);
-            let wait = row.get(6)?;
-            let retry: Option<usize> = row.get(7)?;
-            let timeout: Option<u64> = row.get(8)?;
-            let count: Option<usize> = row.get(9)?;
-            Ok(ActionRecord {
-                id,
-                name,
-                desc,
-                command,
-                args: args_text,
-                typ,
-                wait,
-                retry,
-                timeout,
-                count,
-            })
-        })?;
+        let action_iter = stmt.query_map(params.as_slice(), Database::build_action_record_from_row)?;
 
         // 将查询结果存储为map，以id为key
         let mut action_map = HashMap::new();
@@ -207,35 +295,12 @@ impl ActionManager for Database {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
             "SELECT 
-            id, name, desc, command, args, type, wait, retry, timeout, count
+            id, name, desc, command, args, type, wait, retry, timeout, count,
+            cwd, env, stdin, output_file, trust, retry_policy
             FROM actions",
         )?;
 
-        let action_iter = stmt.query_map([], |row| {
-            let id = row.get(0)?;
-            let name = row.get(1)?;
-            let desc = row.get(2)?;
-            let command = row.get(3)?;
-            let args_text: String = row.get(4)?;
-            let typ_number: u8 = row.get(5)?;
-            let typ = ActionType::try_from(typ_number).unwrap_or(ActionType::Command);
-            let wait = row.get(6)?;
-            let retry: Option<usize> = row.get(7)?;
-            let timeout: Option<u64> = row.get(8)?;
-            let count: Option<usize> = row.get(9)?;
-            Ok(ActionRecord {
-                id,
-                name,
-                desc,
-                command,
-                args: args_text,
-                typ,
-                wait,
-                retry,
-                timeout,
-                count,
-            })
-        })?;
+        let action_iter = stmt.query_map([], Database::build_action_record_from_row)?;
 
         let mut actions = Vec::new();
         for action in action_iter {
@@ -248,36 +313,13 @@ impl ActionManager for Database {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
             "SELECT 
-            id, name, desc, command, args, type, wait, retry, timeout, count
+            id, name, desc, command, args, type, wait, retry, timeout, count,
+            cwd, env, stdin, output_file, trust, retry_policy
             FROM actions
             ORDER BY count DESC
             LIMIT ?1",
         )?;
-        let action_iter = stmt.query_map([limit as i64], |row| {
-            let id = row.get(0)?;
-            let name = row.get(1)?;
-            let desc = row.get(2)?;
-            let command = row.get(3)?;
-            let args_text: String = row.get(4)?;
-            let typ_number: u8 = row.get(5)?;
-            let typ = ActionType::try_from(typ_number).unwrap_or(ActionType::Command);
-            let wait = row.get(6)?;
-            let retry: Option<usize> = row.get(7)?;
-            let timeout: Option<u64> = row.get(8)?;
-            let count: Option<usize> = row.get(9)?;
-            Ok(ActionRecord {
-                id,
-                name,
-                desc,
-                command,
-                args: args_text,
-                typ,
-                wait,
-                retry,
-                timeout,
-                count,
-            })
-        })?;
+        let action_iter = stmt.query_map([limit as i64], Database::build_action_record_from_row)?;
 
         let mut actions = Vec::new();
         for action in action_iter {
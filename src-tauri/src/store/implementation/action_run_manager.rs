@@ -0,0 +1,134 @@
+use anyhow::Result;
+use rusqlite::params;
+
+use crate::{
+    logging,
+    schema::{ActionRunFilter, ActionRunRecord, RunTrigger},
+    store::{db::Database, module::ActionRunManager},
+    utils::logging::Type,
+};
+
+const DEFAULT_RUN_LIMIT: usize = 200;
+
+impl Database {
+    pub fn build_action_run_record_from_row(
+        row: &rusqlite::Row,
+    ) -> rusqlite::Result<ActionRunRecord> {
+        let trigger_number: u8 = row.get(3)?;
+        let stdout: Option<String> = row.get(10)?;
+        let stderr: Option<String> = row.get(11)?;
+        Ok(ActionRunRecord {
+            id: row.get(0)?,
+            action_id: row.get(1)?,
+            action_name: row.get(2)?,
+            trigger: RunTrigger::try_from(trigger_number).unwrap_or(RunTrigger::Manual),
+            started_at: row.get(4)?,
+            ended_at: row.get(5)?,
+            duration_ms: row.get(6)?,
+            attempts: row.get(7)?,
+            success: row.get(8)?,
+            exit_code: row.get(9)?,
+            stdout: stdout.unwrap_or_default(),
+            stderr: stderr.unwrap_or_default(),
+        })
+    }
+}
+
+impl ActionRunManager for Database {
+    fn create_action_run(&self, run: &ActionRunRecord) -> Result<()> {
+        let conn = self.conn.write();
+        conn.execute(
+            "INSERT INTO action_runs (id, action_id, action_name, trigger, started_at, ended_at, duration_ms, attempts, success, exit_code, stdout, stderr)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                &run.id,
+                &run.action_id,
+                &run.action_name,
+                u8::from(run.trigger),
+                run.started_at,
+                run.ended_at,
+                run.duration_ms,
+                run.attempts,
+                run.success,
+                run.exit_code,
+                &run.stdout,
+                &run.stderr,
+            ],
+        )?;
+        logging!(debug, Type::Database, "记录动作执行: {} ({})", run.action_name, run.id);
+        Ok(())
+    }
+
+    fn get_action_run(&self, id: &str) -> Result<ActionRunRecord> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, action_id, action_name, trigger, started_at, ended_at, duration_ms, attempts, success, exit_code, stdout, stderr
+            FROM action_runs WHERE id = ?1",
+        )?;
+        let run = stmt.query_row([id], |row| Self::build_action_run_record_from_row(row))?;
+        Ok(run)
+    }
+
+    fn get_action_runs(&self, filter: &ActionRunFilter) -> Result<Vec<ActionRunRecord>> {
+        let conn = self.conn.read();
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(action_id) = &filter.action_id {
+            values.push(Box::new(action_id.clone()));
+            conditions.push(format!("action_id = ?{}", values.len()));
+        }
+        if let Some(trigger) = filter.trigger {
+            values.push(Box::new(u8::from(trigger)));
+            conditions.push(format!("trigger = ?{}", values.len()));
+        }
+        if let Some(success) = filter.success {
+            values.push(Box::new(success));
+            conditions.push(format!("success = ?{}", values.len()));
+        }
+        if let Some(start_time) = filter.start_time {
+            values.push(Box::new(start_time));
+            conditions.push(format!("started_at >= ?{}", values.len()));
+        }
+        if let Some(end_time) = filter.end_time {
+            values.push(Box::new(end_time));
+            conditions.push(format!("started_at <= ?{}", values.len()));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        values.push(Box::new(filter.limit.unwrap_or(DEFAULT_RUN_LIMIT) as i64));
+        let query = format!(
+            "SELECT id, action_id, action_name, trigger, started_at, ended_at, duration_ms, attempts, success, exit_code, stdout, stderr
+            FROM action_runs
+            {}
+            ORDER BY started_at DESC, rowid DESC
+            LIMIT ?{}",
+            where_clause,
+            values.len()
+        );
+
+        let mut stmt = conn.prepare(&query)?;
+        let params = values.iter().map(|v| v.as_ref()).collect::<Vec<_>>();
+        let runs = stmt.query_map(params.as_slice(), |row| {
+            Self::build_action_run_record_from_row(row)
+        })?;
+
+        let mut result = Vec::new();
+        for run in runs {
+            result.push(run?);
+        }
+        Ok(result)
+    }
+
+    fn delete_action_runs_before(&self, timestamp: i64) -> Result<usize> {
+        let conn = self.conn.write();
+        let rows_affected = conn.execute(
+            "DELETE FROM action_runs WHERE started_at < ?1",
+            [timestamp],
+        )?;
+        logging!(info, Type::Database, "清理动作执行记录: {} 条", rows_affected);
+        Ok(rows_affected)
+    }
+}
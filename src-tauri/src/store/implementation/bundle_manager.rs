@@ -0,0 +1,447 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use rusqlite::{params, OptionalExtension, Transaction};
+
+use crate::{
+    logging,
+    schema::{
+        encode_args, encode_env, encode_retry_policy, ActionRecord, ActionType, Bundle, ConflictStrategy, ExportOptions, ImportItem,
+        ImportItemKind, ImportOptions, ImportOutcome, ImportReport, TaskRecord, TrustLevel,
+        Workflow, BUNDLE_FORMAT_VERSION,
+    },
+    store::{
+        db::Database,
+        implementation::action_manager::find_member_path,
+        module::{ActionManager, BundleManager, PeriodicTaskManager, TaskManager},
+    },
+    utils::{
+        help::{get_uid, random_string},
+        logging::Type,
+        recurrence::Recurrence,
+    },
+};
+
+impl Database {
+    /// 获取指定任务及其全部子任务
+    fn collect_task_trees(&self, ids: &[String]) -> Result<Vec<TaskRecord>> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        let mut queue = self.get_tasks(ids)?;
+        while let Some(task) = queue.pop() {
+            if !seen.insert(task.id.clone()) {
+                continue;
+            }
+            queue.extend(self.get_tasks_by_parent_id(&task.id)?);
+            result.push(task);
+        }
+        Ok(result)
+    }
+
+    /// 获取指定动作，组合动作的成员会递归包含进来
+    fn collect_actions_with_members(&self, ids: Vec<String>) -> Result<Vec<ActionRecord>> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        let mut pending = ids;
+        while !pending.is_empty() {
+            pending.retain(|id| seen.insert(id.clone()));
+            let actions = self.get_actions(&pending)?;
+            pending = actions.iter().flat_map(ActionRecord::member_ids).collect();
+            result.extend(actions);
+        }
+        Ok(result)
+    }
+}
+
+/// 导入的命令与请求动作来自其他设备，默认需要确认后才能执行
+fn imported_trust(action: &ActionRecord) -> Option<TrustLevel> {
+    match (&action.typ, action.trust) {
+        (ActionType::Command | ActionType::Http, None | Some(TrustLevel::Always)) => Some(TrustLevel::Confirm),
+        (_, trust) => trust,
+    }
+}
+
+fn insert_action(tx: &Transaction, id: &str, name: &str, action: &ActionRecord) -> Result<()> {
+    tx.execute(
+        "INSERT INTO actions (id, name, desc, command, args, type, wait, retry, timeout, count,
+        cwd, env, stdin, output_file, trust, retry_policy)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            id,
+            name,
+            &action.desc,
+            &action.command,
+            encode_args(&action.args),
+            u8::from(action.typ.clone()),
+            action.wait,
+            action.retry,
+            action.timeout,
+            &action.cwd,
+            encode_env(action.env.as_ref()),
+            &action.stdin,
+            &action.output_file,
+            imported_trust(action).map(|trust| trust.as_str()),
+            encode_retry_policy(action.retry_policy.as_ref()),
+        ],
+    )?;
+    Ok(())
+}
+
+fn overwrite_action(tx: &Transaction, id: &str, action: &ActionRecord) -> Result<()> {
+    tx.execute(
+        "UPDATE actions SET desc = ?1, command = ?2, args = ?3, type = ?4, wait = ?5, retry = ?6, timeout = ?7,
+        cwd = ?9, env = ?10, stdin = ?11, output_file = ?12, trust = ?13,
+        retry_policy = ?14
+        WHERE id = ?8",
+        params![
+            &action.desc,
+            &action.command,
+            encode_args(&action.args),
+            u8::from(action.typ.clone()),
+            action.wait,
+            action.retry,
+            action.timeout,
+            id,
+            &action.cwd,
+            encode_env(action.env.as_ref()),
+            &action.stdin,
+            &action.output_file,
+            imported_trust(action).map(|trust| trust.as_str()),
+            encode_retry_policy(action.retry_policy.as_ref()),
+        ],
+    )?;
+    Ok(())
+}
+
+/// 导入后组合动作、工作流的成员关系，键为动作 id
+fn load_member_graph(tx: &Transaction) -> Result<HashMap<String, Vec<String>>> {
+    let mut stmt = tx.prepare(
+        "SELECT
+        id, name, desc, command, args, type, wait, retry, timeout, count,
+        cwd, env, stdin, output_file, trust, retry_policy
+        FROM actions WHERE type IN (?1, ?2)",
+    )?;
+    let graph = stmt
+        .query_map(
+            params![u8::from(ActionType::Group), u8::from(ActionType::Workflow)],
+            Database::build_action_record_from_row,
+        )?
+        .map(|record| record.map(|record| (record.id.clone(), record.member_ids())))
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(graph)
+}
+
+fn find_action_by_name(tx: &Transaction, name: &str) -> Result<Option<String>> {
+    let id = tx
+        .query_row("SELECT id FROM actions WHERE name = ?1", [name], |row| row.get(0))
+        .optional()?;
+    Ok(id)
+}
+
+/// 生成 `名称 (2)`、`名称 (3)` 这样不与现有动作重复的名称
+fn unique_action_name(tx: &Transaction, name: &str) -> Result<String> {
+    let mut index = 2;
+    loop {
+        let candidate = format!("{} ({})", name, index);
+        if find_action_by_name(tx, &candidate)?.is_none() {
+            return Ok(candidate);
+        }
+        index += 1;
+    }
+}
+
+impl BundleManager for Database {
+    fn export_bundle(&self, options: &ExportOptions) -> Result<Bundle> {
+        let mut tasks = match &options.task_ids {
+            Some(ids) => self.collect_task_trees(ids)?,
+            None => self.get_all_tasks()?,
+        };
+        if options.exclude_completed {
+            tasks.retain(|task| !task.completed);
+        }
+
+        let actions = if options.task_ids.is_none() && options.action_ids.is_none() {
+            self.get_all_actions()?
+        } else {
+            let mut action_ids: Vec<String> = tasks
+                .iter()
+                .flat_map(|task| task.actions.iter().cloned())
+                .collect();
+            action_ids.extend(options.action_ids.clone().unwrap_or_default());
+            self.collect_actions_with_members(action_ids)?
+        };
+
+        let mut periodic_ids = HashSet::new();
+        let mut periodic_tasks = Vec::new();
+        for periodic_id in tasks.iter().filter_map(|task| task.periodic.as_ref()) {
+            if periodic_ids.insert(periodic_id.clone()) {
+                if let Ok(periodic) = self.get_periodic_task(periodic_id) {
+                    periodic_tasks.push(periodic);
+                }
+            }
+        }
+
+        logging!(
+            info,
+            Type::Database,
+            true,
+            "导出数据: {} 个任务, {} 个动作, {} 个周期规则",
+            tasks.len(),
+            actions.len(),
+            periodic_tasks.len()
+        );
+        Ok(Bundle {
+            version: BUNDLE_FORMAT_VERSION,
+            exported_at: Local::now().timestamp(),
+            actions,
+            tasks,
+            periodic_tasks,
+        })
+    }
+
+    fn import_bundle(&self, bundle: &Bundle, options: &ImportOptions) -> Result<ImportReport> {
+        if bundle.version > BUNDLE_FORMAT_VERSION {
+            bail!("不支持的导出文件版本: {}", bundle.version);
+        }
+        let mut report = ImportReport {
+            dry_run: options.dry_run,
+            ..Default::default()
+        };
+        let mut conn = self.conn.write();
+        let tx = conn.transaction()?;
+
+        // 1. 动作：按名称处理冲突，并记录新旧 id 的映射
+        let mut action_map = HashMap::new();
+        let mut written_groups = Vec::new();
+        for action in &bundle.actions {
+            let existing = find_action_by_name(&tx, &action.name)?;
+            let (target_id, outcome, renamed_to) = match (existing, options.conflict) {
+                (None, _) => {
+                    let id = format!("act{}", random_string(6));
+                    insert_action(&tx, &id, &action.name, action)?;
+                    (id, ImportOutcome::Created, None)
+                }
+                (Some(id), ConflictStrategy::Skip) => (id, ImportOutcome::Skipped, None),
+                (Some(id), ConflictStrategy::Overwrite) => {
+                    overwrite_action(&tx, &id, action)?;
+                    (id, ImportOutcome::Overwritten, None)
+                }
+                (Some(_), ConflictStrategy::Rename) => {
+                    let name = unique_action_name(&tx, &action.name)?;
+                    let id = format!("act{}", random_string(6));
+                    insert_action(&tx, &id, &name, action)?;
+                    (id, ImportOutcome::Renamed, Some(name))
+                }
+            };
+            if outcome != ImportOutcome::Skipped
+                && matches!(action.typ, ActionType::Group | ActionType::Workflow)
+            {
+                written_groups.push((target_id.clone(), action));
+            }
+            action_map.insert(action.id.clone(), target_id.clone());
+            report.items.push(ImportItem {
+                kind: ImportItemKind::Action,
+                name: action.name.clone(),
+                source_id: action.id.clone(),
+                target_id,
+                outcome,
+                renamed_to,
+                note: None,
+            });
+        }
+
+        // 组合动作的成员 id 需要等全部动作导入后再改写
+        for (target_id, action) in &written_groups {
+            let (target_id, action) = (target_id.clone(), *action);
+            if matches!(action.typ, ActionType::Workflow) {
+                let Ok(mut workflow) = Workflow::parse(&action.command) else {
+                    report
+                        .warnings
+                        .push(format!("工作流 {} 的定义无效，未改写引用的动作", action.name));
+                    continue;
+                };
+                for step in &mut workflow.steps {
+                    match action_map.get(&step.action_id) {
+                        Some(id) => step.action_id = id.clone(),
+                        None => report.warnings.push(format!(
+                            "工作流 {} 的步骤 {} 引用的动作 {} 不在导入文件中",
+                            action.name, step.id, step.action_id
+                        )),
+                    }
+                }
+                tx.execute(
+                    "UPDATE actions SET command = ?1 WHERE id = ?2",
+                    params![serde_json::to_string(&workflow)?, target_id],
+                )?;
+                continue;
+            }
+            let mut members = Vec::new();
+            for member in action.member_ids() {
+                match action_map.get(&member) {
+                    Some(id) => members.push(id.clone()),
+                    None => report.warnings.push(format!(
+                        "组合动作 {} 的成员 {} 不在导入文件中，已忽略",
+                        action.name, member
+                    )),
+                }
+            }
+            tx.execute(
+                "UPDATE actions SET args = ?1 WHERE id = ?2",
+                params![encode_args(&members), target_id],
+            )?;
+        }
+
+        // 覆盖已有动作时可能与库中的组合动作形成循环，与界面中修改动作一样拒绝
+        if !written_groups.is_empty() {
+            let graph = load_member_graph(&tx)?;
+            for (target_id, action) in &written_groups {
+                let members = graph.get(target_id).cloned().unwrap_or_default();
+                let path = find_member_path(&members, target_id, |id| {
+                    Ok(graph.get(id).cloned().unwrap_or_default())
+                })?;
+                if let Some(path) = path {
+                    bail!(
+                        "导入后动作 {} 会形成循环引用: {} -> {}",
+                        action.name,
+                        target_id,
+                        path.join(" -> ")
+                    );
+                }
+            }
+        }
+
+        // 周期规则先校验，无效的规则跳过导入，引用它的任务也不再关联
+        let recurrences: Vec<_> = bundle
+            .periodic_tasks
+            .iter()
+            .map(Recurrence::from_record)
+            .collect();
+        let skipped_periodic: HashSet<&str> = bundle
+            .periodic_tasks
+            .iter()
+            .zip(&recurrences)
+            .filter(|(_, recurrence)| recurrence.is_err())
+            .map(|(periodic, _)| periodic.id.as_str())
+            .collect();
+
+        // 2. 任务：全部使用新 id，周期规则与其所属任务共用同一个 id
+        let mut task_map: HashMap<String, String> = HashMap::new();
+        for id in bundle
+            .tasks
+            .iter()
+            .map(|task| &task.id)
+            .chain(bundle.periodic_tasks.iter().map(|periodic| &periodic.id))
+        {
+            task_map.entry(id.clone()).or_insert_with(|| get_uid("task"));
+        }
+        for task in &bundle.tasks {
+            let target_id = task_map[&task.id].clone();
+            let parent_id = task.parent_id.as_ref().and_then(|id| {
+                let mapped = task_map.get(id).cloned();
+                if mapped.is_none() {
+                    report.warnings.push(format!(
+                        "任务 {} 的父任务不在导入文件中，已作为顶层任务导入",
+                        task.name
+                    ));
+                }
+                mapped
+            });
+            let periodic = task
+                .periodic
+                .as_ref()
+                .filter(|id| !skipped_periodic.contains(id.as_str()))
+                .and_then(|id| task_map.get(id).cloned());
+            let mut actions = Vec::new();
+            for action_id in &task.actions {
+                match action_map.get(action_id) {
+                    Some(id) => actions.push(id.clone()),
+                    None => report.warnings.push(format!(
+                        "任务 {} 引用的动作 {} 不在导入文件中，已忽略",
+                        task.name, action_id
+                    )),
+                }
+            }
+            tx.execute(
+                "INSERT INTO tasks (id, value, completed, auto, parent_id, periodic, name, actions, created_at, due_to, reminder)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    &target_id,
+                    task.value,
+                    task.completed,
+                    task.auto,
+                    parent_id,
+                    periodic,
+                    &task.name,
+                    serde_json::to_string(&actions)?,
+                    task.created_at,
+                    task.due_to,
+                    task.reminder,
+                ],
+            )?;
+            report.items.push(ImportItem {
+                kind: ImportItemKind::Task,
+                name: task.name.clone(),
+                source_id: task.id.clone(),
+                target_id,
+                outcome: ImportOutcome::Created,
+                renamed_to: None,
+                note: None,
+            });
+        }
+
+        // 3. 周期规则
+        for (periodic, recurrence) in bundle.periodic_tasks.iter().zip(recurrences) {
+            let target_id = task_map[&periodic.id].clone();
+            let outcome = match recurrence {
+                Ok(_) => {
+                    tx.execute(
+                        "INSERT INTO periodic_tasks (id, name, interval, last_period, next_period, rule)
+                        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            &target_id,
+                            &periodic.name,
+                            periodic.interval,
+                            periodic.last_period,
+                            periodic.next_period,
+                            &periodic.rule,
+                        ],
+                    )?;
+                    ImportOutcome::Created
+                }
+                Err(e) => {
+                    report
+                        .warnings
+                        .push(format!("周期规则 {} 无效，已跳过: {}", periodic.name, e));
+                    ImportOutcome::Skipped
+                }
+            };
+            report.items.push(ImportItem {
+                kind: ImportItemKind::PeriodicTask,
+                name: periodic.name.clone(),
+                source_id: periodic.id.clone(),
+                target_id,
+                outcome,
+                renamed_to: None,
+                note: None,
+            });
+        }
+
+        // 预览模式下回滚事务，结果与实际导入完全一致
+        if options.dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+        }
+        logging!(
+            info,
+            Type::Database,
+            true,
+            "导入数据{}: {} 项, {} 条警告",
+            if options.dry_run { "（预览）" } else { "" },
+            report.items.len(),
+            report.warnings.len()
+        );
+        Ok(report)
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use rusqlite::{params, Connection};
+
+use crate::store::{
+    db::Database,
+    module::{DependencyManager, TaskManager},
+};
+
+/// 从 `blocker_id` 沿前置关系向上能否到达 `task_id`，能到达说明新增依赖会形成循环
+fn reaches(conn: &Connection, blocker_id: &str, task_id: &str) -> rusqlite::Result<bool> {
+    conn.prepare(
+        "WITH RECURSIVE upstream(id) AS (
+            SELECT ?1
+            UNION
+            SELECT d.blocker_id FROM task_dependencies d JOIN upstream u ON d.task_id = u.id
+        )
+        SELECT 1 FROM upstream WHERE id = ?2",
+    )?
+    .exists(params![blocker_id, task_id])
+}
+
+fn validate_blockers(conn: &Connection, task_id: &str, blocker_ids: &[String]) -> Result<()> {
+    for blocker_id in blocker_ids {
+        if blocker_id == task_id {
+            bail!("任务不能依赖自身: {}", task_id);
+        }
+        let exists = conn
+            .prepare("SELECT 1 FROM tasks WHERE id = ?1")?
+            .exists([blocker_id])?;
+        if !exists {
+            bail!("前置任务不存在: {}", blocker_id);
+        }
+        if reaches(conn, blocker_id, task_id)? {
+            bail!("任务依赖存在循环: {} 已直接或间接依赖 {}", blocker_id, task_id);
+        }
+    }
+    Ok(())
+}
+
+/// 在调用方的事务内整体替换任务的前置任务，校验失败时由调用方回滚
+pub(crate) fn replace_task_blockers(conn: &Connection, task_id: &str, blocker_ids: &[String]) -> Result<()> {
+    validate_blockers(conn, task_id, blocker_ids)?;
+    conn.execute("DELETE FROM task_dependencies WHERE task_id = ?1", [task_id])?;
+    for blocker_id in blocker_ids.iter().collect::<HashSet<_>>() {
+        conn.execute(
+            "INSERT INTO task_dependencies (task_id, blocker_id) VALUES (?1, ?2)",
+            params![task_id, blocker_id],
+        )?;
+    }
+    Ok(())
+}
+
+impl DependencyManager for Database {
+    fn get_task_blockers(&self, task_id: &str) -> Result<Vec<String>> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(
+            "SELECT blocker_id FROM task_dependencies WHERE task_id = ?1 ORDER BY blocker_id",
+        )?;
+        let ids = stmt
+            .query_map([task_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    fn get_task_dependents(&self, blocker_id: &str) -> Result<Vec<String>> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(
+            "SELECT task_id FROM task_dependencies WHERE blocker_id = ?1 ORDER BY task_id",
+        )?;
+        let ids = stmt
+            .query_map([blocker_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    fn set_task_blockers(&self, task_id: &str, blocker_ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.write();
+        let tx = conn.transaction()?;
+        replace_task_blockers(&tx, task_id, blocker_ids)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn is_task_blocked(&self, task_id: &str) -> Result<bool> {
+        let conn = self.conn.read();
+        let blocked = conn
+            .prepare(
+                "SELECT 1 FROM task_dependencies d JOIN tasks t ON t.id = d.blocker_id
+                 WHERE d.task_id = ?1 AND t.completed = 0",
+            )?
+            .exists([task_id])?;
+        Ok(blocked)
+    }
+
+    fn get_blocked_task_ids(&self) -> Result<HashSet<String>> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT d.task_id FROM task_dependencies d JOIN tasks t ON t.id = d.blocker_id
+             WHERE t.completed = 0",
+        )?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+        Ok(ids)
+    }
+    fn get_unblocked_dependents(&self, completed_ids: &[String]) -> Result<Vec<String>> {
+        let blocked = self.get_blocked_task_ids()?;
+        let mut unblocked = Vec::new();
+        for id in completed_ids {
+            for dependent in self.get_task_dependents(id)? {
+                if blocked.contains(&dependent) || unblocked.contains(&dependent) {
+                    continue;
+                }
+                if !self.get_task(&dependent)?.completed {
+                    unblocked.push(dependent);
+                }
+            }
+        }
+        Ok(unblocked)
+    }
+}
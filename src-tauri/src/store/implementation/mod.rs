@@ -1,3 +1,8 @@
 pub mod action_manager;
+pub mod action_run_manager;
+pub mod bundle_manager;
 pub mod task_manager;
 pub mod periodic_task_manager;
+pub mod tag_manager;
+pub mod search_manager;
+pub mod dependency_manager;
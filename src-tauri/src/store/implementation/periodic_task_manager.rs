@@ -2,18 +2,34 @@ use anyhow::Result;
 use rusqlite::params;
 
 use crate::{
-    schema::{PeriodicTaskData, PeriodicTaskRecord, TaskRecord},
+    schema::{PeriodicTaskData, PeriodicTaskRecord, TaskRecord, CUSTOM_PERIOD_INTERVAL},
     store::{db::Database, module::PeriodicTaskManager},
     utils::{
         logging::Type,
-        date::{calculate_next_period, str_to_datetime}
+        date::str_to_datetime,
+        recurrence::Recurrence,
     },
     logging
 };
 
+/// 仅自定义周期保存规则，去掉首尾空白
+fn normalized_rule(task: &PeriodicTaskData) -> Option<String> {
+    if task.interval != CUSTOM_PERIOD_INTERVAL {
+        return None;
+    }
+    task.rule
+        .as_deref()
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(str::to_string)
+}
+
 
 impl PeriodicTaskManager for Database {
     fn create_periodic_task(&self, task: &PeriodicTaskData) -> Result<PeriodicTaskRecord> {
+        // 先校验周期规则，避免写入无法计算下一周期的任务
+        let recurrence = Recurrence::new(task.interval, task.rule.as_deref())?;
+        let rule = normalized_rule(task);
         // 直接创建带有periodic字段的任务，避免先创建再更新的冗余操作
         let conn = self.conn.write();
         let actions = serde_json::to_string(&task.task.actions)?;
@@ -61,10 +77,10 @@ impl PeriodicTaskManager for Database {
         
         // 继续使用同一个连接创建periodic_tasks记录
         let mut stmt = conn.prepare(
-            "INSERT INTO periodic_tasks (id, name, interval, next_period, last_period) 
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO periodic_tasks (id, name, interval, next_period, last_period, rule) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         )?;
-        let next_period = calculate_next_period(task_record.due_to, task.interval as u8);
+        let next_period = recurrence.next_after(task_record.due_to);
         let last_period = if task.interval == 0 || task.interval == 100 {
             None
         } else {
@@ -76,6 +92,7 @@ impl PeriodicTaskManager for Database {
             task.interval.clone() as u8,
             Some(next_period),
             last_period,
+            &rule,
         ])?;
 
         logging!(debug, Type::Database, "创建周期性任务成功: {}", row_id);
@@ -84,6 +101,7 @@ impl PeriodicTaskManager for Database {
             id: task_record.id.clone(),
             name: task.name.clone(),
             interval: task.interval.clone() as u8,
+            rule,
             last_period: last_period.map(|v| v as u64), 
             next_period: Some(next_period as u64),
         })
@@ -96,10 +114,10 @@ impl PeriodicTaskManager for Database {
         let conn = self.conn.write();
 
         // 获取当前的 next_period 和 interval
-        let (current_next_period, current_last_period, interval): (Option<i64>, Option<i64>, u8) = conn.query_row(
-            "SELECT next_period, last_period, interval FROM periodic_tasks WHERE id = ?1",
+        let (current_next_period, current_last_period, interval, rule): (Option<i64>, Option<i64>, u8, Option<String>) = conn.query_row(
+            "SELECT next_period, last_period, interval, rule FROM periodic_tasks WHERE id = ?1",
             params![id],
-            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
         )?;
 
         let (new_last_period, new_next_period) = if let Some(period) = period {
@@ -112,7 +130,7 @@ impl PeriodicTaskManager for Database {
             let current_next_period = current_next_period.ok_or_else(|| anyhow::anyhow!("周期性任务缺少 next_period: {}", id))?;
             (
                 current_next_period,
-                calculate_next_period(current_next_period, interval),
+                Recurrence::new(interval, rule.as_deref())?.next_after(current_next_period),
             )
         };
 
@@ -182,7 +200,7 @@ impl PeriodicTaskManager for Database {
     fn get_enabled_periodic_tasks(&self) -> Result<Vec<PeriodicTaskRecord>> {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
-            "SELECT id, name, interval, last_period, next_period 
+            "SELECT id, name, interval, last_period, next_period, rule 
              FROM periodic_tasks ",
         )?;
 
@@ -200,7 +218,7 @@ impl PeriodicTaskManager for Database {
     fn get_startup_periodic_tasks(&self) -> Result<Vec<PeriodicTaskRecord>> {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
-            "SELECT id, name, interval, last_period, next_period 
+            "SELECT id, name, interval, last_period, next_period, rule 
              FROM periodic_tasks 
              WHERE interval = 0 OR interval = 100",
         )?;
@@ -219,7 +237,7 @@ impl PeriodicTaskManager for Database {
     fn get_periodic_task(&self, id: &str) -> Result<PeriodicTaskRecord> {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
-            "SELECT id, name, interval, last_period, next_period 
+            "SELECT id, name, interval, last_period, next_period, rule 
              FROM periodic_tasks WHERE id = ?1",
         )?;
 
@@ -238,17 +256,31 @@ impl PeriodicTaskManager for Database {
         task: &PeriodicTaskData,
     ) -> Result<PeriodicTaskRecord> {
         let current_periodic_task = self.get_periodic_task(periodic_id)?;
+        let recurrence = Recurrence::new(task.interval, task.rule.as_deref())?;
+        let rule = normalized_rule(task);
+        let mut last_period = current_periodic_task.last_period;
+        let mut next_period = current_periodic_task.next_period;
+        // 周期规则变化后，以任务当前截止时间为锚点重新推算下一周期
+        let rule_changed = current_periodic_task.interval != task.interval || current_periodic_task.rule != rule;
+        if rule_changed && task.interval != 0 && task.interval != 100 {
+            if let Some(due_to) = task.task.due_to.as_deref() {
+                let due_to_ts = str_to_datetime(due_to).timestamp();
+                last_period = Some(due_to_ts as u64);
+                next_period = Some(recurrence.next_after(due_to_ts) as u64);
+            }
+        }
         let conn = self.conn.write();
         let query = "UPDATE periodic_tasks 
-                     SET name = ?1, interval = ?2, last_period = ?3, next_period = ?4
-                     WHERE id = ?5";
+                     SET name = ?1, interval = ?2, last_period = ?3, next_period = ?4, rule = ?5
+                     WHERE id = ?6";
         conn.execute(
             query,
             params![
                 task.name.as_str(),
                 task.interval,
-                current_periodic_task.last_period,  
-                current_periodic_task.next_period,
+                last_period,
+                next_period,
+                &rule,
                 periodic_id
             ],
         )?;
@@ -256,13 +288,16 @@ impl PeriodicTaskManager for Database {
             id: periodic_id.to_string(),
             name: task.name.clone(),
             interval: task.interval,
-            last_period: current_periodic_task.last_period,
-            next_period: current_periodic_task.next_period,
+            rule,
+            last_period,
+            next_period,
         })
     }
 
     /// 将已有任务转为周期任务：只创建 periodic_tasks 记录，不重复插入 tasks 表
     fn create_periodic_rule_only(&self, task: &PeriodicTaskData) -> Result<PeriodicTaskRecord> {
+        let recurrence = Recurrence::new(task.interval, task.rule.as_deref())?;
+        let rule = normalized_rule(task);
         let conn = self.conn.write();
         let task_id = task.task.id.as_deref().unwrap_or("");
 
@@ -278,7 +313,7 @@ impl PeriodicTaskManager for Database {
         )?;
 
         // 2. 只插入 periodic_tasks 记录
-        let next_period = calculate_next_period(due_to_ts, task.interval as u8);
+        let next_period = recurrence.next_after(due_to_ts);
         let last_period = if task.interval == 0 || task.interval == 100 {
             None
         } else {
@@ -286,8 +321,8 @@ impl PeriodicTaskManager for Database {
         };
 
         let mut stmt = conn.prepare(
-            "INSERT INTO periodic_tasks (id, name, interval, next_period, last_period)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO periodic_tasks (id, name, interval, next_period, last_period, rule)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         )?;
         let row_id = stmt.insert(params![
             task_id,
@@ -295,6 +330,7 @@ impl PeriodicTaskManager for Database {
             task.interval as u8,
             Some(next_period),
             last_period,
+            &rule,
         ])?;
 
         logging!(debug, Type::Database, "将任务转为周期任务成功: {}", row_id);
@@ -303,6 +339,7 @@ impl PeriodicTaskManager for Database {
             id: task_id.to_string(),
             name: task.name.clone(),
             interval: task.interval,
+            rule,
             last_period: last_period.map(|v| v as u64),
             next_period: Some(next_period as u64),
         })
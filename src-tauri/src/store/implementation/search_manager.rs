@@ -0,0 +1,96 @@
+use anyhow::Result;
+use rusqlite::{params, OptionalExtension};
+
+use crate::{
+    schema::{SearchHit, SearchKind},
+    store::{
+        db::Database,
+        module::SearchManager,
+        search::{build_match_query, segment, unsegment, HIGHLIGHT_CLOSE, HIGHLIGHT_OPEN},
+    },
+};
+
+impl Database {
+    /// 将触发器记录的待更新条目重新切分后写入索引，删除的条目只移除索引
+    fn sync_search_index(&self) -> Result<()> {
+        let mut conn = self.conn.write();
+        let tx = conn.transaction()?;
+        let pending = {
+            let mut stmt = tx.prepare("SELECT kind, item_id FROM search_pending")?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+        for (kind, item_id) in &pending {
+            tx.execute(
+                "DELETE FROM search_index WHERE kind = ?1 AND item_id = ?2",
+                params![kind, item_id],
+            )?;
+            let source = match SearchKind::parse(kind) {
+                Some(SearchKind::Task) => "SELECT name, '' FROM tasks WHERE id = ?1",
+                Some(SearchKind::Action) => "SELECT name, IFNULL(desc, '') FROM actions WHERE id = ?1",
+                Some(SearchKind::Periodic) => "SELECT name, '' FROM periodic_tasks WHERE id = ?1",
+                None => continue,
+            };
+            let text = tx
+                .query_row(source, [item_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })
+                .optional()?;
+            if let Some((title, body)) = text {
+                tx.execute(
+                    "INSERT INTO search_index (kind, item_id, title, body) VALUES (?1, ?2, ?3, ?4)",
+                    params![kind, item_id, segment(&title), segment(&body)],
+                )?;
+            }
+        }
+        tx.execute("DELETE FROM search_pending", [])?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl SearchManager for Database {
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let Some(expression) = build_match_query(query) else {
+            return Ok(Vec::new());
+        };
+        self.sync_search_index()?;
+        let conn = self.conn.read();
+        // 名称的权重高于描述；bm25 越小越相关
+        let mut stmt = conn.prepare(
+            "SELECT search_index.kind, search_index.item_id, search_index.title,
+                    highlight(search_index, 2, ?2, ?3),
+                    snippet(search_index, 3, ?2, ?3, '…', 24),
+                    bm25(search_index, 0.0, 0.0, 10.0, 1.0) AS rank_score,
+                    tasks.completed
+             FROM search_index
+             LEFT JOIN tasks ON search_index.kind = 'task' AND tasks.id = search_index.item_id
+             WHERE search_index MATCH ?1
+             ORDER BY rank_score
+             LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![expression, HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE, limit as i64],
+            |row| {
+                let kind: String = row.get(0)?;
+                let snippet: String = row.get(4)?;
+                let score: f64 = row.get(5)?;
+                Ok(SearchHit {
+                    kind: SearchKind::parse(&kind).unwrap_or(SearchKind::Task),
+                    id: row.get(1)?,
+                    title: unsegment(&row.get::<_, String>(2)?),
+                    highlight: unsegment(&row.get::<_, String>(3)?),
+                    snippet: (!snippet.is_empty()).then(|| unsegment(&snippet)),
+                    completed: row.get(6)?,
+                    score: -score,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
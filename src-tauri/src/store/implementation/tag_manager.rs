@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use rusqlite::{params, params_from_iter, Connection};
+
+use crate::{
+    logging,
+    schema::{Tag, TagData, TagFilter},
+    store::{db::Database, module::TagManager},
+    utils::{help::get_uid, logging::Type},
+};
+
+impl Database {
+    pub fn build_tag_from_row(row: &rusqlite::Row) -> rusqlite::Result<Tag> {
+        Ok(Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+}
+
+fn normalized_name(tag: &TagData) -> Result<String> {
+    let name = tag.name.trim();
+    if name.is_empty() {
+        bail!("标签名称不能为空");
+    }
+    Ok(name.to_string())
+}
+
+/// 在调用方的事务内整体替换任务的标签，标签不存在时由调用方回滚
+pub(crate) fn replace_task_tags(conn: &Connection, task_id: &str, tag_ids: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM task_tags WHERE task_id = ?1", [task_id])?;
+    for tag_id in tag_ids.iter().collect::<HashSet<_>>() {
+        let exists = conn
+            .prepare("SELECT 1 FROM tags WHERE id = ?1")?
+            .exists([tag_id])?;
+        if !exists {
+            bail!("标签不存在: {}", tag_id);
+        }
+        conn.execute(
+            "INSERT INTO task_tags (task_id, tag_id) VALUES (?1, ?2)",
+            params![task_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+impl TagManager for Database {
+    fn create_tag(&self, tag: &TagData) -> Result<Tag> {
+        let record = Tag {
+            id: get_uid("tag"),
+            name: normalized_name(tag)?,
+            color: tag.color.clone(),
+            created_at: Local::now().timestamp(),
+        };
+        let conn = self.conn.write();
+        conn.execute(
+            "INSERT INTO tags (id, name, color, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![&record.id, &record.name, &record.color, record.created_at],
+        )?;
+        logging!(info, Type::Database, "创建标签成功: {}", record.name);
+        Ok(record)
+    }
+
+    fn update_tag(&self, id: &str, tag: &TagData) -> Result<Tag> {
+        {
+            let conn = self.conn.write();
+            let changed = conn.execute(
+                "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+                params![normalized_name(tag)?, &tag.color, id],
+            )?;
+            if changed == 0 {
+                bail!("标签不存在: {}", id);
+            }
+        }
+        self.get_tag(id)
+    }
+
+    fn delete_tag(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.write();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM task_tags WHERE tag_id = ?1", [id])?;
+        tx.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+        tx.commit()?;
+        logging!(info, Type::Database, "删除标签成功: {}", id);
+        Ok(())
+    }
+
+    fn get_tag(&self, id: &str) -> Result<Tag> {
+        let conn = self.conn.read();
+        let tag = conn.query_row(
+            "SELECT id, name, color, created_at FROM tags WHERE id = ?1",
+            [id],
+            Self::build_tag_from_row,
+        )?;
+        Ok(tag)
+    }
+
+    fn get_all_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare("SELECT id, name, color, created_at FROM tags ORDER BY name")?;
+        let tags = stmt
+            .query_map([], Self::build_tag_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(tags)
+    }
+
+    fn get_task_tags(&self, task_id: &str) -> Result<Vec<Tag>> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, t.created_at FROM tags t
+            JOIN task_tags tt ON tt.tag_id = t.id
+            WHERE tt.task_id = ?1
+            ORDER BY t.name",
+        )?;
+        let tags = stmt
+            .query_map([task_id], Self::build_tag_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(tags)
+    }
+
+    fn set_task_tags(&self, task_id: &str, tag_ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.write();
+        let tx = conn.transaction()?;
+        replace_task_tags(&tx, task_id, tag_ids)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_task_ids_by_tags(&self, filter: &TagFilter) -> Result<HashSet<String>> {
+        let tag_ids: Vec<&String> = filter.tag_ids.iter().collect::<HashSet<_>>().into_iter().collect();
+        if tag_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let placeholders = vec!["?"; tag_ids.len()].join(", ");
+        let having = if filter.match_all {
+            format!("HAVING COUNT(DISTINCT tag_id) = {}", tag_ids.len())
+        } else {
+            String::new()
+        };
+        let sql = format!(
+            "SELECT task_id FROM task_tags WHERE tag_id IN ({}) GROUP BY task_id {}",
+            placeholders, having
+        );
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(&sql)?;
+        let ids = stmt
+            .query_map(params_from_iter(tag_ids), |row| row.get(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+        Ok(ids)
+    }
+}
@@ -3,17 +3,23 @@ use rusqlite::params;
 
 use crate::{
     schema::{TaskData, TaskRecord},
-    store::{db::Database, module::{TaskManager, PeriodicTaskManager}},
+    store::{
+        db::Database,
+        implementation::{dependency_manager::replace_task_blockers, tag_manager::replace_task_tags},
+        module::{PeriodicTaskManager, TaskManager},
+    },
     utils::logging::Type,
     logging
 };
 
 impl TaskManager for Database {
     fn create_task(&self, task: &TaskData) -> Result<TaskRecord> {
-        let conn = self.conn.write();
+        let mut conn = self.conn.write();
         let actions = serde_json::to_string(&task.actions)?;
         let record = TaskRecord::from(task.clone());
-        let mut stmt = conn.prepare(
+        // 任务、标签与前置任务在同一事务内写入，任一校验失败都不会留下只创建了一半的任务
+        let tx = conn.transaction()?;
+        let mut stmt = tx.prepare(
             "
         INSERT INTO tasks (id, value, auto, parent_id, periodic, name, actions, created_at, due_to, reminder) 
         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
@@ -46,15 +52,24 @@ impl TaskManager for Database {
                 return Err(anyhow::anyhow!("创建任务失败"));
             }
         };
+        drop(stmt);
+        if let Some(tags) = &task.tags {
+            replace_task_tags(&tx, &record.id, tags)?;
+        }
+        if let Some(blockers) = &task.blocked_by {
+            replace_task_blockers(&tx, &record.id, blockers)?;
+        }
+        tx.commit()?;
 
         Ok(record)
     }
 
     fn update_task(&self, id: &str, task: &TaskData) -> Result<TaskRecord> {
-        let conn = self.conn.write();
+        let mut conn = self.conn.write();
         let actions = serde_json::to_string(&task.actions)?;
         let record = TaskRecord::from(task.clone());
-        conn.execute(
+        let tx = conn.transaction()?;
+        tx.execute(
             "UPDATE tasks 
             SET name = ?1, value = ?2, actions = ?3, due_to = ?4, reminder = ?5, completed = ?6, auto = ?7, parent_id = ?8, periodic = ?9
             WHERE id = ?10",
@@ -70,6 +85,13 @@ impl TaskManager for Database {
                 &record.periodic,
                 id],
         )?;
+        if let Some(tags) = &task.tags {
+            replace_task_tags(&tx, id, tags)?;
+        }
+        if let Some(blockers) = &task.blocked_by {
+            replace_task_blockers(&tx, id, blockers)?;
+        }
+        tx.commit()?;
         Ok(record)
     }
 
@@ -90,6 +112,21 @@ impl TaskManager for Database {
         Ok(true)
     }
 
+    fn update_task_reminder(&self, id: &str, reminder: Option<i64>) -> Result<()> {
+        let conn = self.conn.write();
+        let rows_affected = conn.execute(
+            "UPDATE tasks 
+            SET reminder = ?1
+            WHERE id = ?2",
+            params![reminder, id],
+        )?;
+        if rows_affected == 0 {
+            return Err(anyhow::anyhow!("任务不存在: {}", id));
+        }
+        logging!(info, Type::Database, true, "任务 {} 提醒时间已更新", id);
+        Ok(())
+    }
+
     fn delete_task(&self, id: &str) -> Result<()> {
         // 首先检查任务是否存在periodic字段
         let task = self.get_task(id)?;
@@ -118,6 +155,11 @@ impl TaskManager for Database {
         // 删除任务本身
         let conn = self.conn.write();
         conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+        conn.execute("DELETE FROM task_tags WHERE task_id = ?1", [id])?;
+        conn.execute(
+            "DELETE FROM task_dependencies WHERE task_id = ?1 OR blocker_id = ?1",
+            [id],
+        )?;
         
         logging!(info, Type::Database, "成功删除任务: id={}", id);
         Ok(())
@@ -241,6 +283,29 @@ impl TaskManager for Database {
         Ok(result)
     }
 
+    fn get_uncompleted_tasks_by_reminder_range(
+        &self,
+        start_date: i64,
+        end_date: i64,
+    ) -> Result<Vec<TaskRecord>> {
+        let conn = self.conn.read();
+        let mut stmt = conn.prepare(
+            "SELECT id, completed, parent_id, name, auto, actions, created_at, due_to, reminder, value, periodic 
+            FROM tasks 
+            WHERE (reminder BETWEEN ?1 AND ?2) AND completed = 0
+            ORDER BY reminder DESC",
+        )?;
+        let tasks = stmt.query_map([start_date, end_date], |row| {
+            Self::build_task_record_from_row(row)
+        })?;
+
+        let mut result = Vec::new();
+        for task in tasks {
+            result.push(task?);
+        }
+        Ok(result)
+    }
+
     fn get_all_tasks(&self) -> Result<Vec<TaskRecord>> {
         let conn = self.conn.read();
         let mut stmt = conn.prepare(
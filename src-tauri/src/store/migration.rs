@@ -0,0 +1,555 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use rusqlite::{Connection, Transaction};
+use std::{fs, path::{Path, PathBuf}};
+
+use super::search;
+use crate::{
+    logging,
+    schema::{encode_args, split_legacy_args},
+    utils::logging::Type,
+};
+
+/// 单个升级步骤，版本号记录在 `PRAGMA user_version` 中
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// 按版本号递增排列，只能追加，不能修改已发布的步骤
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "初始表结构",
+        up: create_base_tables,
+    },
+    Migration {
+        version: 2,
+        description: "任务提醒时间索引",
+        up: create_reminder_index,
+    },
+    Migration {
+        version: 3,
+        description: "动作执行记录表",
+        up: create_action_runs,
+    },
+    Migration {
+        version: 4,
+        description: "周期任务自定义规则",
+        up: add_periodic_rule,
+    },
+    Migration {
+        version: 5,
+        description: "任务标签",
+        up: create_tags,
+    },
+    Migration {
+        version: 6,
+        description: "全文检索索引",
+        up: create_search_index,
+    },
+    Migration {
+        version: 7,
+        description: "任务依赖",
+        up: create_task_dependencies,
+    },
+    Migration {
+        version: 8,
+        description: "动作参数改为 JSON 数组",
+        up: encode_action_args,
+    },
+    Migration {
+        version: 9,
+        description: "命令动作的工作目录、环境变量与输入输出",
+        up: add_command_options,
+    },
+    Migration {
+        version: 10,
+        description: "命令动作的执行策略",
+        up: add_action_trust,
+    },
+    Migration {
+        version: 11,
+        description: "动作的重试策略",
+        up: add_action_retry_policy,
+    },
+    Migration {
+        version: 12,
+        description: "检索索引触发器不再依赖自定义函数",
+        up: queue_search_index_updates,
+    },
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    let version = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    Ok(version)
+}
+
+/// 将数据库升级到最新版本，升级前备份到 `backup_dir`
+pub fn run_migrations(conn: &mut Connection, backup_dir: &Path) -> Result<u32> {
+    // v6 建立检索索引时使用自定义函数切分文本
+    search::register_functions(conn)?;
+    migrate_with(conn, backup_dir, MIGRATIONS)
+}
+
+fn migrate_with(conn: &mut Connection, backup_dir: &Path, migrations: &[Migration]) -> Result<u32> {
+    let mut version = current_version(conn)?;
+    let pending: Vec<&Migration> = migrations.iter().filter(|m| m.version > version).collect();
+    if pending.is_empty() {
+        return Ok(version);
+    }
+
+    let backup = if has_user_tables(conn)? {
+        Some(backup_database(conn, backup_dir, version)?)
+    } else {
+        None
+    };
+
+    for migration in pending {
+        logging!(
+            info,
+            Type::Database,
+            true,
+            "执行数据库迁移 v{}: {}",
+            migration.version,
+            migration.description
+        );
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)
+            .and_then(|_| tx.pragma_update(None, "user_version", migration.version))
+            .and_then(|_| tx.commit())
+            .with_context(|| {
+                let backup = backup
+                    .as_ref()
+                    .map_or("无".to_string(), |path| path.display().to_string());
+                format!(
+                    "数据库迁移 v{} ({}) 失败，已回滚到 v{}，备份: {}",
+                    migration.version, migration.description, version, backup
+                )
+            })?;
+        version = migration.version;
+    }
+    logging!(info, Type::Database, true, "数据库已升级到 v{}", version);
+    Ok(version)
+}
+
+fn has_user_tables(conn: &Connection) -> Result<bool> {
+    let exists = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'")?
+        .exists([])?;
+    Ok(exists)
+}
+
+/// 使用 `VACUUM INTO` 生成一致的快照，文件名包含原版本号与时间
+fn backup_database(conn: &Connection, backup_dir: &Path, version: u32) -> Result<PathBuf> {
+    fs::create_dir_all(backup_dir)?;
+    let path = backup_dir.join(format!(
+        "ducker-v{}-{}.db",
+        version,
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .with_context(|| format!("备份数据库失败: {}", path.display()))?;
+    logging!(info, Type::Database, true, "迁移前已备份数据库: {}", path.display());
+    Ok(path)
+}
+
+fn has_column(tx: &Transaction, table: &str, column: &str) -> rusqlite::Result<bool> {
+    tx.prepare("SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2")?
+        .exists([table, column])
+}
+
+fn create_base_tables(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tasks (
+            id TEXT PRIMARY KEY,
+            value REAL DEFAULT 0,
+            completed INTEGER DEFAULT 0,
+            auto INTEGER DEFAULT 0,
+            parent_id TEXT,
+            periodic TEXT,
+            name TEXT NOT NULL,
+            actions TEXT,
+            created_at INTEGER,
+            due_to INTEGER,
+            reminder INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS actions (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            desc TEXT,
+            command TEXT NOT NULL,
+            args TEXT,
+            type INTEGER NOT NULL,
+            wait INTEGER NOT NULL DEFAULT 0,
+            retry INTEGER  DEFAULT 0,
+            timeout INTEGER,
+            count INTEGER DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS periodic_tasks (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            interval INTEGER NOT NULL,
+            last_period INTEGER,
+            next_period INTEGER
+        );
+        -- 创建索引以提升查询性能
+        CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);
+        CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id);
+        CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks(created_at);
+        CREATE INDEX IF NOT EXISTS idx_actions_name ON actions(name);
+        CREATE INDEX IF NOT EXISTS idx_periodic_tasks_last_period ON periodic_tasks(last_period);",
+    )
+}
+
+fn create_reminder_index(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch("CREATE INDEX IF NOT EXISTS idx_tasks_reminder ON tasks(reminder);")
+}
+
+fn create_action_runs(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS action_runs (
+            id TEXT PRIMARY KEY,
+            action_id TEXT,
+            action_name TEXT NOT NULL,
+            trigger INTEGER NOT NULL,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            attempts INTEGER NOT NULL DEFAULT 1,
+            success INTEGER NOT NULL DEFAULT 0,
+            exit_code INTEGER,
+            stdout TEXT,
+            stderr TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_action_runs_action_id ON action_runs(action_id);
+        CREATE INDEX IF NOT EXISTS idx_action_runs_started_at ON action_runs(started_at);",
+    )
+}
+
+fn add_periodic_rule(tx: &Transaction) -> rusqlite::Result<()> {
+    // 未记录版本号的旧库可能已经由早期代码补过该列
+    if !has_column(tx, "periodic_tasks", "rule")? {
+        tx.execute("ALTER TABLE periodic_tasks ADD COLUMN rule TEXT", [])?;
+    }
+    Ok(())
+}
+
+fn create_tags(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS task_tags (
+            task_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY (task_id, tag_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_task_tags_tag_id ON task_tags(tag_id);",
+    )
+}
+
+/// 任务、动作与周期规则的名称、描述写入 FTS5 索引，由触发器保持同步
+fn create_search_index(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            kind UNINDEXED,
+            item_id UNINDEXED,
+            title,
+            body,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+        INSERT INTO search_index (kind, item_id, title, body)
+            SELECT 'task', id, search_segment(name), '' FROM tasks;
+        INSERT INTO search_index (kind, item_id, title, body)
+            SELECT 'action', id, search_segment(name), search_segment(desc) FROM actions;
+        INSERT INTO search_index (kind, item_id, title, body)
+            SELECT 'periodic', id, search_segment(name), '' FROM periodic_tasks;
+
+        CREATE TRIGGER IF NOT EXISTS tasks_search_insert AFTER INSERT ON tasks BEGIN
+            INSERT INTO search_index (kind, item_id, title, body)
+                VALUES ('task', new.id, search_segment(new.name), '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS tasks_search_update AFTER UPDATE OF id, name ON tasks BEGIN
+            DELETE FROM search_index WHERE kind = 'task' AND item_id = old.id;
+            INSERT INTO search_index (kind, item_id, title, body)
+                VALUES ('task', new.id, search_segment(new.name), '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS tasks_search_delete AFTER DELETE ON tasks BEGIN
+            DELETE FROM search_index WHERE kind = 'task' AND item_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS actions_search_insert AFTER INSERT ON actions BEGIN
+            INSERT INTO search_index (kind, item_id, title, body)
+                VALUES ('action', new.id, search_segment(new.name), search_segment(new.desc));
+        END;
+        CREATE TRIGGER IF NOT EXISTS actions_search_update AFTER UPDATE OF id, name, desc ON actions BEGIN
+            DELETE FROM search_index WHERE kind = 'action' AND item_id = old.id;
+            INSERT INTO search_index (kind, item_id, title, body)
+                VALUES ('action', new.id, search_segment(new.name), search_segment(new.desc));
+        END;
+        CREATE TRIGGER IF NOT EXISTS actions_search_delete AFTER DELETE ON actions BEGIN
+            DELETE FROM search_index WHERE kind = 'action' AND item_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS periodic_tasks_search_insert AFTER INSERT ON periodic_tasks BEGIN
+            INSERT INTO search_index (kind, item_id, title, body)
+                VALUES ('periodic', new.id, search_segment(new.name), '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS periodic_tasks_search_update AFTER UPDATE OF id, name ON periodic_tasks BEGIN
+            DELETE FROM search_index WHERE kind = 'periodic' AND item_id = old.id;
+            INSERT INTO search_index (kind, item_id, title, body)
+                VALUES ('periodic', new.id, search_segment(new.name), '');
+        END;
+        CREATE TRIGGER IF NOT EXISTS periodic_tasks_search_delete AFTER DELETE ON periodic_tasks BEGIN
+            DELETE FROM search_index WHERE kind = 'periodic' AND item_id = old.id;
+        END;",
+    )
+}
+
+/// `task_id` 在 `blocker_id` 完成之前处于阻塞状态
+fn create_task_dependencies(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS task_dependencies (
+            task_id TEXT NOT NULL,
+            blocker_id TEXT NOT NULL,
+            PRIMARY KEY (task_id, blocker_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_task_dependencies_blocker_id ON task_dependencies(blocker_id);",
+    )
+}
+
+/// 旧版 args 以逗号拼接，按原有的读取方式拆分后写回 JSON 数组
+fn encode_action_args(tx: &Transaction) -> rusqlite::Result<()> {
+    let rows = {
+        let mut stmt = tx.prepare("SELECT id, IFNULL(args, '') FROM actions")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows
+    };
+    for (id, args) in rows {
+        tx.execute(
+            "UPDATE actions SET args = ?1 WHERE id = ?2",
+            (encode_args(&split_legacy_args(&args)), &id),
+        )?;
+    }
+    Ok(())
+}
+
+/// env 以 JSON 对象保存
+fn add_command_options(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE actions ADD COLUMN cwd TEXT;
+        ALTER TABLE actions ADD COLUMN env TEXT;
+        ALTER TABLE actions ADD COLUMN stdin TEXT;
+        ALTER TABLE actions ADD COLUMN output_file TEXT;",
+    )
+}
+
+/// 取值为 always、confirm、deny，NULL 视为 always
+fn add_action_trust(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute("ALTER TABLE actions ADD COLUMN trust TEXT", [])?;
+    Ok(())
+}
+
+/// 以 JSON 保存，NULL 表示使用默认策略
+fn add_action_retry_policy(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute("ALTER TABLE actions ADD COLUMN retry_policy TEXT", [])?;
+    Ok(())
+}
+
+/// v6 的触发器调用仅在应用连接中注册的 `search_segment`，其他工具写入这些表时会报错。
+/// 改为只记录待更新的条目，由应用在检索前切分文本并写入索引
+fn queue_search_index_updates(tx: &Transaction) -> rusqlite::Result<()> {
+    let mut sql = String::from(
+        "CREATE TABLE IF NOT EXISTS search_pending (
+            kind TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            PRIMARY KEY (kind, item_id)
+        );",
+    );
+    for (table, kind, columns) in [
+        ("tasks", "task", "id, name"),
+        ("actions", "action", "id, name, desc"),
+        ("periodic_tasks", "periodic", "id, name"),
+    ] {
+        sql.push_str(&format!(
+            "DROP TRIGGER IF EXISTS {table}_search_insert;
+            DROP TRIGGER IF EXISTS {table}_search_update;
+            DROP TRIGGER IF EXISTS {table}_search_delete;
+            CREATE TRIGGER {table}_search_insert AFTER INSERT ON {table} BEGIN
+                INSERT OR IGNORE INTO search_pending (kind, item_id) VALUES ('{kind}', new.id);
+            END;
+            CREATE TRIGGER {table}_search_update AFTER UPDATE OF {columns} ON {table} BEGIN
+                INSERT OR IGNORE INTO search_pending (kind, item_id) VALUES ('{kind}', old.id);
+                INSERT OR IGNORE INTO search_pending (kind, item_id) VALUES ('{kind}', new.id);
+            END;
+            CREATE TRIGGER {table}_search_delete AFTER DELETE ON {table} BEGIN
+                INSERT OR IGNORE INTO search_pending (kind, item_id) VALUES ('{kind}', old.id);
+            END;"
+        ));
+    }
+    tx.execute_batch(&sql)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "ducker-migration-{}-{}",
+                name,
+                Local::now().timestamp_nanos_opt().unwrap_or_default()
+            ));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// 未引入版本号之前的数据库结构：没有 user_version、action_runs 与 rule 列
+    fn open_legacy_db(dir: &Path) -> Connection {
+        let conn = Connection::open(dir.join("ducker.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tasks (
+                id TEXT PRIMARY KEY, value REAL DEFAULT 0, completed INTEGER DEFAULT 0,
+                auto INTEGER DEFAULT 0, parent_id TEXT, periodic TEXT, name TEXT NOT NULL,
+                actions TEXT, created_at INTEGER, due_to INTEGER, reminder INTEGER
+            );
+            CREATE TABLE actions (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL UNIQUE, desc TEXT, command TEXT NOT NULL,
+                args TEXT, type INTEGER NOT NULL, wait INTEGER NOT NULL DEFAULT 0,
+                retry INTEGER DEFAULT 0, timeout INTEGER, count INTEGER DEFAULT 0
+            );
+            CREATE TABLE periodic_tasks (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL, interval INTEGER NOT NULL,
+                last_period INTEGER, next_period INTEGER
+            );
+            INSERT INTO tasks (id, name, actions, created_at, due_to) VALUES ('t1', '旧任务', '[]', 1, 2);
+            INSERT INTO actions (id, name, command, args, type) VALUES ('a1', '旧动作', 'echo', 'a, b', 3);
+            INSERT INTO actions (id, name, command, args, type) VALUES ('a2', '旧通知', '标题', NULL, 4);
+            INSERT INTO periodic_tasks (id, name, interval, last_period, next_period) VALUES ('t1', '旧任务', 1, 2, 86402);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn legacy_database_is_backed_up_and_upgraded() {
+        let dir = TempDir::new("legacy");
+        let mut conn = open_legacy_db(&dir.0);
+        let backup_dir = dir.0.join("backups");
+
+        let version = run_migrations(&mut conn, &backup_dir).unwrap();
+        assert_eq!(version, latest_version());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        let (name, rule): (String, Option<String>) = conn
+            .query_row("SELECT name, rule FROM periodic_tasks WHERE id = 't1'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(name, "旧任务");
+        assert_eq!(rule, None);
+        let runs: i64 = conn
+            .query_row("SELECT COUNT(*) FROM action_runs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(runs, 0);
+        // 已有数据会补写入检索索引
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM search_index WHERE search_index MATCH ?1",
+                [search::build_match_query("旧任").unwrap()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 2);
+        // 逗号拼接的参数改为 JSON 数组
+        let args: Vec<String> = conn
+            .prepare("SELECT args FROM actions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(args, vec![r#"["a","b"]"#, "[]"]);
+
+        let backups: Vec<_> = fs::read_dir(&backup_dir).unwrap().collect();
+        assert_eq!(backups.len(), 1);
+        let backup = Connection::open(backups[0].as_ref().unwrap().path()).unwrap();
+        assert_eq!(current_version(&backup).unwrap(), 0);
+        let tasks: i64 = backup
+            .query_row("SELECT COUNT(*) FROM tasks", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tasks, 1);
+
+        // 已是最新版本时不再备份
+        assert_eq!(run_migrations(&mut conn, &backup_dir).unwrap(), latest_version());
+        assert_eq!(fs::read_dir(&backup_dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn legacy_database_with_rule_column_is_upgraded() {
+        let dir = TempDir::new("partial");
+        let mut conn = open_legacy_db(&dir.0);
+        conn.execute("ALTER TABLE periodic_tasks ADD COLUMN rule TEXT", [])
+            .unwrap();
+
+        let version = run_migrations(&mut conn, &dir.0.join("backups")).unwrap();
+        assert_eq!(version, latest_version());
+    }
+
+    #[test]
+    fn fresh_database_skips_backup() {
+        let dir = TempDir::new("fresh");
+        let mut conn = Connection::open(dir.0.join("ducker.db")).unwrap();
+        let backup_dir = dir.0.join("backups");
+
+        run_migrations(&mut conn, &backup_dir).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(!backup_dir.exists());
+    }
+
+    #[test]
+    fn failed_migration_rolls_back() {
+        fn broken(tx: &Transaction) -> rusqlite::Result<()> {
+            tx.execute("ALTER TABLE tasks ADD COLUMN note TEXT", [])?;
+            tx.execute("ALTER TABLE missing ADD COLUMN note TEXT", [])?;
+            Ok(())
+        }
+        let migrations = [
+            Migration { version: 1, description: "初始表结构", up: create_base_tables },
+            Migration { version: 2, description: "损坏的迁移", up: broken },
+        ];
+        let dir = TempDir::new("broken");
+        let mut conn = open_legacy_db(&dir.0);
+
+        let err = migrate_with(&mut conn, &dir.0.join("backups"), &migrations).unwrap_err();
+        assert!(format!("{err}").contains("v2"));
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        let has_note = conn
+            .prepare("SELECT 1 FROM pragma_table_info('tasks') WHERE name = 'note'")
+            .unwrap()
+            .exists([])
+            .unwrap();
+        assert!(!has_note);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod db;
+pub mod migration;
 pub mod module;
+pub mod search;
 pub mod implementation;
\ No newline at end of file
@@ -1,4 +1,10 @@
-use crate::schema::{Action, ActionRecord, PeriodicTaskData, PeriodicTaskRecord, TaskData, TaskRecord};
+use std::collections::HashSet;
+
+use crate::schema::{
+    Action, ActionRecord, ActionRunFilter, ActionRunRecord, Bundle, ExportOptions, ImportOptions,
+    ImportReport, PeriodicTaskData, PeriodicTaskRecord, SearchHit, Tag, TagData, TagFilter,
+    TaskData, TaskRecord,
+};
 use anyhow::Result;
 pub trait ActionManager {
     fn create_action(&self, action: &Action) -> Result<ActionRecord>;
@@ -14,6 +20,7 @@ pub trait TaskManager {
     fn create_task(&self, task: &TaskData) -> Result<TaskRecord>;
     fn update_task(&self, id: &str, task: &TaskData) -> Result<TaskRecord>;
     fn update_task_status(&self, id: &str, completed: bool) -> Result<bool>;
+    fn update_task_reminder(&self, id: &str, reminder: Option<i64>) -> Result<()>;
     fn delete_task(&self, id: &str) -> Result<()>;
     fn get_task(&self, id: &str) -> Result<TaskRecord>;
     fn get_tasks(&self, ids: &[String]) -> Result<Vec<TaskRecord>>;
@@ -25,6 +32,11 @@ pub trait TaskManager {
         start_date: i64,
         end_date: i64,
     ) -> Result<Vec<TaskRecord>>;
+    fn get_uncompleted_tasks_by_reminder_range(
+        &self,
+        start_date: i64,
+        end_date: i64,
+    ) -> Result<Vec<TaskRecord>>;
     fn get_all_tasks(&self) -> Result<Vec<TaskRecord>>;
 }
 
@@ -38,4 +50,49 @@ pub trait PeriodicTaskManager {
     fn get_startup_periodic_tasks(&self) -> Result<Vec<PeriodicTaskRecord>>;
     fn get_periodic_task(&self, id: &str) -> Result<PeriodicTaskRecord>;
     fn create_periodic_rule_only(&self, task: &PeriodicTaskData) -> Result<PeriodicTaskRecord>;
-}
\ No newline at end of file
+}
+
+pub trait ActionRunManager {
+    fn create_action_run(&self, run: &ActionRunRecord) -> Result<()>;
+    fn get_action_run(&self, id: &str) -> Result<ActionRunRecord>;
+    fn get_action_runs(&self, filter: &ActionRunFilter) -> Result<Vec<ActionRunRecord>>;
+    fn delete_action_runs_before(&self, timestamp: i64) -> Result<usize>;
+}
+
+pub trait BundleManager {
+    fn export_bundle(&self, options: &ExportOptions) -> Result<Bundle>;
+    fn import_bundle(&self, bundle: &Bundle, options: &ImportOptions) -> Result<ImportReport>;
+}
+
+pub trait TagManager {
+    fn create_tag(&self, tag: &TagData) -> Result<Tag>;
+    fn update_tag(&self, id: &str, tag: &TagData) -> Result<Tag>;
+    fn delete_tag(&self, id: &str) -> Result<()>;
+    fn get_tag(&self, id: &str) -> Result<Tag>;
+    fn get_all_tags(&self) -> Result<Vec<Tag>>;
+    fn get_task_tags(&self, task_id: &str) -> Result<Vec<Tag>>;
+    /// 用 `tag_ids` 整体替换任务的标签
+    fn set_task_tags(&self, task_id: &str, tag_ids: &[String]) -> Result<()>;
+    /// 返回满足筛选条件的任务 id
+    fn get_task_ids_by_tags(&self, filter: &TagFilter) -> Result<HashSet<String>>;
+}
+
+pub trait DependencyManager {
+    /// 直接阻塞该任务的任务 id
+    fn get_task_blockers(&self, task_id: &str) -> Result<Vec<String>>;
+    /// 直接被该任务阻塞的任务 id
+    fn get_task_dependents(&self, blocker_id: &str) -> Result<Vec<String>>;
+    /// 用 `blocker_ids` 整体替换任务的前置任务，前置任务不存在或形成循环时报错
+    fn set_task_blockers(&self, task_id: &str, blocker_ids: &[String]) -> Result<()>;
+    /// 是否仍有未完成的前置任务
+    fn is_task_blocked(&self, task_id: &str) -> Result<bool>;
+    /// 所有仍有未完成前置任务的任务 id
+    fn get_blocked_task_ids(&self) -> Result<HashSet<String>>;
+    /// `completed_ids` 完成后不再被阻塞的未完成后续任务 id
+    fn get_unblocked_dependents(&self, completed_ids: &[String]) -> Result<Vec<String>>;
+}
+
+pub trait SearchManager {
+    /// 按名称与描述全文检索，结果按相关度排序，包含已完成的任务
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>>;
+}
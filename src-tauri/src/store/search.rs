@@ -0,0 +1,90 @@
+use rusqlite::{functions::FunctionFlags, Connection};
+
+/// 写入索引时插在中日韩字符两侧的分隔符，unicode61 分词器会把它当作词边界，
+/// 展示前再去掉即可还原原文
+pub const SEGMENT_SEPARATOR: char = '\u{2063}';
+pub const HIGHLIGHT_OPEN: &str = "<mark>";
+pub const HIGHLIGHT_CLOSE: &str = "</mark>";
+
+/// 中日韩文字之间没有空格，需要逐字切分才能按词检索
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF66}'..='\u{FF9F}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// 将中日韩字符逐个切分，其余文本保持原样
+pub fn segment(text: &str) -> String {
+    let mut segmented = String::with_capacity(text.len() * 2);
+    for c in text.chars() {
+        if c == SEGMENT_SEPARATOR {
+            continue;
+        }
+        if is_cjk(c) {
+            if !segmented.ends_with(SEGMENT_SEPARATOR) {
+                segmented.push(SEGMENT_SEPARATOR);
+            }
+            segmented.push(c);
+            segmented.push(SEGMENT_SEPARATOR);
+        } else {
+            segmented.push(c);
+        }
+    }
+    segmented
+}
+
+pub fn unsegment(text: &str) -> String {
+    text.chars().filter(|c| *c != SEGMENT_SEPARATOR).collect()
+}
+
+/// 将用户输入转换为 FTS5 查询：按空白拆分为多个词，每个词作为短语并对最后一个词元做前缀匹配，
+/// 多个词之间要求同时命中。没有可检索内容时返回 None
+pub fn build_match_query(input: &str) -> Option<String> {
+    let phrases: Vec<String> = input
+        .split_whitespace()
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{}\"*", segment(term).replace('"', "\"\"")))
+        .collect();
+    (!phrases.is_empty()).then(|| phrases.join(" "))
+}
+
+/// 注册 v6 迁移建立索引时使用的 `search_segment` 函数
+pub fn register_functions(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "search_segment",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text: Option<String> = ctx.get(0)?;
+            Ok(segment(&text.unwrap_or_default()))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cjk_text_is_segmented_and_restored() {
+        let segmented = segment("写周报 weekly-report");
+        assert_eq!(
+            segmented.split(SEGMENT_SEPARATOR).filter(|s| !s.is_empty()).collect::<Vec<_>>(),
+            vec!["写", "周", "报", " weekly-report"]
+        );
+        assert_eq!(unsegment(&segmented), "写周报 weekly-report");
+    }
+
+    #[test]
+    fn match_query_uses_prefix_phrases() {
+        let sep = SEGMENT_SEPARATOR;
+        assert_eq!(
+            build_match_query(" 周报  back\"up ").unwrap(),
+            format!("\"{sep}周{sep}报{sep}\"* \"back\"\"up\"*")
+        );
+        assert_eq!(build_match_query("  - ! "), None);
+    }
+}
@@ -4,8 +4,10 @@ pub mod help;
 pub mod init;
 pub mod logging;
 pub mod macros;
+pub mod recurrence;
 pub mod resolve;
 pub mod singleton;
+pub mod template;
 pub mod window_manager;
 // 后端只有托盘要更新的话那我就不打算在后端支持了
 // pub mod i18n;
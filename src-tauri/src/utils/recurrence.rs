@@ -0,0 +1,640 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone, Timelike};
+
+use super::date::{calculate_next_period, calculate_next_period_from_now};
+use crate::schema::{Period, PeriodicTaskRecord, CUSTOM_PERIOD_INTERVAL};
+
+/// 1-12 月全部选中时的掩码
+const ALL_MONTHS: u64 = 0x1ffe;
+/// cron 向后查找的最大天数，超过则认为规则不会再触发
+const CRON_SEARCH_DAYS: i64 = 366 * 5;
+/// `@every` 允许的最长间隔，约 100 年
+const MAX_EVERY_SECONDS: i64 = 100 * 366 * 24 * 3600;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+/// RFC 5545 中的星期缩写，下标与 cron 一致（0 为周日）
+const RRULE_WEEKDAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+/// 周期任务的重复规则
+///
+/// - `Fixed`：旧的固定周期（1/7/30 天及启动时触发），沿用 `calculate_next_period`
+/// - `Every`：`@every 2h` 形式的自定义间隔
+/// - `Cron`：五段式 cron 表达式，支持 `L`（月末）与 `5#3`（第三个周五）
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recurrence {
+    Fixed(u8),
+    Every(i64),
+    Cron(CronSchedule),
+}
+
+impl Recurrence {
+    pub fn new(interval: u8, rule: Option<&str>) -> Result<Self> {
+        if interval != CUSTOM_PERIOD_INTERVAL {
+            Period::try_from(interval).map_err(|e| anyhow!(e))?;
+            return Ok(Recurrence::Fixed(interval));
+        }
+        let rule = rule
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .ok_or_else(|| anyhow!("自定义周期缺少重复规则"))?;
+        Self::parse(rule)
+    }
+
+    pub fn from_record(record: &PeriodicTaskRecord) -> Result<Self> {
+        Self::new(record.interval, record.rule.as_deref())
+    }
+
+    /// 解析自定义规则：`@every <n><m|h|d|w>`、`@hourly` 等宏或五段式 cron 表达式
+    pub fn parse(rule: &str) -> Result<Self> {
+        let rule = rule.trim();
+        if let Some(every) = rule.strip_prefix("@every") {
+            return Ok(Recurrence::Every(parse_every(every.trim())?));
+        }
+        let expression = match rule {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            _ => rule,
+        };
+        let schedule = CronSchedule::parse(expression)?;
+        if schedule.next_after(Local::now().timestamp()).is_none() {
+            bail!("cron 表达式在未来五年内没有匹配的时间: {}", rule);
+        }
+        Ok(Recurrence::Cron(schedule))
+    }
+
+    /// 计算严格晚于 `timestamp` 的下一次执行时间
+    pub fn next_after(&self, timestamp: i64) -> i64 {
+        match self {
+            Recurrence::Fixed(interval) => calculate_next_period(timestamp, *interval),
+            Recurrence::Every(seconds) => timestamp + seconds,
+            Recurrence::Cron(schedule) => schedule
+                .next_after(timestamp)
+                .unwrap_or(timestamp + CRON_SEARCH_DAYS * 24 * 3600),
+        }
+    }
+
+    /// 从 `timestamp` 起向后推进，返回第一个晚于当前时间的执行时间
+    pub fn next_after_now(&self, timestamp: i64) -> i64 {
+        let now = Local::now().timestamp();
+        match self {
+            Recurrence::Fixed(interval) => calculate_next_period_from_now(timestamp, *interval),
+            // 只有启动时触发的 Fixed 周期没有执行时间
+            _ => self.first_at_or_after(timestamp, now + 1).unwrap_or(timestamp),
+        }
+    }
+
+    /// 转换为 RFC 5545 的 RRULE，无法等价表达时返回 None
+    pub fn to_rrule(&self) -> Option<String> {
+        match self {
+            Recurrence::Fixed(1) => Some("FREQ=DAILY".to_string()),
+            Recurrence::Fixed(7) => Some("FREQ=WEEKLY".to_string()),
+            Recurrence::Fixed(30) => Some("FREQ=MONTHLY".to_string()),
+            Recurrence::Fixed(_) => None,
+            Recurrence::Every(seconds) => {
+                let (freq, unit) = [("WEEKLY", 7 * 24 * 3600), ("DAILY", 24 * 3600), ("HOURLY", 3600)]
+                    .into_iter()
+                    .find(|(_, unit)| seconds % unit == 0)
+                    .unwrap_or(("MINUTELY", 60));
+                Some(format!("FREQ={};INTERVAL={}", freq, seconds / unit))
+            }
+            Recurrence::Cron(schedule) => schedule.to_rrule(),
+        }
+    }
+
+    /// 是否为启动时触发的周期，这类周期没有按时间推算的执行时间
+    pub fn is_startup(&self) -> bool {
+        matches!(self, Recurrence::Fixed(interval) if matches!(
+            Period::try_from(*interval),
+            Ok(Period::OnStart | Period::OnceStarted)
+        ))
+    }
+
+    /// 以 `anchor` 为锚点，返回不早于 `start` 的第一次执行时间，启动时触发的周期返回 None
+    pub fn first_at_or_after(&self, anchor: i64, start: i64) -> Option<i64> {
+        if self.is_startup() {
+            return None;
+        }
+        if anchor >= start {
+            return Some(anchor);
+        }
+        let next = match self {
+            Recurrence::Every(seconds) => {
+                let periods = (start - anchor + seconds - 1) / seconds;
+                anchor + periods * seconds
+            }
+            Recurrence::Cron(_) => self.next_after(start - 1),
+            Recurrence::Fixed(_) => {
+                let mut current = anchor;
+                while current < start {
+                    current = self.next_after(current);
+                }
+                current
+            }
+        };
+        Some(next)
+    }
+}
+
+/// 将 RFC 5545 的 RRULE 转换为周期设置 `(interval, rule)`
+///
+/// `start` 为 DTSTART，用于补全 RRULE 未指定的分、时、日等字段。
+/// COUNT/UNTIL、带间隔的 BY* 组合等无法用现有周期表达的规则会返回错误。
+pub fn period_from_rrule(rrule: &str, start: i64) -> Result<(u8, Option<String>)> {
+    let start = Local
+        .timestamp_opt(start, 0)
+        .single()
+        .ok_or_else(|| anyhow!("无效的开始时间: {}", start))?;
+    let mut freq = None;
+    let mut interval = 1;
+    let mut by = std::collections::HashMap::new();
+    for part in rrule.trim().trim_start_matches("RRULE:").split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => freq = Some(value.to_ascii_uppercase()),
+            "INTERVAL" => {
+                interval = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|value| *value > 0)
+                    .ok_or_else(|| anyhow!("INTERVAL 无效: {}", value))?
+            }
+            "WKST" => {}
+            "BYSECOND" if value == "0" => {}
+            key @ ("BYMONTH" | "BYMONTHDAY" | "BYDAY" | "BYHOUR" | "BYMINUTE") => {
+                by.insert(key.to_string(), value.to_ascii_uppercase());
+            }
+            key => bail!("不支持 RRULE 的 {} 属性", key),
+        }
+    }
+    let freq = freq.ok_or_else(|| anyhow!("RRULE 缺少 FREQ: {}", rrule))?;
+
+    if by.is_empty() {
+        match (freq.as_str(), interval) {
+            ("DAILY", 1) => return Ok((1, None)),
+            ("WEEKLY", 1) => return Ok((7, None)),
+            ("MONTHLY", 1) => return Ok((30, None)),
+            _ => {}
+        }
+    }
+    if interval > 1 {
+        if !by.is_empty() {
+            bail!("不支持同时带 INTERVAL 与 BY* 的 RRULE");
+        }
+        let unit = match freq.as_str() {
+            "MINUTELY" => "m",
+            "HOURLY" => "h",
+            "DAILY" => "d",
+            "WEEKLY" => "w",
+            _ => bail!("不支持 {} 频率的 INTERVAL", freq),
+        };
+        return Ok((CUSTOM_PERIOD_INTERVAL, Some(format!("@every {}{}", interval, unit))));
+    }
+
+    let list = |key: &str, min: i64, max: i64| -> Result<Option<String>> {
+        let Some(value) = by.get(key) else {
+            return Ok(None);
+        };
+        for item in value.split(',') {
+            match item.parse::<i64>() {
+                Ok(number) if (min..=max).contains(&number) => {}
+                _ => bail!("{} 的取值无效: {}", key, item),
+            }
+        }
+        Ok(Some(value.clone()))
+    };
+    let by_minute = list("BYMINUTE", 0, 59)?;
+    let by_hour = list("BYHOUR", 0, 23)?;
+    let by_month = list("BYMONTH", 1, 12)?;
+    let by_month_day = match by.get("BYMONTHDAY") {
+        Some(value) => {
+            let mut days = Vec::new();
+            for item in value.split(',') {
+                match item.parse::<i64>() {
+                    Ok(-1) => days.push("L".to_string()),
+                    Ok(day) if (1..=31).contains(&day) => days.push(day.to_string()),
+                    _ => bail!("BYMONTHDAY 的取值无法表示: {}", item),
+                }
+            }
+            Some(days.join(","))
+        }
+        None => None,
+    };
+    let by_day = match by.get("BYDAY") {
+        Some(value) => {
+            let mut days = Vec::new();
+            for item in value.split(',') {
+                let (nth, day) = item.split_at(item.len().saturating_sub(2));
+                let weekday = RRULE_WEEKDAYS
+                    .iter()
+                    .position(|name| *name == day)
+                    .ok_or_else(|| anyhow!("BYDAY 的取值无效: {}", item))?;
+                match nth.trim_start_matches('+') {
+                    "" => days.push(weekday.to_string()),
+                    nth if matches!(freq.as_str(), "MONTHLY" | "YEARLY") => match nth.parse::<u32>() {
+                        Ok(nth @ 1..=5) => days.push(format!("{}#{}", weekday, nth)),
+                        _ => bail!("BYDAY 的取值无法表示: {}", item),
+                    },
+                    _ => bail!("BYDAY 的取值无法表示: {}", item),
+                }
+            }
+            Some(days.join(","))
+        }
+        None => None,
+    };
+    if by_month_day.is_some() && by_day.is_some() {
+        bail!("不支持同时指定 BYMONTHDAY 与 BYDAY 的 RRULE");
+    }
+
+    let start_minute = start.minute().to_string();
+    let start_hour = start.hour().to_string();
+    let start_day = start.day().to_string();
+    let start_weekday = start.weekday().num_days_from_sunday().to_string();
+    let start_month = start.month().to_string();
+    let any = || "*".to_string();
+    let (minute, hour) = match freq.as_str() {
+        "MINUTELY" => (by_minute.unwrap_or_else(any), by_hour.unwrap_or_else(any)),
+        "HOURLY" => (by_minute.unwrap_or(start_minute), by_hour.unwrap_or_else(any)),
+        "DAILY" | "WEEKLY" | "MONTHLY" | "YEARLY" => {
+            (by_minute.unwrap_or(start_minute), by_hour.unwrap_or(start_hour))
+        }
+        _ => bail!("不支持的 FREQ: {}", freq),
+    };
+    let (day, weekday) = match (freq.as_str(), by_month_day, by_day) {
+        ("WEEKLY", Some(_), _) => bail!("不支持 WEEKLY 频率的 BYMONTHDAY"),
+        ("WEEKLY", None, None) => (any(), start_weekday),
+        ("MONTHLY" | "YEARLY", None, None) => (start_day, any()),
+        (_, day, weekday) => (day.unwrap_or_else(any), weekday.unwrap_or_else(any)),
+    };
+    let month = match (freq.as_str(), by_month) {
+        (_, Some(month)) => month,
+        ("YEARLY", None) => start_month,
+        _ => any(),
+    };
+
+    let rule = format!("{} {} {} {} {}", minute, hour, day, month, weekday);
+    Recurrence::parse(&rule)?;
+    Ok((CUSTOM_PERIOD_INTERVAL, Some(rule)))
+}
+
+fn parse_every(value: &str) -> Result<i64> {
+    let unit_index = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow!("@every 缺少时间单位: {}", value))?;
+    let (amount, unit) = value.split_at(unit_index);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow!("@every 间隔无效: {}", value))?;
+    let unit_seconds = match unit.trim() {
+        "m" | "min" => 60,
+        "h" => 3600,
+        "d" => 24 * 3600,
+        "w" => 7 * 24 * 3600,
+        _ => bail!("@every 时间单位无效: {}", unit),
+    };
+    if amount <= 0 {
+        bail!("@every 间隔必须大于 0: {}", value);
+    }
+    // 过大的间隔在推算执行时间时同样会溢出，一并拒绝
+    amount
+        .checked_mul(unit_seconds)
+        .filter(|seconds| *seconds <= MAX_EVERY_SECONDS)
+        .ok_or_else(|| anyhow!("@every 间隔无效: {}", value))
+}
+
+/// 五段式 cron 表达式：分 时 日 月 周
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    last_day: bool,
+    months: u64,
+    weekdays: u64,
+    nth_weekdays: Vec<(u32, u32)>,
+    day_restricted: bool,
+    weekday_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            bail!("cron 表达式需要 5 个字段: {}", expression);
+        }
+        let minutes = parse_field(fields[0], 0, 59, &[])?;
+        let hours = parse_field(fields[1], 0, 23, &[])?;
+        let months = parse_field(fields[3], 1, 12, &MONTH_NAMES)?;
+
+        let mut days = 0;
+        let mut last_day = false;
+        for item in fields[2].split(',') {
+            if item.eq_ignore_ascii_case("L") {
+                last_day = true;
+            } else {
+                days |= parse_field(item, 1, 31, &[])?;
+            }
+        }
+
+        let mut weekdays = 0;
+        let mut nth_weekdays = Vec::new();
+        for item in fields[4].split(',') {
+            if let Some((weekday, nth)) = item.split_once('#') {
+                let weekday = parse_value(weekday, 0, 7, &WEEKDAY_NAMES)? % 7;
+                let nth = parse_value(nth, 1, 5, &[])?;
+                nth_weekdays.push((weekday, nth));
+            } else {
+                let mask = parse_field(item, 0, 7, &WEEKDAY_NAMES)?;
+                // 7 与 0 都表示周日
+                weekdays |= (mask | (mask >> 7)) & 0x7f;
+            }
+        }
+
+        Ok(Self {
+            minutes,
+            hours,
+            days,
+            last_day,
+            months,
+            weekdays,
+            nth_weekdays,
+            day_restricted: fields[2] != "*",
+            weekday_restricted: fields[4] != "*",
+        })
+    }
+
+    /// 查找严格晚于 `timestamp` 的第一个匹配时间（按本地时区）
+    pub fn next_after(&self, timestamp: i64) -> Option<i64> {
+        let start = Local.timestamp_opt(timestamp, 0).single()?.naive_local();
+        let mut date = start.date();
+        for _ in 0..CRON_SEARCH_DAYS {
+            if self.matches_date(date) {
+                for hour in bits(self.hours) {
+                    for minute in bits(self.minutes) {
+                        let Some(candidate) = date.and_hms_opt(hour, minute, 0) else {
+                            continue;
+                        };
+                        if candidate <= start {
+                            continue;
+                        }
+                        // 夏令时跳过的时刻不存在，继续找下一个
+                        if let Some(dt) = Local.from_local_datetime(&candidate).earliest() {
+                            return Some(dt.timestamp());
+                        }
+                    }
+                }
+            }
+            date = date + Duration::days(1);
+        }
+        None
+    }
+
+    /// 按本地时间解释的 RRULE，BYHOUR/BYMINUTE 需配合浮动时间的 DTSTART 使用
+    pub fn to_rrule(&self) -> Option<String> {
+        // cron 中日与周同时限定时为“或”关系，RRULE 只能表达“且”
+        if self.day_restricted && self.weekday_restricted {
+            return None;
+        }
+        let join = |mask: u64| {
+            bits(mask)
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut parts = Vec::new();
+        if self.day_restricted || self.weekday_restricted {
+            parts.push("FREQ=MONTHLY".to_string());
+        } else {
+            parts.push("FREQ=DAILY".to_string());
+        }
+        if self.months != ALL_MONTHS {
+            parts.push(format!("BYMONTH={}", join(self.months)));
+        }
+        if self.day_restricted {
+            let mut days: Vec<String> = bits(self.days).map(|day| day.to_string()).collect();
+            if self.last_day {
+                days.push("-1".to_string());
+            }
+            parts.push(format!("BYMONTHDAY={}", days.join(",")));
+        }
+        if self.weekday_restricted {
+            let days: Vec<String> = bits(self.weekdays)
+                .map(|day| RRULE_WEEKDAYS[day as usize].to_string())
+                .chain(
+                    self.nth_weekdays
+                        .iter()
+                        .map(|(day, nth)| format!("{}{}", nth, RRULE_WEEKDAYS[*day as usize])),
+                )
+                .collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        parts.push(format!("BYHOUR={}", join(self.hours)));
+        parts.push(format!("BYMINUTE={}", join(self.minutes)));
+        Some(parts.join(";"))
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day_match = self.days & (1 << date.day()) != 0
+            || (self.last_day && date.day() == date.num_days_in_month() as u32);
+        let weekday = date.weekday().num_days_from_sunday();
+        let nth = (date.day() - 1) / 7 + 1;
+        let weekday_match = self.weekdays & (1 << weekday) != 0
+            || self
+                .nth_weekdays
+                .iter()
+                .any(|(w, n)| *w == weekday && *n == nth);
+        // 与标准 cron 一致：日与周同时限定时满足其一即可
+        match (self.day_restricted, self.weekday_restricted) {
+            (true, true) => day_match || weekday_match,
+            (true, false) => day_match,
+            (false, true) => weekday_match,
+            (false, false) => true,
+        }
+    }
+}
+
+fn bits(mask: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |bit| mask & (1 << bit) != 0)
+}
+
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str]) -> Result<u32> {
+    let number = match names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+    {
+        // 月份名从 1 开始，星期名从 0（周日）开始
+        Some(index) => index as u32 + if min == 1 { 1 } else { 0 },
+        None => value
+            .parse()
+            .map_err(|_| anyhow!("cron 字段值无效: {}", value))?,
+    };
+    if number < min || number > max {
+        bail!("cron 字段值 {} 超出范围 {}-{}", value, min, max);
+    }
+    Ok(number)
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, parse_value(step, 1, max.max(1), &[])?),
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, max, names)?,
+                parse_value(end, min, max, names)?,
+            )
+        } else {
+            let start = parse_value(range, min, max, names)?;
+            // `5/15` 表示从 5 开始每 15 个单位
+            (start, if item.contains('/') { max } else { start })
+        };
+        if start > end {
+            bail!("cron 范围无效: {}", item);
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_ts(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn next(rule: &str, from: i64) -> i64 {
+        Recurrence::parse(rule).unwrap().next_after(from)
+    }
+
+    #[test]
+    fn every_two_hours_by_cron_and_interval() {
+        let from = local_ts(2025, 6, 2, 9, 30);
+        assert_eq!(next("0 */2 * * *", from), local_ts(2025, 6, 2, 10, 0));
+        assert_eq!(next("@every 2h", from), from + 2 * 3600);
+    }
+
+    #[test]
+    fn weekdays_at_nine_skip_weekend() {
+        // 2025-06-06 是周五
+        let from = local_ts(2025, 6, 6, 9, 0);
+        assert_eq!(next("0 9 * * 1-5", from), local_ts(2025, 6, 9, 9, 0));
+        assert_eq!(next("0 9 * * MON-FRI", from), local_ts(2025, 6, 9, 9, 0));
+    }
+
+    #[test]
+    fn third_friday_and_last_day_of_month() {
+        let from = local_ts(2025, 6, 21, 0, 0);
+        // 2025 年 7 月的第三个周五是 18 日
+        assert_eq!(next("0 9 * * 5#3", from), local_ts(2025, 7, 18, 9, 0));
+        assert_eq!(next("30 18 L * *", from), local_ts(2025, 6, 30, 18, 30));
+        let february = local_ts(2025, 2, 1, 0, 0);
+        assert_eq!(next("30 18 L * *", february), local_ts(2025, 2, 28, 18, 30));
+    }
+
+    #[test]
+    fn first_at_or_after_keeps_interval_anchor() {
+        let anchor = local_ts(2025, 6, 1, 8, 0);
+        let every = Recurrence::parse("@every 3h").unwrap();
+        assert_eq!(
+            every.first_at_or_after(anchor, local_ts(2025, 6, 1, 12, 0)),
+            Some(local_ts(2025, 6, 1, 14, 0))
+        );
+        let fixed = Recurrence::new(1, None).unwrap();
+        assert_eq!(
+            fixed.first_at_or_after(anchor, local_ts(2025, 6, 3, 0, 0)),
+            Some(local_ts(2025, 6, 3, 8, 0))
+        );
+
+        // 启动时触发的周期没有下一次执行时间，起点晚于当前时间也不能死循环
+        let future = Local::now().timestamp() + 24 * 3600;
+        for interval in [0, 100] {
+            let startup = Recurrence::new(interval, None).unwrap();
+            assert_eq!(startup.first_at_or_after(anchor, future), None);
+        }
+    }
+
+    #[test]
+    fn rules_convert_to_rrule() {
+        let rrule = |rule: &str| Recurrence::parse(rule).unwrap().to_rrule();
+        assert_eq!(
+            rrule("0 9 * * 1-5").as_deref(),
+            Some("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYHOUR=9;BYMINUTE=0")
+        );
+        assert_eq!(
+            rrule("0 9 * * 5#3").as_deref(),
+            Some("FREQ=MONTHLY;BYDAY=3FR;BYHOUR=9;BYMINUTE=0")
+        );
+        assert_eq!(
+            rrule("30 18 L * *").as_deref(),
+            Some("FREQ=MONTHLY;BYMONTHDAY=-1;BYHOUR=18;BYMINUTE=30")
+        );
+        assert_eq!(
+            rrule("0 */6 * * *").as_deref(),
+            Some("FREQ=DAILY;BYHOUR=0,6,12,18;BYMINUTE=0")
+        );
+        assert_eq!(rrule("@every 2h").as_deref(), Some("FREQ=HOURLY;INTERVAL=2"));
+        assert_eq!(rrule("@every 90m").as_deref(), Some("FREQ=MINUTELY;INTERVAL=90"));
+        assert_eq!(rrule("0 9 1 * 1"), None);
+        assert_eq!(Recurrence::new(0, None).unwrap().to_rrule(), None);
+    }
+
+    #[test]
+    fn rrules_convert_to_periods() {
+        // 2025-06-06 09:30 是周五
+        let start = local_ts(2025, 6, 6, 9, 30);
+        let period = |rrule: &str| period_from_rrule(rrule, start).map_err(|e| e.to_string());
+        let custom = |rule: &str| Ok((CUSTOM_PERIOD_INTERVAL, Some(rule.to_string())));
+        assert_eq!(period("FREQ=DAILY"), Ok((1, None)));
+        assert_eq!(period("FREQ=WEEKLY;WKST=MO"), Ok((7, None)));
+        assert_eq!(period("FREQ=MONTHLY"), Ok((30, None)));
+        assert_eq!(period("FREQ=DAILY;INTERVAL=3"), custom("@every 3d"));
+        assert_eq!(period("FREQ=WEEKLY;BYDAY=MO,WE"), custom("30 9 * * 1,3"));
+        assert_eq!(period("FREQ=MONTHLY;BYDAY=3FR"), custom("30 9 * * 5#3"));
+        assert_eq!(period("FREQ=MONTHLY;BYMONTHDAY=-1;BYHOUR=18;BYMINUTE=0"), custom("0 18 L * *"));
+        assert_eq!(period("FREQ=YEARLY"), custom("30 9 6 6 *"));
+        assert_eq!(period("FREQ=HOURLY"), custom("30 * * * *"));
+        assert!(period("FREQ=DAILY;COUNT=5").is_err());
+        assert!(period("FREQ=MONTHLY;INTERVAL=2;BYDAY=MO").is_err());
+        assert!(period("FREQ=MONTHLY;BYDAY=-1FR").is_err());
+        assert!(period("FREQ=MONTHLY;BYMONTHDAY=1;BYDAY=MO").is_err());
+
+        // 与导出方向互逆
+        let rrule = Recurrence::parse("0 9 * * 1-5").unwrap().to_rrule().unwrap();
+        assert_eq!(period(&rrule), custom("0 9 * * 1,2,3,4,5"));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(Recurrence::parse("0 9 * *").is_err());
+        assert!(Recurrence::parse("61 * * * *").is_err());
+        assert!(Recurrence::parse("0 0 30 2 *").is_err());
+        assert!(Recurrence::parse("@every 0h").is_err());
+        assert!(Recurrence::parse("@every 9999999999999999w").is_err());
+        assert!(Recurrence::parse("@every 99999999999d").is_err());
+        assert!(Recurrence::new(CUSTOM_PERIOD_INTERVAL, None).is_err());
+        assert_eq!(Recurrence::new(7, None).unwrap(), Recurrence::Fixed(7));
+        assert!(Recurrence::new(2, None).is_err());
+    }
+}
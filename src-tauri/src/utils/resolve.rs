@@ -5,7 +5,7 @@ use tauri::App;
 #[cfg(desktop)]
 use crate::core::tray;
 use crate::{
-    config::Config, core::{handle, timer}, logging, logging_error, module::lightweight::auto_lightweight_mode_init, utils::{logging::Type, window_manager}
+    config::Config, core::{handle, timer}, logging, logging_error, module::{hotkey, ics_feed::IcsFeed, lightweight::auto_lightweight_mode_init, rest_api::RestApi}, utils::{logging::Type, window_manager}
 };
 
 pub static VERSION: OnceCell<String> = OnceCell::new();
@@ -67,6 +67,9 @@ pub async fn resolve_setup(app: &mut App) {
     // #[cfg(desktop)]
     logging_error!(Type::Tray, tray::Tray::global().update_part());
     logging_error!(Type::System, timer::Timer::global().init());
+    logging_error!(Type::Service, IcsFeed::global().apply_config());
+    logging_error!(Type::Service, RestApi::global().apply_config());
+    logging_error!(Type::Hotkey, hotkey::Hotkey::global().apply_config());
     auto_lightweight_mode_init();
     let elapsed = start_time.elapsed();
     logging!(
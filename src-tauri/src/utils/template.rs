@@ -0,0 +1,76 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    Local,
+};
+
+/// `{{date}}` 未指定格式时使用的日期格式
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 与具体动作无关的内置变量：`date:<格式>` 为当前时间，`env.<名称>` 为环境变量
+pub fn builtin_variable(name: &str) -> Option<String> {
+    if name == "date" {
+        return format_now(DEFAULT_DATE_FORMAT);
+    }
+    if let Some(format) = name.strip_prefix("date:") {
+        return format_now(format);
+    }
+    if let Some(key) = name.strip_prefix("env.") {
+        return std::env::var(key).ok();
+    }
+    None
+}
+
+/// 格式无效时返回 None，避免 chrono 在格式化时 panic
+fn format_now(format: &str) -> Option<String> {
+    let items = StrftimeItems::new(format).collect::<Vec<_>>();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return None;
+    }
+    Some(Local::now().format_with_items(items.into_iter()).to_string())
+}
+
+/// 替换文本中 `{{ 名称 }}` 形式的变量，`lookup` 返回 None 的变量保持原样
+pub fn render(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len;
+        rendered.push_str(&rest[..start]);
+        match lookup(rest[start + 2..end].trim()) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_variables_are_replaced() {
+        let lookup = |name: &str| (name == "prev.stdout").then(|| "v1.2".to_string());
+        assert_eq!(render("tag {{prev.stdout}}", lookup), "tag v1.2");
+        assert_eq!(render("{{ prev.stdout }}-{{prev.stdout}}", lookup), "v1.2-v1.2");
+        assert_eq!(render("{{unknown}} {{prev.stdout", lookup), "{{unknown}} {{prev.stdout");
+    }
+
+    #[test]
+    fn builtin_variables_cover_date_and_env() {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(builtin_variable("date"), Some(today.clone()));
+        assert_eq!(builtin_variable("date:%Y-%m-%d"), Some(today));
+        assert_eq!(builtin_variable("date:%Q"), None);
+
+        std::env::set_var("DUCKER_TEMPLATE_TEST", "value");
+        assert_eq!(builtin_variable("env.DUCKER_TEMPLATE_TEST"), Some("value".to_string()));
+        assert_eq!(builtin_variable("env.DUCKER_TEMPLATE_MISSING"), None);
+        assert_eq!(builtin_variable("task.name"), None);
+    }
+}
@@ -6,7 +6,7 @@ import {
 } from '@tauri-apps/plugin-notification';
 
 
-import type { Action, CreateActionData, UpdateActionData } from '@/types';
+import type { Action, CreateActionData, RunningAction, UpdateActionData } from '@/types';
 async function execute_actions(actions: Action[] | undefined) {
     try {
         if (!actions || actions.length === 0) {
@@ -64,11 +64,21 @@ async function get_all_actions() {
     }
 }
 
+async function get_running_actions(): Promise<RunningAction[]> {
+    return await invoke<RunningAction[]>('get_running_actions');
+}
+
+async function cancel_action_run(runId: string): Promise<void> {
+    return await invoke<void>('cancel_action_run', { runId });
+}
+
 export {
     execute_actions,
     execute_single_action,
     create_action,
     update_action,
     delete_action,
-    get_all_actions
+    get_all_actions,
+    get_running_actions,
+    cancel_action_run
 }
\ No newline at end of file
@@ -26,6 +26,12 @@ async function update_task_status(id: string, completed: boolean): Promise<Task>
     return result;
 }
 
+// 稍后提醒，返回新的提醒时间
+async function snooze_task_reminder(id: string, minutes?: number): Promise<string> {
+    const result = await invoke<string>("snooze_task_reminder", { id, minutes });
+    return result;
+}
+
 async function delete_task(id: string): Promise<void> {
     const result = await invoke<void>("delete_task", { id });
     return result;
@@ -114,6 +120,7 @@ export {
     get_tasks,
     update_task,
     update_task_status,
+    snooze_task_reminder,
     delete_task,
     // 周期任务相关导出
     create_periodic_task,
@@ -10,12 +10,13 @@ import { Card, CardAction, CardContent, CardHeader, CardTitle, } from "@/compone
 import { Select, SelectContent, SelectGroup, SelectItem, SelectLabel, SelectTrigger, SelectValue, } from "@/components/ui/select"
 import { Button } from '@/components/ui/button';
 import { Textarea } from '@/components/ui/textarea';
+import { Switch } from '@/components/ui/switch';
 import { toast } from "sonner"
 
 import { useActionStore } from '@/store';
 import { open_file_select_dialog } from '@/utils';
 import { ActionIcon } from '@/utils/icon';
-import type { Action, ActionTypeValue } from '@/types';
+import type { Action, ActionTypeValue, GroupOptions } from '@/types';
 import ActionSelect from '@/components/Action/ActionSelect';
 import {
   ArrowLeft, FolderOpen, Plus, ChevronUp, ChevronDown, X, TriangleAlert,
@@ -37,6 +38,9 @@ const formSchema = z.object({
     wait: z.coerce.number().min(0, "Wait time must be non-negative"),
     retry: z.coerce.number().min(0, "Retry count must be non-negative").max(10, "Retry count must be less than 10").optional(),
     timeout: z.coerce.number().min(0, "Timeout must be non-negative").optional(),
+    group_mode: z.enum(["sequential", "parallel"]).optional(),
+    max_parallel: z.coerce.number().int().min(0, "Max parallel must be non-negative").optional(),
+    stop_on_failure: z.boolean().optional(),
 }).refine((data) => {
     // 非Group类型必须有command
     if (data.type !== "group" && (!data.command || data.command.trim() === "")) {
@@ -76,6 +80,39 @@ const formSchema = z.object({
     path: ["args"],
 })
 
+// 命令参数每行一个，通知正文整体作为一个参数，组合动作为逗号分隔的动作 ID
+const argsToText = (type: ActionTypeValue, args?: string[]) =>
+    args?.join(type === "group" ? "," : "\n") || "";
+
+const textToArgs = (type: ActionTypeValue, text?: string): string[] | undefined => {
+    if (!text) return undefined;
+    if (type === "notice") return [text];
+    const args = type === "group"
+        ? text.split(",").map(arg => arg.trim()).filter(arg => arg.length > 0)
+        : text.split("\n").filter(arg => arg.length > 0);
+    return args.length > 0 ? args : undefined;
+};
+
+// 旧数据中组合动作的 command 为占位符 "group"，按默认的依次执行处理
+const parseGroupOptions = (command?: string): GroupOptions => {
+    try {
+        const options = JSON.parse(command || "{}");
+        return typeof options === "object" && options !== null ? options : {};
+    } catch {
+        return {};
+    }
+};
+
+const groupOptionsToCommand = (values: { group_mode?: string, max_parallel?: number, stop_on_failure?: boolean }) => {
+    const parallel = values.group_mode === "parallel";
+    const options: GroupOptions = {
+        mode: parallel ? "parallel" : "sequential",
+        max_parallel: parallel && values.max_parallel ? values.max_parallel : undefined,
+        stop_on_failure: values.stop_on_failure ?? false,
+    };
+    return JSON.stringify(options);
+};
+
 const ActionModify: React.FC = () => {
     const [currentActionType, setCurrentActionType] = useState<ActionTypeValue>("file");
     const [isSubmitting, setIsSubmitting] = useState(false);
@@ -103,6 +140,9 @@ const ActionModify: React.FC = () => {
             wait: 0,
             retry: undefined,
             timeout: undefined,
+            group_mode: "sequential",
+            max_parallel: undefined,
+            stop_on_failure: false,
         },
     })
 
@@ -150,6 +190,9 @@ const ActionModify: React.FC = () => {
                 wait: 0,
                 retry: undefined,
                 timeout: undefined,
+                group_mode: "sequential" as const,
+                max_parallel: undefined,
+                stop_on_failure: false,
             };
             form.reset(defaultValues);
             setCurrentActionType("file");
@@ -159,13 +202,19 @@ const ActionModify: React.FC = () => {
                 name: currentAction.name,
                 type: currentAction.type,
                 command: currentAction.command || "",
-                args: currentAction.args?.join(",") || "",
+                args: argsToText(currentAction.type, currentAction.args),
                 desc: currentAction.desc || "",
                 wait: currentAction.wait,
                 retry: currentAction.retry,
                 timeout: currentAction.timeout,
             };
-            form.reset(formData);
+            const groupOptions = currentAction.type === "group" ? parseGroupOptions(currentAction.command) : {};
+            form.reset({
+                ...formData,
+                group_mode: groupOptions.mode ?? "sequential",
+                max_parallel: groupOptions.max_parallel,
+                stop_on_failure: groupOptions.stop_on_failure ?? false,
+            });
             setCurrentActionType(currentAction.type);
             
             // 如果是 group 类型，设置选中的 actions
@@ -194,17 +243,16 @@ const ActionModify: React.FC = () => {
         setIsSubmitting(true);
         
         try {
-            // 处理args字段
-            const args = values.args?.split(",").map(arg => arg.trim()).filter(arg => arg.length > 0);
-            
+            const args = textToArgs(values.type as ActionTypeValue, values.args);
+
             // 构建actionData，过滤掉undefined的可选字段
             const actionData: any = {
                 name: values.name,
                 desc: values.desc || "",
                 wait: values.wait,
                 type: values.type as Action['type'],
-                command: values.type === "group" ? "group" : values.command,
-                args: args && args.length > 0 ? args : undefined,
+                command: values.type === "group" ? groupOptionsToCommand(values) : values.command,
+                args,
             };
             
             // 只有当值存在且大于0时才添加retry和timeout
@@ -250,7 +298,7 @@ const ActionModify: React.FC = () => {
                     commandLabel: "Notice Title",
                     commandPlaceholder: "Enter notice title",
                     argsLabel: "Notice Body",
-                    argsPlaceholder: "Enter notice body (use comma for line breaks)"
+                    argsPlaceholder: "Enter notice body"
                 };
             case "group":
                 return {
@@ -264,7 +312,7 @@ const ActionModify: React.FC = () => {
                     commandLabel: "Command",
                     commandPlaceholder: "Enter command",
                     argsLabel: "Arguments",
-                    argsPlaceholder: "Enter arguments (comma separated)"
+                    argsPlaceholder: "Enter arguments (one per line)"
                 };
             case "url":
                 return {
@@ -437,6 +485,62 @@ const ActionModify: React.FC = () => {
                                     )}
                                 />
                             )}
+                            {currentActionType === "group" && (
+                                <div className='grid gap-4 grid-cols-3 w-5/6 mb-2'>
+                                    <FormField
+                                        name="group_mode"
+                                        control={form.control}
+                                        render={({ field }) => (
+                                            <FormItem>
+                                                <FormLabel className="block text-sm font-medium text-gray-700">Execution Mode</FormLabel>
+                                                <Select value={field.value || "sequential"} onValueChange={field.onChange}>
+                                                    <FormControl>
+                                                        <SelectTrigger className="w-full">
+                                                            <SelectValue />
+                                                        </SelectTrigger>
+                                                    </FormControl>
+                                                    <SelectContent>
+                                                        <SelectItem value="sequential">Sequential</SelectItem>
+                                                        <SelectItem value="parallel">Parallel</SelectItem>
+                                                    </SelectContent>
+                                                </Select>
+                                            </FormItem>
+                                        )}
+                                    />
+                                    <FormField
+                                        name="max_parallel"
+                                        control={form.control}
+                                        render={({ field }) => (
+                                            <FormItem>
+                                                <FormLabel className="block text-sm font-medium text-gray-700">Max Parallel</FormLabel>
+                                                <FormControl>
+                                                    <Input
+                                                        {...field}
+                                                        value={field.value ?? ""}
+                                                        type="number"
+                                                        min={0}
+                                                        placeholder="Unlimited"
+                                                        disabled={form.watch("group_mode") !== "parallel"}
+                                                    />
+                                                </FormControl>
+                                                <FormMessage />
+                                            </FormItem>
+                                        )}
+                                    />
+                                    <FormField
+                                        name="stop_on_failure"
+                                        control={form.control}
+                                        render={({ field }) => (
+                                            <FormItem>
+                                                <FormLabel className="block text-sm font-medium text-gray-700">Stop On Failure</FormLabel>
+                                                <FormControl>
+                                                    <Switch checked={field.value ?? false} onCheckedChange={field.onChange} />
+                                                </FormControl>
+                                            </FormItem>
+                                        )}
+                                    />
+                                </div>
+                            )}
                             {(currentActionType === "command" || currentActionType === "notice" || currentActionType === "group") && (
                                 <FormField
                                     name="args"
@@ -447,7 +551,7 @@ const ActionModify: React.FC = () => {
                                                 {fieldLabels.argsLabel}
                                             </FormLabel>
                                             <FormControl>
-                                                {currentActionType === "notice" ? (
+                                                {currentActionType === "notice" || currentActionType === "command" ? (
                                                     <Textarea
                                                         {...field}
                                                         placeholder={fieldLabels.argsPlaceholder}
@@ -0,0 +1,41 @@
+import { useEffect } from "react";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import { toast } from "sonner";
+import { snooze_task_reminder, update_task_status } from "@/api";
+import type { Task } from "@/types";
+
+// 后端触发任务提醒时发送到主窗口的事件
+const TASK_REMINDER_EVENT = "task-reminder";
+
+// 监听任务提醒，展示带「稍后提醒」「完成」按钮的提醒，直到用户选择其一或关闭
+export const useTaskReminder = () => {
+    useEffect(() => {
+        const unlisten = getCurrentWebviewWindow().listen<Task>(TASK_REMINDER_EVENT, (event) => {
+            const task = event.payload;
+            toast(task.name, {
+                id: `task-reminder-${task.id}`,
+                description: task.due_to ? `截止时间: ${task.due_to}` : "任务提醒",
+                duration: Infinity,
+                cancel: {
+                    label: "稍后提醒",
+                    onClick: () => {
+                        snooze_task_reminder(task.id)
+                            .then((reminder) => toast.success(`将于 ${reminder} 再次提醒`))
+                            .catch((err) => toast.error(`推迟提醒失败: ${err}`));
+                    },
+                },
+                action: {
+                    label: "完成",
+                    onClick: () => {
+                        update_task_status(task.id, true)
+                            .then(() => toast.success("任务已完成"))
+                            .catch((err) => toast.error(`完成任务失败: ${err}`));
+                    },
+                },
+            });
+        });
+        return () => {
+            unlisten.then((fn) => fn()).catch(console.warn);
+        };
+    }, []);
+};
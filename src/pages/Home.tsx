@@ -1,5 +1,6 @@
 import { type FC } from 'react';
 import Live2DModelComponent from '@/components/Live2D';
+import { useTaskReminder } from '@/hooks/use-task-reminder';
 
 
 const Live2D: FC = () => {
@@ -14,6 +15,9 @@ const Live2D: FC = () => {
 
 
 const Home: FC = () => {
+  // 主窗口常驻置顶，任务提醒的操作按钮在这里展示
+  useTaskReminder();
+
   return (
     <div className="home-page" >
       <div className="live2d-wrapper" data-tauri-drag-region>
@@ -8,6 +8,31 @@ export interface Action {
     timeout?: number;
     command?: string;
     args?: string[];
+    cwd?: string;
+    env?: Record<string, string>;
+    stdin?: string;
+    output_file?: string;
+    trust?: TrustLevel;
+    retry_policy?: RetryPolicy;
+}
+
+// 组合动作的执行方式，以 JSON 保存在 command 中
+export interface GroupOptions {
+    mode?: 'sequential' | 'parallel';
+    max_parallel?: number;
+    stop_on_failure?: boolean;
+}
+
+// 命令动作的执行策略：直接执行、每次确认或禁止执行
+export type TrustLevel = 'always' | 'confirm' | 'deny';
+
+// 重试的等待方式与条件
+export interface RetryPolicy {
+    backoff?: 'fixed' | 'linear' | 'exponential';
+    delay_ms?: number;
+    max_delay_ms?: number;
+    jitter?: boolean;
+    retry_on?: 'failure' | 'timeout' | { exit_codes: number[] };
 }
 
 // Action数据操作类型 - 使用工具类型优化
@@ -68,6 +93,25 @@ export interface ActionResult {
     exitCode?: number
 }
 
+// 前台命令逐行输出的 action-output 事件
+export interface ActionOutputEvent {
+    run_id: string
+    action_id?: string
+    stream: 'stdout' | 'stderr'
+    line: string
+}
+
+// 正在执行的命令
+export interface RunningAction {
+    run_id: string
+    pid: number
+    action_id?: string
+    action_name: string
+    task_id?: string
+    started_at: number
+    detached: boolean
+}
+
 // Action执行上下文
 export interface ActionContext {
     variables: Record<string, any>
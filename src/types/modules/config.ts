@@ -2,6 +2,14 @@ interface Config {
     enable_auto_launch?: boolean
     silent_launch?: boolean
     language?: string
+    hotkeys?: Record<string, HotkeyCommand>
 }
 
-export type { Config }
\ No newline at end of file
+// 全局快捷键触发的操作
+type HotkeyCommand =
+    | { type: 'toggle_window'; window: string }
+    | { type: 'quick_add_task' }
+    | { type: 'run_action'; action_id: string }
+    | { type: 'complete_next_task' }
+
+export type { Config, HotkeyCommand }
\ No newline at end of file